@@ -0,0 +1,264 @@
+use super::clip::AnimationClip;
+use super::controller::AnimationController;
+use super::skeleton::{Pose, Skeleton};
+use cgmath::Matrix4;
+use model::Model;
+use std::sync::Arc;
+
+/// Plays back animation clips on a skeleton and on morph target weights.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    skeleton: Option<Arc<Skeleton>>,
+    /// glTF node of the animated mesh, which morph weight channels refer to.
+    node: Option<usize>,
+    clip: Option<Arc<AnimationClip>>,
+    /// Drives the pose instead of the clip, if set.
+    controller: Option<AnimationController>,
+    /// Current time in the clip, in seconds.
+    pub time: f32,
+    /// Playback speed, where 1 is normal speed.
+    pub speed: f32,
+    /// If the clip starts over when it reaches the end.
+    pub looping: bool,
+    pose: Pose,
+    palette: Vec<Matrix4<f32>>,
+    /// Morph target weights used where the current clip does not animate them.
+    base_weights: Vec<f32>,
+    weights: Vec<f32>,
+}
+
+impl Animator {
+    pub fn new(skeleton: Arc<Skeleton>) -> Animator {
+        Animator::build(Some(skeleton), None, Vec::new())
+    }
+
+    /// Build an animator for a model, if it is skinned or has morph targets.
+    pub fn for_model(model: &Model) -> Option<Animator> {
+        if model.skeleton.is_none() && model.weights.is_empty() {
+            return None;
+        }
+
+        Some(Animator::build(
+            model.skeleton.clone(),
+            model.node,
+            model.weights.clone(),
+        ))
+    }
+
+    fn build(
+        skeleton: Option<Arc<Skeleton>>,
+        node: Option<usize>,
+        weights: Vec<f32>,
+    ) -> Animator {
+        let mut palette = Vec::new();
+
+        let pose = match skeleton {
+            Some(ref skeleton) => {
+                let pose = skeleton.rest_pose();
+                skeleton.palette(&pose, &mut palette);
+                pose
+            }
+            None => Pose { transforms: Vec::new() },
+        };
+
+        Animator {
+            skeleton: skeleton,
+            node: node,
+            clip: None,
+            controller: None,
+            time: 0.0,
+            speed: 1.0,
+            looping: true,
+            pose: pose,
+            palette: palette,
+            base_weights: weights.clone(),
+            weights: weights,
+        }
+    }
+
+    pub fn skeleton(&self) -> Option<&Arc<Skeleton>> {
+        self.skeleton.as_ref()
+    }
+
+    /// Start playing the given clip from the beginning.
+    pub fn play(&mut self, clip: Arc<AnimationClip>) {
+        self.clip = Some(clip);
+        self.time = 0.0;
+    }
+
+    /// Stop playing, returning the skeleton to its rest pose on the next advance.
+    pub fn stop(&mut self) {
+        self.clip = None;
+        self.time = 0.0;
+    }
+
+    /// The clip being played, if any.
+    pub fn clip(&self) -> Option<&Arc<AnimationClip>> {
+        self.clip.as_ref()
+    }
+
+    /// Let a controller drive the pose, instead of a single clip.
+    ///
+    /// Morph target weights are still animated by the clip being played, if any.
+    pub fn set_controller(&mut self, controller: AnimationController) {
+        self.controller = Some(controller);
+    }
+
+    pub fn controller(&self) -> Option<&AnimationController> {
+        self.controller.as_ref()
+    }
+
+    pub fn controller_mut(&mut self) -> Option<&mut AnimationController> {
+        self.controller.as_mut()
+    }
+
+    /// Set the weight of a morph target, used unless the current clip animates it.
+    pub fn set_weight(&mut self, index: usize, weight: f32) {
+        if let Some(w) = self.base_weights.get_mut(index) {
+            *w = weight;
+        }
+
+        if let Some(w) = self.weights.get_mut(index) {
+            *w = weight;
+        }
+    }
+
+    /// Advance playback by the given number of seconds, and update the pose, joint palette and
+    /// morph target weights.
+    pub fn advance(&mut self, dt: f32) {
+        self.weights.clone_from(&self.base_weights);
+
+        if let Some(ref skeleton) = self.skeleton {
+            self.pose = skeleton.rest_pose();
+        }
+
+        if let Some(ref clip) = self.clip {
+            self.time += dt * self.speed;
+
+            if self.looping && clip.duration > 0.0 {
+                self.time %= clip.duration;
+
+                if self.time < 0.0 {
+                    self.time += clip.duration;
+                }
+            } else {
+                self.time = f32::max(0.0, f32::min(clip.duration, self.time));
+            }
+
+            if let (Some(skeleton), None) = (self.skeleton.as_ref(), self.controller.as_ref()) {
+                clip.sample(self.time, skeleton, &mut self.pose);
+            }
+
+            if let Some(node) = self.node {
+                clip.sample_weights(self.time, node, &mut self.weights);
+            }
+        }
+
+        if let Some(ref mut controller) = self.controller {
+            controller.update(dt * self.speed);
+            self.pose.clone_from(controller.pose());
+        }
+
+        if let Some(ref skeleton) = self.skeleton {
+            skeleton.palette(&self.pose, &mut self.palette);
+        }
+    }
+
+    /// The current pose.
+    pub fn pose(&self) -> &Pose {
+        &self.pose
+    }
+
+    /// Joint matrices for the current pose, in the order vertices refer to joints.
+    ///
+    /// Empty if there is no skeleton.
+    pub fn palette(&self) -> &[Matrix4<f32>] {
+        &self.palette
+    }
+
+    /// Current morph target weights.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use animation::clip::{Channel, ChannelValues};
+    use animation::sampler::{Interpolation, Sampler};
+    use animation::skeleton::{Joint, JointTransform};
+    use cgmath::{One, Vector3};
+
+    fn animator() -> Animator {
+        let skeleton = Skeleton::new(vec![
+            Joint {
+                name: None,
+                node: 0,
+                parent: None,
+                base: Matrix4::one(),
+                inverse_bind: Matrix4::one(),
+                rest: JointTransform::identity(),
+            },
+        ]);
+
+        let clip = AnimationClip::new(
+            None,
+            vec![
+                Channel {
+                    node: 0,
+                    values: ChannelValues::Translation(Sampler::new(
+                        Interpolation::Linear,
+                        vec![0.0, 1.0],
+                        vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0)],
+                    )),
+                },
+            ],
+        );
+
+        let mut animator = Animator::new(Arc::new(skeleton));
+        animator.play(Arc::new(clip));
+        animator
+    }
+
+    #[test]
+    fn test_looping() {
+        let mut animator = animator();
+        animator.advance(1.25);
+        assert!((animator.time - 0.25).abs() < 0.0001);
+        assert!((animator.pose().transforms[0].translation.x - 0.25).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_clamped() {
+        let mut animator = animator();
+        animator.looping = false;
+        animator.advance(3.0);
+        assert_eq!(1.0, animator.time);
+        assert_eq!(1, animator.palette().len());
+    }
+
+    #[test]
+    fn test_weights() {
+        let mut animator = Animator::build(None, Some(5), vec![0.0, 0.0]);
+
+        let clip = AnimationClip::new(
+            None,
+            vec![
+                Channel {
+                    node: 5,
+                    values: ChannelValues::Weights(vec![
+                        Sampler::new(Interpolation::Linear, vec![0.0, 1.0], vec![0.0, 1.0]),
+                    ]),
+                },
+            ],
+        );
+
+        animator.set_weight(1, 0.25);
+        animator.play(Arc::new(clip));
+        animator.advance(0.5);
+
+        assert_eq!(&[0.5, 0.25], animator.weights());
+        assert!(animator.palette().is_empty());
+    }
+}
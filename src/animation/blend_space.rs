@@ -0,0 +1,159 @@
+use super::clip::AnimationClip;
+use std::f32;
+use std::sync::Arc;
+
+/// Clips placed along a single parameter, like idle, walk and run placed by speed.
+#[derive(Debug, Clone)]
+pub struct BlendSpace1D {
+    /// Name of the parameter which picks the position in the blend space.
+    pub parameter: String,
+    /// Clips and their positions, in increasing order.
+    points: Vec<(f32, Arc<AnimationClip>)>,
+}
+
+impl BlendSpace1D {
+    pub fn new(parameter: &str) -> BlendSpace1D {
+        BlendSpace1D {
+            parameter: parameter.to_owned(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Place a clip at the given position.
+    pub fn add(&mut self, position: f32, clip: Arc<AnimationClip>) {
+        let index = self.points
+            .iter()
+            .position(|p| p.0 > position)
+            .unwrap_or(self.points.len());
+
+        self.points.insert(index, (position, clip));
+    }
+
+    pub fn clips(&self) -> Vec<&Arc<AnimationClip>> {
+        self.points.iter().map(|p| &p.1).collect()
+    }
+
+    /// Weight of each clip at the given position, in the order they are returned by `clips`.
+    ///
+    /// Blends between the two closest clips, clamping to the clips at either end.
+    pub fn weights(&self, value: f32) -> Vec<f32> {
+        let mut weights = vec![0.0; self.points.len()];
+
+        if self.points.is_empty() {
+            return weights;
+        }
+
+        let next = self.points.iter().position(|p| p.0 > value).unwrap_or(
+            self.points.len(),
+        );
+
+        if next == 0 {
+            weights[0] = 1.0;
+        } else if next == self.points.len() {
+            weights[next - 1] = 1.0;
+        } else {
+            let a = self.points[next - 1].0;
+            let b = self.points[next].0;
+            let t = (value - a) / (b - a);
+            weights[next - 1] = 1.0 - t;
+            weights[next] = t;
+        }
+
+        weights
+    }
+}
+
+/// Clips placed on a plane spanned by two parameters, like walking in different directions.
+#[derive(Debug, Clone)]
+pub struct BlendSpace2D {
+    /// Name of the parameter along the first axis.
+    pub x: String,
+    /// Name of the parameter along the second axis.
+    pub y: String,
+    points: Vec<([f32; 2], Arc<AnimationClip>)>,
+}
+
+impl BlendSpace2D {
+    pub fn new(x: &str, y: &str) -> BlendSpace2D {
+        BlendSpace2D {
+            x: x.to_owned(),
+            y: y.to_owned(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Place a clip at the given position.
+    pub fn add(&mut self, position: [f32; 2], clip: Arc<AnimationClip>) {
+        self.points.push((position, clip));
+    }
+
+    pub fn clips(&self) -> Vec<&Arc<AnimationClip>> {
+        self.points.iter().map(|p| &p.1).collect()
+    }
+
+    /// Weight of each clip at the given position, in the order they are returned by `clips`.
+    ///
+    /// Uses inverse distance weighting, so a position on top of a clip plays only that clip.
+    pub fn weights(&self, position: [f32; 2]) -> Vec<f32> {
+        let distances: Vec<f32> = self.points
+            .iter()
+            .map(|p| {
+                let dx = p.0[0] - position[0];
+                let dy = p.0[1] - position[1];
+                dx * dx + dy * dy
+            })
+            .collect();
+
+        if let Some(exact) = distances.iter().position(|d| *d < f32::EPSILON) {
+            let mut weights = vec![0.0; distances.len()];
+            weights[exact] = 1.0;
+            return weights;
+        }
+
+        let inverse: Vec<f32> = distances.iter().map(|d| 1.0 / d).collect();
+        let total: f32 = inverse.iter().sum();
+
+        inverse.into_iter().map(|w| w / total).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip() -> Arc<AnimationClip> {
+        Arc::new(AnimationClip::new(None, Vec::new()))
+    }
+
+    #[test]
+    fn test_1d() {
+        let mut space = BlendSpace1D::new("speed");
+        space.add(2.0, clip());
+        space.add(0.0, clip());
+        space.add(1.0, clip());
+
+        assert_eq!(vec![1.0, 0.0, 0.0], space.weights(-1.0));
+        assert_eq!(vec![0.0, 0.5, 0.5], space.weights(1.5));
+        assert_eq!(vec![0.0, 0.0, 1.0], space.weights(3.0));
+    }
+
+    #[test]
+    fn test_2d() {
+        let mut space = BlendSpace2D::new("x", "y");
+        space.add([0.0, 0.0], clip());
+        space.add([1.0, 0.0], clip());
+        space.add([0.0, 1.0], clip());
+
+        assert_eq!(vec![0.0, 1.0, 0.0], space.weights([1.0, 0.0]));
+
+        let weights = space.weights([0.5, 0.5]);
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 0.0001);
+        assert!((weights[1] - weights[2]).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(BlendSpace1D::new("speed").weights(1.0).is_empty());
+        assert!(BlendSpace2D::new("x", "y").weights([0.0, 0.0]).is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+use super::sampler::{Interpolation, Sampler};
+use super::skeleton::{Pose, Skeleton};
+use cgmath::{Quaternion, Vector3};
+use errors::*;
+use gltf::animation::{Animation, InterpolationAlgorithm, TrsProperty};
+use gltf_loader::Buffers;
+use gltf_utils::AccessorIter;
+
+/// Keyframes for one property of a node.
+#[derive(Debug, Clone)]
+pub enum ChannelValues {
+    Translation(Sampler<Vector3<f32>>),
+    Rotation(Sampler<Quaternion<f32>>),
+    Scale(Sampler<Vector3<f32>>),
+    /// Morph target weights, one sampler for each target.
+    Weights(Vec<Sampler<f32>>),
+}
+
+/// Animates a single property of a single node.
+#[derive(Debug, Clone)]
+pub struct Channel {
+    /// Index of the glTF node being animated.
+    pub node: usize,
+    pub values: ChannelValues,
+}
+
+/// A named animation, like walking or jumping.
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub name: Option<String>,
+    /// Length of the clip in seconds.
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl AnimationClip {
+    pub fn new(name: Option<String>, channels: Vec<Channel>) -> AnimationClip {
+        let duration = channels
+            .iter()
+            .map(|c| match c.values {
+                ChannelValues::Translation(ref s) => s.duration(),
+                ChannelValues::Rotation(ref s) => s.duration(),
+                ChannelValues::Scale(ref s) => s.duration(),
+                ChannelValues::Weights(ref s) => {
+                    s.iter().map(|s| s.duration()).fold(0.0, f32::max)
+                }
+            })
+            .fold(0.0, f32::max);
+
+        AnimationClip {
+            name: name,
+            duration: duration,
+            channels: channels,
+        }
+    }
+
+    /// Load an animation from glTF.
+    pub fn from_gltf(buffers: &Buffers, animation: &Animation) -> Result<AnimationClip> {
+        let mut channels = Vec::new();
+
+        for channel in animation.channels() {
+            let sampler = channel.sampler();
+            let target = channel.target();
+
+            let interpolation = match sampler.interpolation() {
+                InterpolationAlgorithm::Linear => Interpolation::Linear,
+                InterpolationAlgorithm::Step => Interpolation::Step,
+                InterpolationAlgorithm::CubicSpline => Interpolation::CubicSpline,
+                InterpolationAlgorithm::CatmullRomSpline => {
+                    return Err(ErrorKind::UnsupportedAnimation("catmull-rom interpolation").into());
+                }
+            };
+
+            let times: Vec<f32> = AccessorIter::new(sampler.input(), buffers).collect();
+            let output = sampler.output();
+
+            let values = match target.path() {
+                TrsProperty::Translation => {
+                    ChannelValues::Translation(Sampler::new(
+                        interpolation,
+                        times,
+                        AccessorIter::<[f32; 3]>::new(output, buffers)
+                            .map(Vector3::from)
+                            .collect(),
+                    ))
+                }
+                TrsProperty::Rotation => {
+                    ChannelValues::Rotation(Sampler::new(
+                        interpolation,
+                        times,
+                        AccessorIter::<[f32; 4]>::new(output, buffers)
+                            .map(|r| Quaternion::new(r[3], r[0], r[1], r[2]))
+                            .collect(),
+                    ))
+                }
+                TrsProperty::Scale => {
+                    ChannelValues::Scale(Sampler::new(
+                        interpolation,
+                        times,
+                        AccessorIter::<[f32; 3]>::new(output, buffers)
+                            .map(Vector3::from)
+                            .collect(),
+                    ))
+                }
+                TrsProperty::Weights => {
+                    let output: Vec<f32> = AccessorIter::new(output, buffers).collect();
+                    ChannelValues::Weights(weight_samplers(interpolation, times, output))
+                }
+            };
+
+            channels.push(Channel {
+                node: target.node().index(),
+                values: values,
+            });
+        }
+
+        Ok(AnimationClip::new(
+            animation.name().map(ToOwned::to_owned),
+            channels,
+        ))
+    }
+
+    /// Sample all channels at the given time into a pose.
+    ///
+    /// Joints which are not animated by the clip are left as they are in the pose.
+    pub fn sample(&self, time: f32, skeleton: &Skeleton, pose: &mut Pose) {
+        for channel in &self.channels {
+            let joint = match skeleton.joint_for_node(channel.node) {
+                Some(joint) => joint,
+                None => continue,
+            };
+
+            let transform = match pose.transforms.get_mut(joint) {
+                Some(transform) => transform,
+                None => continue,
+            };
+
+            match channel.values {
+                ChannelValues::Translation(ref s) => {
+                    if let Some(v) = s.sample(time) {
+                        transform.translation = v;
+                    }
+                }
+                ChannelValues::Rotation(ref s) => {
+                    if let Some(v) = s.sample(time) {
+                        transform.rotation = v;
+                    }
+                }
+                ChannelValues::Scale(ref s) => {
+                    if let Some(v) = s.sample(time) {
+                        transform.scale = v;
+                    }
+                }
+                ChannelValues::Weights(_) => {}
+            }
+        }
+    }
+
+    /// Sample the morph target weights of the given node at the given time.
+    ///
+    /// Weights which are not animated by the clip are left as they are.
+    pub fn sample_weights(&self, time: f32, node: usize, weights: &mut [f32]) {
+        for channel in &self.channels {
+            if channel.node != node {
+                continue;
+            }
+
+            if let ChannelValues::Weights(ref samplers) = channel.values {
+                for (weight, sampler) in weights.iter_mut().zip(samplers.iter()) {
+                    if let Some(v) = sampler.sample(time) {
+                        *weight = v;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Split interleaved weights into one sampler for each morph target.
+///
+/// Every keyframe holds one weight per target, or for cubic splines all in-tangents, followed
+/// by all weights and all out-tangents.
+fn weight_samplers(
+    interpolation: Interpolation,
+    times: Vec<f32>,
+    output: Vec<f32>,
+) -> Vec<Sampler<f32>> {
+    let per_key = match interpolation {
+        Interpolation::CubicSpline => 3,
+        _ => 1,
+    };
+
+    if times.is_empty() {
+        return Vec::new();
+    }
+
+    let count = output.len() / (times.len() * per_key);
+    let mut samplers = Vec::with_capacity(count);
+
+    for target in 0..count {
+        let mut values = Vec::with_capacity(times.len() * per_key);
+
+        for key in 0..times.len() {
+            for element in 0..per_key {
+                values.push(output[(key * per_key + element) * count + target]);
+            }
+        }
+
+        samplers.push(Sampler::new(interpolation, times.clone(), values));
+    }
+
+    samplers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use animation::skeleton::{Joint, JointTransform};
+    use cgmath::{Matrix4, One};
+
+    #[test]
+    fn test_sample_pose() {
+        let skeleton = Skeleton::new(vec![
+            Joint {
+                name: None,
+                node: 7,
+                parent: None,
+                base: Matrix4::one(),
+                inverse_bind: Matrix4::one(),
+                rest: JointTransform::identity(),
+            },
+        ]);
+
+        let clip = AnimationClip::new(
+            Some("walk".to_owned()),
+            vec![
+                Channel {
+                    node: 7,
+                    values: ChannelValues::Translation(Sampler::new(
+                        Interpolation::Linear,
+                        vec![0.0, 2.0],
+                        vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0)],
+                    )),
+                },
+                // channels for nodes outside of the skeleton are ignored.
+                Channel {
+                    node: 8,
+                    values: ChannelValues::Scale(Sampler::new(
+                        Interpolation::Step,
+                        vec![0.0, 4.0],
+                        vec![Vector3::new(1.0, 1.0, 1.0), Vector3::new(2.0, 2.0, 2.0)],
+                    )),
+                },
+            ],
+        );
+
+        assert_eq!(4.0, clip.duration);
+
+        let mut pose = skeleton.rest_pose();
+        clip.sample(1.0, &skeleton, &mut pose);
+        assert_eq!(Vector3::new(1.0, 0.0, 0.0), pose.transforms[0].translation);
+    }
+
+    #[test]
+    fn test_weight_samplers() {
+        let samplers = weight_samplers(
+            Interpolation::Linear,
+            vec![0.0, 1.0],
+            vec![0.0, 1.0, 1.0, 0.0],
+        );
+
+        assert_eq!(2, samplers.len());
+        assert_eq!(vec![0.0, 1.0], samplers[0].values);
+        assert_eq!(vec![1.0, 0.0], samplers[1].values);
+
+        let clip = AnimationClip::new(
+            None,
+            vec![
+                Channel {
+                    node: 3,
+                    values: ChannelValues::Weights(samplers),
+                },
+            ],
+        );
+
+        let mut weights = [0.0, 0.0, 0.7];
+        clip.sample_weights(0.5, 3, &mut weights);
+        assert_eq!([0.5, 0.5, 0.7], weights);
+    }
+
+    #[test]
+    fn test_cubic_weight_samplers() {
+        // in-tangents, values and out-tangents of two targets for a single keyframe.
+        let samplers = weight_samplers(
+            Interpolation::CubicSpline,
+            vec![0.0],
+            vec![0.1, 0.2, 1.0, 2.0, 0.3, 0.4],
+        );
+
+        assert_eq!(vec![0.1, 1.0, 0.3], samplers[0].values);
+        assert_eq!(vec![0.2, 2.0, 0.4], samplers[1].values);
+    }
+}
@@ -0,0 +1,188 @@
+use super::skeleton::{Pose, Skeleton};
+use super::state_machine::{Parameters, StateMachine};
+use std::sync::Arc;
+
+/// How a layer is combined with the layers below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerMode {
+    /// Blend towards the pose of the layer by its weight.
+    Override,
+    /// Add the difference between the pose of the layer and the rest pose, scaled by its weight.
+    Additive,
+}
+
+/// A state machine, combined with the layers below it.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub machine: StateMachine,
+    pub mode: LayerMode,
+    /// Influence of the layer, between 0 and 1.
+    pub weight: f32,
+}
+
+impl Layer {
+    pub fn new(machine: StateMachine, mode: LayerMode) -> Layer {
+        Layer {
+            machine: machine,
+            mode: mode,
+            weight: 1.0,
+        }
+    }
+}
+
+/// Evaluates layers of state machines into a single pose.
+///
+/// Layers are applied in order, so the first layer is the base which later layers modify, like
+/// waving a hand on top of walking.
+#[derive(Debug, Clone)]
+pub struct AnimationController {
+    skeleton: Arc<Skeleton>,
+    pub parameters: Parameters,
+    pub layers: Vec<Layer>,
+    pose: Pose,
+}
+
+impl AnimationController {
+    pub fn new(skeleton: Arc<Skeleton>) -> AnimationController {
+        let pose = skeleton.rest_pose();
+
+        AnimationController {
+            skeleton: skeleton,
+            parameters: Parameters::new(),
+            layers: Vec::new(),
+            pose: pose,
+        }
+    }
+
+    /// Add a layer on top of the existing ones.
+    pub fn add_layer(&mut self, layer: Layer) {
+        self.layers.push(layer);
+    }
+
+    pub fn set_parameter(&mut self, name: &str, value: f32) {
+        self.parameters.set(name, value);
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.parameters.set_bool(name, value);
+    }
+
+    /// Advance all layers by the given number of seconds, and evaluate the new pose.
+    pub fn update(&mut self, dt: f32) {
+        for layer in &mut self.layers {
+            layer.machine.update(dt, &self.parameters);
+        }
+
+        self.pose = self.evaluate();
+    }
+
+    /// The pose as of the last update.
+    pub fn pose(&self) -> &Pose {
+        &self.pose
+    }
+
+    /// Evaluate all layers into a pose.
+    pub fn evaluate(&self) -> Pose {
+        let rest = self.skeleton.rest_pose();
+        let mut pose = rest.clone();
+
+        for layer in &self.layers {
+            if layer.weight <= 0.0 {
+                continue;
+            }
+
+            let layer_pose = layer.machine.sample(&self.parameters, &self.skeleton);
+
+            match layer.mode {
+                LayerMode::Override => pose.blend(&layer_pose, layer.weight),
+                LayerMode::Additive => pose.add(&layer_pose, &rest, layer.weight),
+            }
+        }
+
+        pose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use animation::clip::{AnimationClip, Channel, ChannelValues};
+    use animation::sampler::{Interpolation, Sampler};
+    use animation::skeleton::{Joint, JointTransform};
+    use animation::state_machine::Motion;
+    use cgmath::{Matrix4, One, Vector3};
+
+    fn hold(translation: Vector3<f32>) -> Motion {
+        Motion::Clip(Arc::new(AnimationClip::new(
+            None,
+            vec![
+                Channel {
+                    node: 0,
+                    values: ChannelValues::Translation(Sampler::new(
+                        Interpolation::Step,
+                        vec![0.0, 1.0],
+                        vec![translation, translation],
+                    )),
+                },
+            ],
+        )))
+    }
+
+    fn controller() -> AnimationController {
+        let skeleton = Skeleton::new(vec![
+            Joint {
+                name: None,
+                node: 0,
+                parent: None,
+                base: Matrix4::one(),
+                inverse_bind: Matrix4::one(),
+                rest: JointTransform::identity(),
+            },
+        ]);
+
+        let mut base = StateMachine::new();
+        base.add_state("walk", hold(Vector3::new(2.0, 0.0, 0.0)));
+
+        let mut controller = AnimationController::new(Arc::new(skeleton));
+        controller.add_layer(Layer::new(base, LayerMode::Override));
+        controller
+    }
+
+    #[test]
+    fn test_additive_layer() {
+        let mut controller = controller();
+
+        let mut wave = StateMachine::new();
+        wave.add_state("wave", hold(Vector3::new(0.0, 1.0, 0.0)));
+
+        let mut layer = Layer::new(wave, LayerMode::Additive);
+        layer.weight = 0.5;
+        controller.add_layer(layer);
+
+        controller.update(0.1);
+
+        assert_eq!(
+            Vector3::new(2.0, 0.5, 0.0),
+            controller.pose().transforms[0].translation
+        );
+    }
+
+    #[test]
+    fn test_override_layer() {
+        let mut controller = controller();
+
+        let mut aim = StateMachine::new();
+        aim.add_state("aim", hold(Vector3::new(0.0, 0.0, 0.0)));
+
+        let mut layer = Layer::new(aim, LayerMode::Override);
+        layer.weight = 0.25;
+        controller.add_layer(layer);
+
+        controller.update(0.1);
+
+        assert_eq!(
+            Vector3::new(1.5, 0.0, 0.0),
+            controller.pose().transforms[0].translation
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! # Skeletal animation
+//!
+//! Skeletons and animation clips are loaded from glTF skins and animations through
+//! [GltfLoader](../gltf_loader/struct.GltfLoader.html).
+//! An [Animator](animator/struct.Animator.html) plays back a clip, producing a palette of joint
+//! matrices which is used to skin vertices on the GPU.
+//!
+//! For more than a single clip, an
+//! [AnimationController](controller/struct.AnimationController.html) moves between states and
+//! blends clips based on parameters, like the speed of the player.
+
+pub mod animator;
+pub mod blend_space;
+pub mod clip;
+pub mod controller;
+pub mod sampler;
+pub mod skeleton;
+pub mod state_machine;
+
+pub use self::animator::Animator;
+pub use self::blend_space::{BlendSpace1D, BlendSpace2D};
+pub use self::clip::{AnimationClip, Channel, ChannelValues};
+pub use self::controller::{AnimationController, Layer, LayerMode};
+pub use self::sampler::{Interpolate, Interpolation, Sampler};
+pub use self::skeleton::{Joint, JointTransform, Pose, Skeleton};
+pub use self::state_machine::{Condition, Motion, Parameters, State, StateMachine, Transition};
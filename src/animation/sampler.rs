@@ -0,0 +1,183 @@
+use cgmath::{InnerSpace, Quaternion, Vector3};
+use gfx::color::Color;
+use std::ops::{Add, Mul};
+
+/// How values are interpolated between keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Linear interpolation, spherical for rotations.
+    Linear,
+    /// The value of the previous keyframe is held until the next one.
+    Step,
+    /// Cubic hermite spline, where every keyframe has an in-tangent and an out-tangent.
+    CubicSpline,
+}
+
+/// Values which can be interpolated between keyframes.
+pub trait Interpolate: Copy + Add<Output = Self> + Mul<f32, Output = Self> {
+    /// Interpolate linearly towards another value.
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self * (1.0 - t) + other * t
+    }
+
+    /// Fix up a value which was combined from other values.
+    fn normalize(self) -> Self {
+        self
+    }
+}
+
+impl Interpolate for f32 {}
+
+impl Interpolate for Vector3<f32> {}
+
+impl Interpolate for Color {}
+
+impl Interpolate for Quaternion<f32> {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        // take the shortest path around the sphere.
+        let other = if self.dot(other) < 0.0 { -other } else { other };
+        self.nlerp(other, t)
+    }
+
+    fn normalize(self) -> Self {
+        InnerSpace::normalize(self)
+    }
+}
+
+/// Keyframes of a single animated property.
+#[derive(Debug, Clone)]
+pub struct Sampler<T> {
+    pub interpolation: Interpolation,
+    /// Time of each keyframe in seconds, in increasing order.
+    pub times: Vec<f32>,
+    /// Value of each keyframe.
+    ///
+    /// For cubic splines, each keyframe has three values: the in-tangent, the value and the
+    /// out-tangent.
+    pub values: Vec<T>,
+}
+
+impl<T: Interpolate> Sampler<T> {
+    pub fn new(interpolation: Interpolation, times: Vec<f32>, values: Vec<T>) -> Sampler<T> {
+        Sampler {
+            interpolation: interpolation,
+            times: times,
+            values: values,
+        }
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.times.last().cloned().unwrap_or(0.0)
+    }
+
+    /// Sample the value at the given time.
+    ///
+    /// Times outside of the keyframes are clamped to the first or last keyframe. Returns `None`
+    /// if there are no keyframes, or not enough values for them.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let count = self.times.len();
+
+        let expected = match self.interpolation {
+            Interpolation::CubicSpline => count * 3,
+            _ => count,
+        };
+
+        if count == 0 || self.values.len() < expected {
+            return None;
+        }
+
+        // index of the first keyframe after the given time.
+        let next = self.times.iter().position(|t| *t > time).unwrap_or(count);
+
+        if next == 0 {
+            return Some(self.value(0));
+        }
+
+        if next == count {
+            return Some(self.value(count - 1));
+        }
+
+        let previous = next - 1;
+        let t0 = self.times[previous];
+        let dt = self.times[next] - t0;
+        let t = if dt > 0.0 { (time - t0) / dt } else { 0.0 };
+
+        let value = match self.interpolation {
+            Interpolation::Step => self.value(previous),
+            Interpolation::Linear => self.value(previous).lerp(self.value(next), t),
+            Interpolation::CubicSpline => {
+                let p0 = self.values[previous * 3 + 1];
+                let m0 = self.values[previous * 3 + 2] * dt;
+                let p1 = self.values[next * 3 + 1];
+                let m1 = self.values[next * 3] * dt;
+
+                let t2 = t * t;
+                let t3 = t2 * t;
+
+                (p0 * (2.0 * t3 - 3.0 * t2 + 1.0) + m0 * (t3 - 2.0 * t2 + t) +
+                     p1 * (-2.0 * t3 + 3.0 * t2) + m1 * (t3 - t2))
+                    .normalize()
+            }
+        };
+
+        Some(value)
+    }
+
+    /// The value of the given keyframe, skipping tangents.
+    fn value(&self, index: usize) -> T {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.values[index * 3 + 1],
+            _ => self.values[index],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        let sampler = Sampler::new(Interpolation::Step, vec![0.0, 1.0], vec![1.0f32, 3.0]);
+        assert_eq!(Some(1.0), sampler.sample(0.5));
+        assert_eq!(Some(3.0), sampler.sample(1.0));
+    }
+
+    #[test]
+    fn test_linear_clamps() {
+        let sampler = Sampler::new(Interpolation::Linear, vec![1.0, 2.0], vec![1.0f32, 3.0]);
+        assert_eq!(Some(1.0), sampler.sample(0.0));
+        assert_eq!(Some(2.0), sampler.sample(1.5));
+        assert_eq!(Some(3.0), sampler.sample(5.0));
+    }
+
+    #[test]
+    fn test_cubic_spline() {
+        // zero tangents give a smooth step, which passes through the midpoint.
+        let sampler = Sampler::new(
+            Interpolation::CubicSpline,
+            vec![0.0, 1.0],
+            vec![0.0f32, 0.0, 0.0, 0.0, 2.0, 0.0],
+        );
+
+        assert_eq!(Some(1.0), sampler.sample(0.5));
+        assert!(sampler.sample(0.25).unwrap() < 0.5);
+    }
+
+    #[test]
+    fn test_rotation_is_normalized() {
+        let a = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let b = Quaternion::new(0.0, 0.0, 1.0, 0.0);
+        let sampler = Sampler::new(Interpolation::Linear, vec![0.0, 1.0], vec![a, b]);
+
+        let q = sampler.sample(0.5).unwrap();
+        assert!((q.magnitude() - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_empty() {
+        let sampler: Sampler<f32> = Sampler::new(Interpolation::Linear, vec![], vec![]);
+        assert_eq!(None, sampler.sample(0.0));
+    }
+}
@@ -0,0 +1,436 @@
+use super::sampler::Interpolate;
+use cgmath::{Matrix4, One, Quaternion, Rotation, Vector3};
+use errors::*;
+use gfx::MAX_JOINTS;
+use gltf::{Gltf, Node, Skin};
+use gltf_loader::Buffers;
+use gltf_utils::AccessorIter;
+use std::collections::HashMap;
+
+/// Translation, rotation and scale of a single joint, relative to its parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointTransform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl JointTransform {
+    pub fn identity() -> JointTransform {
+        JointTransform {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// The transform as a matrix, scaling first and translating last.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation) * Matrix4::from(self.rotation) *
+            Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    /// Blend towards another transform, where `t` is in [0, 1].
+    pub fn lerp(&self, other: &JointTransform, t: f32) -> JointTransform {
+        JointTransform {
+            translation: Interpolate::lerp(self.translation, other.translation, t),
+            rotation: Interpolate::lerp(self.rotation, other.rotation, t),
+            scale: Interpolate::lerp(self.scale, other.scale, t),
+        }
+    }
+
+    /// Add the difference between `transform` and `reference` on top of this transform,
+    /// scaled by `weight`.
+    pub fn add(
+        &self,
+        transform: &JointTransform,
+        reference: &JointTransform,
+        weight: f32,
+    ) -> JointTransform {
+        let translation = transform.translation - reference.translation;
+        let rotation = reference.rotation.invert() * transform.rotation;
+
+        let scale = Vector3::new(
+            ratio(transform.scale.x, reference.scale.x),
+            ratio(transform.scale.y, reference.scale.y),
+            ratio(transform.scale.z, reference.scale.z),
+        );
+
+        let one = Vector3::new(1.0, 1.0, 1.0);
+        let rotation = Interpolate::lerp(Quaternion::one(), rotation, weight);
+        let scale = Interpolate::lerp(one, scale, weight);
+
+        JointTransform {
+            translation: self.translation + translation * weight,
+            rotation: self.rotation * rotation,
+            scale: Vector3::new(
+                self.scale.x * scale.x,
+                self.scale.y * scale.y,
+                self.scale.z * scale.z,
+            ),
+        }
+    }
+}
+
+/// Ratio between two scales, where a zero reference scale is treated as no change.
+fn ratio(value: f32, reference: f32) -> f32 {
+    if reference == 0.0 { 1.0 } else { value / reference }
+}
+
+impl Default for JointTransform {
+    fn default() -> JointTransform {
+        JointTransform::identity()
+    }
+}
+
+/// A single joint of a skeleton.
+#[derive(Debug, Clone)]
+pub struct Joint {
+    pub name: Option<String>,
+    /// Index of the glTF node of the joint, which animation channels refer to.
+    pub node: usize,
+    /// Index of the parent joint, if any.
+    pub parent: Option<usize>,
+    /// Static transformation applied between the parent and the joint, from nodes in between
+    /// which are not joints themselves.
+    pub base: Matrix4<f32>,
+    /// Transforms vertices from mesh space into the space of the joint.
+    pub inverse_bind: Matrix4<f32>,
+    /// Transform of the joint when not animated.
+    pub rest: JointTransform,
+}
+
+/// A pose of a skeleton, one transform for each joint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    pub transforms: Vec<JointTransform>,
+}
+
+impl Pose {
+    /// Blend towards another pose, where `t` is in [0, 1].
+    ///
+    /// Joints missing from the other pose are left as they are.
+    pub fn blend(&mut self, other: &Pose, t: f32) {
+        for (a, b) in self.transforms.iter_mut().zip(other.transforms.iter()) {
+            *a = a.lerp(b, t);
+        }
+    }
+
+    /// Add the difference between `pose` and `reference` on top of this pose, scaled by
+    /// `weight`.
+    pub fn add(&mut self, pose: &Pose, reference: &Pose, weight: f32) {
+        let others = pose.transforms.iter().zip(reference.transforms.iter());
+
+        for (a, (b, r)) in self.transforms.iter_mut().zip(others) {
+            *a = a.add(b, r, weight);
+        }
+    }
+}
+
+/// A hierarchy of joints which skinned vertices are attached to.
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    /// Joints, in the order vertices refer to them.
+    joints: Vec<Joint>,
+    /// Joint indices in an order where each parent comes before its children.
+    order: Vec<usize>,
+    /// Joint index for each glTF node index.
+    nodes: HashMap<usize, usize>,
+    /// Change of basis applied to the joint matrices, since vertices are converted into the
+    /// coordinate system of the engine when they are loaded.
+    axes: Matrix4<f32>,
+}
+
+impl Skeleton {
+    /// Build a skeleton from joints.
+    ///
+    /// Joints with invalid or cyclic parents are treated as roots.
+    pub fn new(joints: Vec<Joint>) -> Skeleton {
+        let mut order = Vec::with_capacity(joints.len());
+        let mut visited = vec![false; joints.len()];
+
+        for index in 0..joints.len() {
+            // walk up to the first visited ancestor, and visit everything below it top-down.
+            let mut chain = Vec::new();
+            let mut current = Some(index);
+
+            while let Some(i) = current {
+                if i >= joints.len() || visited[i] || chain.contains(&i) {
+                    break;
+                }
+
+                chain.push(i);
+                current = joints[i].parent;
+            }
+
+            for i in chain.into_iter().rev() {
+                visited[i] = true;
+                order.push(i);
+            }
+        }
+
+        let nodes = joints
+            .iter()
+            .enumerate()
+            .map(|(index, joint)| (joint.node, index))
+            .collect();
+
+        Skeleton {
+            joints: joints,
+            order: order,
+            nodes: nodes,
+            axes: Matrix4::one(),
+        }
+    }
+
+    /// Load the skeleton of a glTF skin.
+    pub fn from_gltf(gltf: &Gltf, buffers: &Buffers, skin: &Skin) -> Result<Skeleton> {
+        let joint_nodes: Vec<Node> = skin.joints().collect();
+
+        // vertices index the joint palette directly, which can not grow past the shader's.
+        if joint_nodes.len() > MAX_JOINTS {
+            return Err(ErrorKind::TooManyJoints(joint_nodes.len()).into());
+        }
+
+        let inverse_binds: Vec<Matrix4<f32>> = match skin.inverse_bind_matrices() {
+            Some(accessor) => {
+                AccessorIter::<[[f32; 4]; 4]>::new(accessor, buffers)
+                    .map(Matrix4::from)
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let mut parents = HashMap::new();
+
+        for node in gltf.nodes() {
+            for child in node.children() {
+                parents.insert(child.index(), node.index());
+            }
+        }
+
+        let local: HashMap<usize, Matrix4<f32>> = gltf.nodes()
+            .map(|node| (node.index(), node_matrix(&node)))
+            .collect();
+
+        let joint_indices: HashMap<usize, usize> = joint_nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.index(), index))
+            .collect();
+
+        let mut joints = Vec::with_capacity(joint_nodes.len());
+
+        for (index, node) in joint_nodes.iter().enumerate() {
+            // fold in all ancestors up to the closest joint.
+            let mut base = Matrix4::one();
+            let mut parent = None;
+            let mut current = parents.get(&node.index()).cloned();
+
+            while let Some(n) = current {
+                if let Some(joint) = joint_indices.get(&n) {
+                    parent = Some(*joint);
+                    break;
+                }
+
+                base = local.get(&n).cloned().unwrap_or(Matrix4::one()) * base;
+                current = parents.get(&n).cloned();
+            }
+
+            let (matrix, rest) = node_transform(node);
+
+            joints.push(Joint {
+                name: node.name().map(ToOwned::to_owned),
+                node: node.index(),
+                parent: parent,
+                base: base * matrix,
+                inverse_bind: inverse_binds.get(index).cloned().unwrap_or(Matrix4::one()),
+                rest: rest,
+            });
+        }
+
+        let mut skeleton = Skeleton::new(joints);
+
+        // matches the conversion of vertex positions in `Model::from_gltf`.
+        skeleton.axes = Matrix4::new(
+            0.0, 0.0, -1.0, 0.0,
+            0.0, -1.0, 0.0, 0.0,
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+
+        Ok(skeleton)
+    }
+
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Index of the joint for the given glTF node, if the node is part of the skeleton.
+    pub fn joint_for_node(&self, node: usize) -> Option<usize> {
+        self.nodes.get(&node).cloned()
+    }
+
+    /// The pose where no joint is animated.
+    pub fn rest_pose(&self) -> Pose {
+        Pose { transforms: self.joints.iter().map(|j| j.rest).collect() }
+    }
+
+    /// Compute the joint matrices for the given pose, which transform vertices from their bind
+    /// pose into the posed mesh.
+    pub fn palette(&self, pose: &Pose, out: &mut Vec<Matrix4<f32>>) {
+        let mut globals = vec![Matrix4::one(); self.joints.len()];
+
+        for &index in &self.order {
+            let joint = &self.joints[index];
+
+            let local = pose.transforms.get(index).unwrap_or(&joint.rest).matrix();
+
+            let parent = joint
+                .parent
+                .and_then(|p| globals.get(p).cloned())
+                .unwrap_or(Matrix4::one());
+
+            globals[index] = parent * joint.base * local;
+        }
+
+        // the inverse of the change of basis is its transpose, since it only swaps and flips
+        // axes.
+        let inverse_axes = {
+            use cgmath::Matrix;
+            self.axes.transpose()
+        };
+
+        out.clear();
+
+        for (joint, global) in self.joints.iter().zip(globals.into_iter()) {
+            out.push(self.axes * global * joint.inverse_bind * inverse_axes);
+        }
+    }
+}
+
+/// The static matrix and animatable transform of a node.
+///
+/// A node is either described by a matrix or by its translation, rotation and scale, where the
+/// other one is the identity.
+fn node_transform(node: &Node) -> (Matrix4<f32>, JointTransform) {
+    let t = node.translation();
+    let r = node.rotation();
+    let s = node.scale();
+
+    let transform = JointTransform {
+        translation: Vector3::new(t[0], t[1], t[2]),
+        rotation: Quaternion::new(r[3], r[0], r[1], r[2]),
+        scale: Vector3::new(s[0], s[1], s[2]),
+    };
+
+    let m = node.matrix();
+
+    let matrix = Matrix4::new(
+        m[0], m[1], m[2], m[3],
+        m[4], m[5], m[6], m[7],
+        m[8], m[9], m[10], m[11],
+        m[12], m[13], m[14], m[15],
+    );
+
+    (matrix, transform)
+}
+
+/// The full local matrix of a node.
+fn node_matrix(node: &Node) -> Matrix4<f32> {
+    let (matrix, transform) = node_transform(node);
+    matrix * transform.matrix()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{Deg, Rotation3, Transform, Point3};
+
+    fn joint(node: usize, parent: Option<usize>, translation: Vector3<f32>) -> Joint {
+        Joint {
+            name: None,
+            node: node,
+            parent: parent,
+            base: Matrix4::one(),
+            inverse_bind: Matrix4::one(),
+            rest: JointTransform {
+                translation: translation,
+                ..JointTransform::identity()
+            },
+        }
+    }
+
+    #[test]
+    fn test_order_parents_first() {
+        // children listed before their parents.
+        let skeleton = Skeleton::new(vec![
+            joint(10, Some(1), Vector3::new(0.0, 1.0, 0.0)),
+            joint(11, Some(2), Vector3::new(0.0, 1.0, 0.0)),
+            joint(12, None, Vector3::new(0.0, 1.0, 0.0)),
+        ]);
+
+        assert_eq!(vec![2, 1, 0], skeleton.order);
+        assert_eq!(Some(1), skeleton.joint_for_node(11));
+
+        let mut palette = Vec::new();
+        skeleton.palette(&skeleton.rest_pose(), &mut palette);
+
+        let p = palette[0].transform_point(Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(Point3::new(0.0, 3.0, 0.0), p);
+    }
+
+    #[test]
+    fn test_blend_and_add() {
+        let rest = JointTransform::identity();
+
+        let moved = JointTransform {
+            translation: Vector3::new(2.0, 0.0, 0.0),
+            ..JointTransform::identity()
+        };
+
+        let mut pose = Pose { transforms: vec![rest] };
+        pose.blend(&Pose { transforms: vec![moved] }, 0.5);
+        assert_eq!(Vector3::new(1.0, 0.0, 0.0), pose.transforms[0].translation);
+
+        pose.add(
+            &Pose { transforms: vec![moved] },
+            &Pose { transforms: vec![rest] },
+            0.5,
+        );
+
+        assert_eq!(Vector3::new(2.0, 0.0, 0.0), pose.transforms[0].translation);
+        assert_eq!(Vector3::new(1.0, 1.0, 1.0), pose.transforms[0].scale);
+    }
+
+    #[test]
+    fn test_cyclic_parents() {
+        let skeleton = Skeleton::new(vec![
+            joint(0, Some(1), Vector3::new(0.0, 0.0, 0.0)),
+            joint(1, Some(0), Vector3::new(0.0, 0.0, 0.0)),
+        ]);
+
+        assert_eq!(2, skeleton.order.len());
+    }
+
+    #[test]
+    fn test_rotated_parent() {
+        let mut skeleton = Skeleton::new(vec![
+            joint(0, None, Vector3::new(0.0, 0.0, 0.0)),
+            joint(1, Some(0), Vector3::new(1.0, 0.0, 0.0)),
+        ]);
+
+        skeleton.joints[1].inverse_bind = Matrix4::from_translation(Vector3::new(-1.0, 0.0, 0.0));
+
+        let mut pose = skeleton.rest_pose();
+        pose.transforms[0].rotation = Quaternion::from_angle_z(Deg(90.0));
+
+        let mut palette = Vec::new();
+        skeleton.palette(&pose, &mut palette);
+
+        // a vertex bound at the child joint follows it around the parent.
+        let p = palette[1].transform_point(Point3::new(1.0, 0.0, 0.0));
+        assert!((p.x - 0.0).abs() < 0.0001);
+        assert!((p.y - 1.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,493 @@
+use super::blend_space::{BlendSpace1D, BlendSpace2D};
+use super::clip::AnimationClip;
+use super::skeleton::{Pose, Skeleton};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Named values which drive transitions and blend spaces.
+///
+/// Booleans are stored as numbers, where anything but zero is set.
+#[derive(Debug, Clone, Default)]
+pub struct Parameters {
+    values: HashMap<String, f32>,
+}
+
+impl Parameters {
+    pub fn new() -> Parameters {
+        Parameters { values: HashMap::new() }
+    }
+
+    pub fn set(&mut self, name: &str, value: f32) {
+        self.values.insert(name.to_owned(), value);
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set(name, if value { 1.0 } else { 0.0 });
+    }
+
+    /// Value of the given parameter, zero if it has not been set.
+    pub fn get(&self, name: &str) -> f32 {
+        self.values.get(name).cloned().unwrap_or(0.0)
+    }
+
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name) != 0.0
+    }
+}
+
+/// What a state plays.
+#[derive(Debug, Clone)]
+pub enum Motion {
+    Clip(Arc<AnimationClip>),
+    BlendSpace1D(BlendSpace1D),
+    BlendSpace2D(BlendSpace2D),
+}
+
+impl Motion {
+    /// Clips of the motion, and the weight of each for the given parameters.
+    fn weighted_clips(&self, parameters: &Parameters) -> Vec<(&Arc<AnimationClip>, f32)> {
+        match *self {
+            Motion::Clip(ref clip) => vec![(clip, 1.0)],
+            Motion::BlendSpace1D(ref space) => {
+                let weights = space.weights(parameters.get(&space.parameter));
+                space.clips().into_iter().zip(weights).collect()
+            }
+            Motion::BlendSpace2D(ref space) => {
+                let position = [parameters.get(&space.x), parameters.get(&space.y)];
+                let weights = space.weights(position);
+                space.clips().into_iter().zip(weights).collect()
+            }
+        }
+    }
+
+    /// Length of the motion in seconds, blended the same way as the clips.
+    fn duration(&self, parameters: &Parameters) -> f32 {
+        self.weighted_clips(parameters)
+            .into_iter()
+            .map(|(clip, weight)| clip.duration * weight)
+            .sum()
+    }
+
+    /// Sample the motion at the given phase, where 0 is the start and 1 is the end.
+    ///
+    /// All clips of a blend space are kept in step, so that for example feet stay in sync.
+    pub fn sample(&self, phase: f32, parameters: &Parameters, skeleton: &Skeleton) -> Pose {
+        let mut result = skeleton.rest_pose();
+        let mut total = 0.0;
+
+        for (clip, weight) in self.weighted_clips(parameters) {
+            if weight <= 0.0 {
+                continue;
+            }
+
+            let mut pose = skeleton.rest_pose();
+            clip.sample(phase * clip.duration, skeleton, &mut pose);
+
+            // accumulating blends keep the result a weighted average of all poses.
+            total += weight;
+            result.blend(&pose, weight / total);
+        }
+
+        result
+    }
+}
+
+/// A state of the state machine.
+#[derive(Debug, Clone)]
+pub struct State {
+    pub name: String,
+    pub motion: Motion,
+    /// Playback speed, where 1 is normal speed.
+    pub speed: f32,
+    /// If the motion starts over when it reaches the end.
+    pub looping: bool,
+}
+
+/// Condition which has to hold for a transition to be taken.
+#[derive(Debug, Clone)]
+pub enum Condition {
+    /// The parameter is greater than the value.
+    Greater(String, f32),
+    /// The parameter is less than the value.
+    Less(String, f32),
+    /// The parameter is set.
+    IsSet(String),
+    /// The parameter is not set.
+    IsNotSet(String),
+    /// The motion of the current state has played to its end at least once.
+    Finished,
+}
+
+/// A transition between two states, taken when all of its conditions hold.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    /// State the transition is taken from, or `None` to take it from any other state.
+    pub from: Option<usize>,
+    pub to: usize,
+    pub conditions: Vec<Condition>,
+    /// Seconds spent cross-fading into the new state.
+    pub duration: f32,
+}
+
+impl Transition {
+    pub fn new(from: usize, to: usize, duration: f32) -> Transition {
+        Transition {
+            from: Some(from),
+            to: to,
+            conditions: Vec::new(),
+            duration: duration,
+        }
+    }
+
+    /// A transition which can be taken from any other state.
+    pub fn any(to: usize, duration: f32) -> Transition {
+        Transition {
+            from: None,
+            to: to,
+            conditions: Vec::new(),
+            duration: duration,
+        }
+    }
+
+    /// Add a condition to the transition.
+    pub fn when(mut self, condition: Condition) -> Transition {
+        self.conditions.push(condition);
+        self
+    }
+}
+
+/// Playback state of a single state.
+#[derive(Debug, Clone, Copy)]
+struct Playback {
+    state: usize,
+    /// Position in the motion, where 0 is the start and 1 is the end.
+    phase: f32,
+    finished: bool,
+}
+
+impl Playback {
+    fn new(state: usize) -> Playback {
+        Playback {
+            state: state,
+            phase: 0.0,
+            finished: false,
+        }
+    }
+}
+
+/// A cross-fade out of a previous state.
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    from: Playback,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Plays one state at a time, moving between states as parameters change.
+#[derive(Debug, Clone)]
+pub struct StateMachine {
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    current: Option<Playback>,
+    fade: Option<Fade>,
+}
+
+impl StateMachine {
+    pub fn new() -> StateMachine {
+        StateMachine {
+            states: Vec::new(),
+            transitions: Vec::new(),
+            current: None,
+            fade: None,
+        }
+    }
+
+    /// Add a looping state, returning its index.
+    ///
+    /// The first state added is where the state machine starts.
+    pub fn add_state(&mut self, name: &str, motion: Motion) -> usize {
+        let index = self.states.len();
+
+        self.states.push(State {
+            name: name.to_owned(),
+            motion: motion,
+            speed: 1.0,
+            looping: true,
+        });
+
+        if self.current.is_none() {
+            self.current = Some(Playback::new(index));
+        }
+
+        index
+    }
+
+    pub fn state_mut(&mut self, index: usize) -> Option<&mut State> {
+        self.states.get_mut(index)
+    }
+
+    pub fn add_transition(&mut self, transition: Transition) {
+        self.transitions.push(transition);
+    }
+
+    /// The current state, if there are any states.
+    pub fn current(&self) -> Option<&State> {
+        self.current.and_then(|c| self.states.get(c.state))
+    }
+
+    /// If the state machine is cross-fading between two states.
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Advance by the given number of seconds, taking the first transition whose conditions
+    /// hold.
+    pub fn update(&mut self, dt: f32, parameters: &Parameters) {
+        let mut current = match self.current {
+            Some(current) => current,
+            None => return,
+        };
+
+        current = self.advance(current, dt, parameters);
+
+        self.fade = match self.fade {
+            Some(mut fade) => {
+                fade.from = self.advance(fade.from, dt, parameters);
+                fade.elapsed += dt;
+
+                if fade.elapsed < fade.duration {
+                    Some(fade)
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+
+        let transition = self.find_transition(&current, parameters).map(
+            |t| (t.to, t.duration),
+        );
+
+        if let Some((to, duration)) = transition {
+            // an interrupted fade is dropped, and the new fade starts from the current state.
+            self.fade = if duration > 0.0 {
+                Some(Fade {
+                    from: current,
+                    elapsed: 0.0,
+                    duration: duration,
+                })
+            } else {
+                None
+            };
+
+            current = Playback::new(to);
+        }
+
+        self.current = Some(current);
+    }
+
+    /// Sample the current pose.
+    pub fn sample(&self, parameters: &Parameters, skeleton: &Skeleton) -> Pose {
+        let current = match self.current {
+            Some(current) => current,
+            None => return skeleton.rest_pose(),
+        };
+
+        let mut pose = self.sample_playback(&current, parameters, skeleton);
+
+        if let Some(fade) = self.fade {
+            let mut from = self.sample_playback(&fade.from, parameters, skeleton);
+            from.blend(&pose, fade.elapsed / fade.duration);
+            pose = from;
+        }
+
+        pose
+    }
+
+    fn sample_playback(
+        &self,
+        playback: &Playback,
+        parameters: &Parameters,
+        skeleton: &Skeleton,
+    ) -> Pose {
+        match self.states.get(playback.state) {
+            Some(state) => state.motion.sample(playback.phase, parameters, skeleton),
+            None => skeleton.rest_pose(),
+        }
+    }
+
+    fn advance(&self, mut playback: Playback, dt: f32, parameters: &Parameters) -> Playback {
+        let state = match self.states.get(playback.state) {
+            Some(state) => state,
+            None => return playback,
+        };
+
+        let duration = state.motion.duration(parameters);
+
+        if duration <= 0.0 {
+            playback.finished = true;
+            return playback;
+        }
+
+        playback.phase += dt * state.speed / duration;
+
+        if playback.phase >= 1.0 {
+            playback.finished = true;
+
+            playback.phase = if state.looping {
+                playback.phase % 1.0
+            } else {
+                1.0
+            };
+        }
+
+        playback
+    }
+
+    fn find_transition(&self, current: &Playback, parameters: &Parameters) -> Option<&Transition> {
+        self.transitions.iter().find(|t| {
+            let from = match t.from {
+                Some(from) => from == current.state,
+                None => t.to != current.state,
+            };
+
+            from &&
+                t.conditions.iter().all(|c| match *c {
+                    Condition::Greater(ref name, value) => parameters.get(name) > value,
+                    Condition::Less(ref name, value) => parameters.get(name) < value,
+                    Condition::IsSet(ref name) => parameters.is_set(name),
+                    Condition::IsNotSet(ref name) => !parameters.is_set(name),
+                    Condition::Finished => current.finished,
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use animation::clip::{Channel, ChannelValues};
+    use animation::sampler::{Interpolation, Sampler};
+    use animation::skeleton::{Joint, JointTransform};
+    use cgmath::{Matrix4, One, Vector3};
+
+    fn skeleton() -> Skeleton {
+        Skeleton::new(vec![
+            Joint {
+                name: None,
+                node: 0,
+                parent: None,
+                base: Matrix4::one(),
+                inverse_bind: Matrix4::one(),
+                rest: JointTransform::identity(),
+            },
+        ])
+    }
+
+    /// A clip holding the joint at the given x coordinate for one second.
+    fn hold(x: f32) -> Arc<AnimationClip> {
+        Arc::new(AnimationClip::new(
+            None,
+            vec![
+                Channel {
+                    node: 0,
+                    values: ChannelValues::Translation(Sampler::new(
+                        Interpolation::Step,
+                        vec![0.0, 1.0],
+                        vec![Vector3::new(x, 0.0, 0.0), Vector3::new(x, 0.0, 0.0)],
+                    )),
+                },
+            ],
+        ))
+    }
+
+    fn x(pose: &Pose) -> f32 {
+        pose.transforms[0].translation.x
+    }
+
+    #[test]
+    fn test_transition_and_fade() {
+        let skeleton = skeleton();
+        let mut parameters = Parameters::new();
+
+        let mut machine = StateMachine::new();
+        let idle = machine.add_state("idle", Motion::Clip(hold(0.0)));
+        let jump = machine.add_state("jump", Motion::Clip(hold(2.0)));
+
+        machine.add_transition(Transition::new(idle, jump, 1.0).when(
+            Condition::IsSet("jump".to_owned()),
+        ));
+
+        machine.update(0.1, &parameters);
+        assert_eq!("idle", machine.current().unwrap().name);
+
+        parameters.set_bool("jump", true);
+        machine.update(0.1, &parameters);
+        assert_eq!("jump", machine.current().unwrap().name);
+        assert!(machine.is_fading());
+        assert_eq!(0.0, x(&machine.sample(&parameters, &skeleton)));
+
+        machine.update(0.5, &parameters);
+        assert_eq!(1.0, x(&machine.sample(&parameters, &skeleton)));
+
+        machine.update(0.5, &parameters);
+        assert!(!machine.is_fading());
+        assert_eq!(2.0, x(&machine.sample(&parameters, &skeleton)));
+    }
+
+    #[test]
+    fn test_finished() {
+        let parameters = Parameters::new();
+
+        let mut machine = StateMachine::new();
+        let attack = machine.add_state("attack", Motion::Clip(hold(0.0)));
+        let idle = machine.add_state("idle", Motion::Clip(hold(0.0)));
+        machine.state_mut(attack).unwrap().looping = false;
+
+        machine.add_transition(Transition::new(attack, idle, 0.0).when(Condition::Finished));
+
+        machine.update(0.5, &parameters);
+        assert_eq!("attack", machine.current().unwrap().name);
+        machine.update(0.6, &parameters);
+        assert_eq!("idle", machine.current().unwrap().name);
+        assert!(!machine.is_fading());
+    }
+
+    #[test]
+    fn test_any_state() {
+        let mut parameters = Parameters::new();
+        parameters.set("health", 0.0);
+
+        let mut machine = StateMachine::new();
+        machine.add_state("idle", Motion::Clip(hold(0.0)));
+        let dead = machine.add_state("dead", Motion::Clip(hold(0.0)));
+
+        machine.add_transition(Transition::any(dead, 0.0).when(
+            Condition::Less("health".to_owned(), 1.0),
+        ));
+
+        machine.update(0.1, &parameters);
+        assert_eq!("dead", machine.current().unwrap().name);
+
+        // does not transition into itself.
+        machine.update(0.1, &parameters);
+        assert_eq!("dead", machine.current().unwrap().name);
+    }
+
+    #[test]
+    fn test_blend_space_state() {
+        let skeleton = skeleton();
+        let mut parameters = Parameters::new();
+
+        let mut space = BlendSpace1D::new("speed");
+        space.add(0.0, hold(0.0));
+        space.add(1.0, hold(4.0));
+
+        let mut machine = StateMachine::new();
+        machine.add_state("locomotion", Motion::BlendSpace1D(space));
+
+        parameters.set("speed", 0.25);
+        machine.update(0.1, &parameters);
+        assert_eq!(1.0, x(&machine.sample(&parameters, &skeleton)));
+    }
+}
@@ -0,0 +1,384 @@
+use errors::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+
+/// A reference-counted handle to a cached asset.
+///
+/// Cheap to clone, all clones refer to the same asset.
+pub struct Handle<T> {
+    key: Arc<String>,
+    value: Arc<T>,
+}
+
+impl<T> Handle<T> {
+    /// The key the asset is cached under, typically its path.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Share the asset with something which does not know about handles, like a primitive.
+    ///
+    /// The asset stays cached for as long as the returned reference is alive.
+    pub fn shared(&self) -> Arc<T> {
+        self.value.clone()
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        Handle {
+            key: self.key.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T> Deref for Handle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Handle {{ key: {:?} }}", self.key)
+    }
+}
+
+/// Assets of a single type, keyed by path or URI.
+///
+/// Cheap to clone, all clones share the same entries.
+pub struct AssetCache<T> {
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    entries: Mutex<HashMap<String, Entry<T>>>,
+    /// Signalled whenever a load finishes, successfully or not.
+    loaded: Condvar,
+}
+
+enum Entry<T> {
+    /// Someone is loading the asset, others wait for them instead of loading it again.
+    Loading,
+    Loaded(Weak<T>),
+}
+
+impl<T> AssetCache<T> {
+    pub fn new() -> AssetCache<T> {
+        AssetCache {
+            shared: Arc::new(Shared {
+                entries: Mutex::new(HashMap::new()),
+                loaded: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Get the asset with the given key, if it is loaded.
+    pub fn get(&self, key: &str) -> Result<Option<Handle<T>>> {
+        let entries = self.lock()?;
+        Ok(Self::lookup(&entries, key).map(|value| handle(key, value)))
+    }
+
+    /// Get the asset with the given key, loading it if it is not loaded.
+    ///
+    /// The cache is not locked while loading, so other assets can be loaded in the meantime.
+    /// Callers asking for an asset which is being loaded wait for that load to finish, and load
+    /// it themselves only if it failed.
+    pub fn get_or_load<F>(&self, key: &str, load: F) -> Result<Handle<T>>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        let mut entries = self.lock()?;
+
+        loop {
+            let loading = match entries.get(key) {
+                Some(&Entry::Loading) => true,
+                Some(&Entry::Loaded(ref value)) => {
+                    if let Some(value) = value.upgrade() {
+                        return Ok(handle(key, value));
+                    }
+
+                    false
+                }
+                None => false,
+            };
+
+            if !loading {
+                break;
+            }
+
+            entries = self.shared.loaded.wait(entries).map_err(
+                |_| ErrorKind::PoisonError,
+            )?;
+        }
+
+        entries.insert(key.to_string(), Entry::Loading);
+        drop(entries);
+
+        // clears the entry if loading fails or panics, so a waiting caller can try again.
+        let _pending = Pending {
+            cache: self,
+            key: key,
+        };
+
+        let value = load()?;
+        self.get_or_insert(key, value)
+    }
+
+    /// Get the asset with the given key, or insert the given value if it is not loaded.
+    ///
+    /// If someone else loaded the same asset in the meantime, the given value is discarded.
+    pub fn get_or_insert(&self, key: &str, value: T) -> Result<Handle<T>> {
+        let mut entries = self.lock()?;
+
+        if let Some(value) = Self::lookup(&entries, key) {
+            return Ok(handle(key, value));
+        }
+
+        Ok(Self::insert_entry(&mut entries, key, value))
+    }
+
+    /// Insert the given value, replacing any existing entry.
+    ///
+    /// Existing handles keep referring to the previous value.
+    pub fn insert(&self, key: &str, value: T) -> Result<Handle<T>> {
+        let mut entries = self.lock()?;
+        Ok(Self::insert_entry(&mut entries, key, value))
+    }
+
+    /// Remove the asset with the given key, so that it is loaded again the next time.
+    ///
+    /// Existing handles keep referring to the removed asset.
+    pub fn remove(&self, key: &str) -> Result<()> {
+        self.lock()?.remove(key);
+        Ok(())
+    }
+
+    /// Keys of all assets which are still loaded.
+    pub fn keys(&self) -> Result<Vec<String>> {
+        let mut entries = self.lock()?;
+        Self::purge(&mut entries);
+
+        Ok(
+            entries
+                .iter()
+                .filter(|&(_, entry)| Self::is_loaded(entry))
+                .map(|(key, _)| key.clone())
+                .collect(),
+        )
+    }
+
+    /// Number of assets which are still loaded.
+    pub fn len(&self) -> Result<usize> {
+        let mut entries = self.lock()?;
+        Self::purge(&mut entries);
+        Ok(entries.values().filter(|entry| Self::is_loaded(entry)).count())
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    fn lookup(entries: &HashMap<String, Entry<T>>, key: &str) -> Option<Arc<T>> {
+        match entries.get(key) {
+            Some(&Entry::Loaded(ref value)) => value.upgrade(),
+            _ => None,
+        }
+    }
+
+    fn is_loaded(entry: &Entry<T>) -> bool {
+        match *entry {
+            Entry::Loaded(_) => true,
+            Entry::Loading => false,
+        }
+    }
+
+    fn insert_entry(entries: &mut HashMap<String, Entry<T>>, key: &str, value: T) -> Handle<T> {
+        // released assets are only removed when the cache grows, keeping lookups cheap.
+        Self::purge(entries);
+
+        let value = Arc::new(value);
+        entries.insert(key.to_string(), Entry::Loaded(Arc::downgrade(&value)));
+        handle(key, value)
+    }
+
+    /// Remove all entries whose last handle has been dropped.
+    fn purge(entries: &mut HashMap<String, Entry<T>>) {
+        entries.retain(|_, entry| match *entry {
+            Entry::Loading => true,
+            Entry::Loaded(ref value) => value.upgrade().is_some(),
+        });
+    }
+
+    fn lock(&self) -> Result<MutexGuard<HashMap<String, Entry<T>>>> {
+        Ok(self.shared.entries.lock().map_err(|_| ErrorKind::PoisonError)?)
+    }
+}
+
+fn handle<T>(key: &str, value: Arc<T>) -> Handle<T> {
+    Handle {
+        key: Arc::new(key.to_string()),
+        value: value,
+    }
+}
+
+/// A load in progress, which wakes up waiting callers when it ends.
+struct Pending<'a, T: 'a> {
+    cache: &'a AssetCache<T>,
+    key: &'a str,
+}
+
+impl<'a, T> Drop for Pending<'a, T> {
+    fn drop(&mut self) {
+        // a poisoned lock already fails all waiting callers.
+        if let Ok(mut entries) = self.cache.shared.entries.lock() {
+            let loading = match entries.get(self.key) {
+                Some(&Entry::Loading) => true,
+                _ => false,
+            };
+
+            if loading {
+                entries.remove(self.key);
+            }
+        }
+
+        self.cache.shared.loaded.notify_all();
+    }
+}
+
+impl<T> Clone for AssetCache<T> {
+    fn clone(&self) -> AssetCache<T> {
+        AssetCache { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Default for AssetCache<T> {
+    fn default() -> AssetCache<T> {
+        AssetCache::new()
+    }
+}
+
+impl<T> fmt::Debug for AssetCache<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "AssetCache {{ }}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_deduplicate() {
+        let cache = AssetCache::new();
+        let loads = Cell::new(0);
+
+        let load = || {
+            loads.set(loads.get() + 1);
+            Ok(42)
+        };
+
+        let a = cache.get_or_load("a.png", &load).unwrap();
+        let b = cache.get_or_load("a.png", &load).unwrap();
+
+        assert_eq!(1, loads.get());
+        assert_eq!(42, *b);
+        assert!(Arc::ptr_eq(&a.shared(), &b.shared()));
+        assert_eq!("a.png", b.key());
+    }
+
+    #[test]
+    fn test_release_on_last_drop() {
+        let cache = AssetCache::new();
+
+        let a = cache.get_or_insert("a.png", 1).unwrap();
+        let b = a.clone();
+        let shared = a.shared();
+
+        drop(a);
+        drop(b);
+        assert_eq!(1, cache.len().unwrap());
+
+        drop(shared);
+        assert!(cache.get("a.png").unwrap().is_none());
+        assert_eq!(0, cache.len().unwrap());
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let cache = AssetCache::new();
+
+        let old = cache.get_or_insert("a.png", 1).unwrap();
+        assert_eq!(1, *cache.get_or_insert("a.png", 2).unwrap());
+
+        let new = cache.insert("a.png", 3).unwrap();
+        assert_eq!(1, *old);
+        assert_eq!(3, *new);
+        assert_eq!(3, *cache.get("a.png").unwrap().unwrap());
+    }
+
+    #[test]
+    fn test_wait_for_pending_load() {
+        let cache = AssetCache::new();
+        let loads = Arc::new(AtomicUsize::new(0));
+        let (started_tx, started_rx) = mpsc::channel();
+        let (finish_tx, finish_rx) = mpsc::channel::<()>();
+
+        let first = {
+            let (cache, loads) = (cache.clone(), loads.clone());
+
+            thread::spawn(move || {
+                cache
+                    .get_or_load("a.gltf", || {
+                        started_tx.send(()).unwrap();
+                        finish_rx.recv().unwrap();
+                        loads.fetch_add(1, Ordering::SeqCst);
+                        Ok(42)
+                    })
+                    .unwrap()
+            })
+        };
+
+        started_rx.recv().unwrap();
+
+        let second = {
+            let (cache, loads) = (cache.clone(), loads.clone());
+
+            thread::spawn(move || {
+                cache
+                    .get_or_load("a.gltf", || {
+                        loads.fetch_add(1, Ordering::SeqCst);
+                        Ok(0)
+                    })
+                    .unwrap()
+            })
+        };
+
+        finish_tx.send(()).unwrap();
+        let (first, second) = (first.join().unwrap(), second.join().unwrap());
+
+        assert_eq!(1, loads.load(Ordering::SeqCst));
+        assert_eq!(42, *second);
+        assert!(Arc::ptr_eq(&first.shared(), &second.shared()));
+    }
+
+    #[test]
+    fn test_load_again_after_failure() {
+        let cache = AssetCache::new();
+
+        let failed = cache.get_or_load("a.gltf", || Err("broken".into()));
+        assert!(failed.is_err());
+        assert_eq!(0, cache.len().unwrap());
+
+        assert_eq!(1, *cache.get_or_load("a.gltf", || Ok(1)).unwrap());
+    }
+}
@@ -0,0 +1,346 @@
+use super::cache::Handle;
+use super::loader::{AssetLoader, Pending};
+use super::manager::{model_key, path_key};
+use super::watcher::FileWatcher;
+use errors::*;
+use gfx::Gfx;
+use gltf_loader::GltfLoader;
+use model::Model;
+use scheduler::{Scheduler, SchedulerSetup};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use texture::Texture;
+
+/// Ticks between checking for changed files.
+const POLL_TICKS: u32 = 50;
+
+/// Something drawn using a model, which can have its model replaced without losing any other
+/// state, like its position.
+pub trait ModelObject {
+    fn replace_model(&self, model: Model) -> Result<()>;
+
+    fn clone_model_object(&self) -> Box<ModelObject>;
+}
+
+/// A model which is reloaded when its files change.
+struct TrackedModel {
+    path: PathBuf,
+    node: String,
+    model: Handle<Model>,
+    objects: Vec<Box<ModelObject>>,
+}
+
+/// A reloaded document, with the reloaded models by their index among the tracked models.
+type Reloaded = (Handle<GltfLoader>, Vec<(usize, Option<Model>)>);
+
+/// A document which is being imported again on a worker thread.
+struct Reload {
+    key: String,
+    pending: Pending<Reloaded>,
+    /// The document changed again while it was being imported.
+    again: bool,
+}
+
+struct HotReloadState {
+    loader: AssetLoader,
+    gfx: Option<Gfx>,
+    watcher: FileWatcher,
+    models: Vec<TrackedModel>,
+    reloading: Vec<Reload>,
+}
+
+/// Reloads models when their glTF documents or textures change on disk.
+///
+/// Reloaded models keep the mesh of the model they replace, so everything drawn using the mesh
+/// is updated on the render thread.
+/// Registered in a scene, which checks for changes twice a second. Documents are imported on the
+/// worker threads of the loader, so the scene keeps running while they are reloaded.
+pub struct HotReload {
+    state: Rc<RefCell<HotReloadState>>,
+}
+
+impl HotReload {
+    pub fn new(loader: &AssetLoader) -> HotReload {
+        HotReload {
+            state: Rc::new(RefCell::new(HotReloadState {
+                loader: loader.clone(),
+                gfx: None,
+                watcher: FileWatcher::new(),
+                models: Vec::new(),
+                reloading: Vec::new(),
+            })),
+        }
+    }
+
+    /// Replace the model of the given object when the model changes.
+    ///
+    /// The object has to be drawn using the model loaded through
+    /// `AssetManager::model` for the same path and node.
+    pub fn track<P: AsRef<Path>>(&self, path: P, node: &str, object: &ModelObject) -> Result<()> {
+        let path = path.as_ref();
+        let mut state = self.state.try_borrow_mut()?;
+
+        let document = state.loader.manager().gltf(path)?;
+        let model = state.loader.manager().model(path, node)?.ok_or_else(|| {
+            ErrorKind::MissingNode(node.to_string())
+        })?;
+
+        state.watch(&document);
+
+        if let Some(tracked) = state.models.iter_mut().find(|m| {
+            m.path == path && m.node == node
+        })
+        {
+            tracked.objects.push(object.clone_model_object());
+            return Ok(());
+        }
+
+        state.models.push(TrackedModel {
+            path: path.to_owned(),
+            node: node.to_string(),
+            model: model,
+            objects: vec![object.clone_model_object()],
+        });
+
+        Ok(())
+    }
+
+    /// Send reloaded meshes to the given render thread, done when a scene is set up.
+    pub fn set_gfx(&self, gfx: &Gfx) -> Result<()> {
+        self.state.try_borrow_mut()?.gfx = Some(gfx.clone());
+        Ok(())
+    }
+
+    /// Check for changed files, and start reloading everything affected by them.
+    ///
+    /// Models whose reload has finished in the meantime are replaced.
+    pub fn poll(&self) -> Result<()> {
+        self.state.try_borrow_mut()?.poll()
+    }
+}
+
+impl HotReloadState {
+    fn watch(&mut self, document: &GltfLoader) {
+        self.watcher.watch(document.path());
+
+        for image in document.image_paths() {
+            self.watcher.watch(image);
+        }
+    }
+
+    fn poll(&mut self) -> Result<()> {
+        self.finish_reloads();
+
+        let changed = self.watcher.changed();
+
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let textures = self.loader.manager().textures().clone();
+        let mut documents = Vec::new();
+        let mut replaced: Vec<Arc<Texture>> = Vec::new();
+
+        for path in changed {
+            info!("changed: {}", path.display());
+
+            let key = path_key(&path);
+
+            // embedded images are keyed by their document, and textures by their file, followed
+            // by their sampler.
+            let prefix = if self.models.iter().any(|m| path_key(&m.path) == key) {
+                documents.push(key.clone());
+                format!("{}#image", key)
+            } else {
+                format!("{}?", key)
+            };
+
+            for texture_key in textures.keys()? {
+                if is_loaded_from(&texture_key, &key, &prefix) {
+                    if let Some(texture) = textures.get(&texture_key)? {
+                        replaced.push(texture.shared());
+                    }
+
+                    textures.remove(&texture_key)?;
+                }
+            }
+        }
+
+        // documents are imported once, even if multiple models are loaded from them.
+        let mut keys = Vec::new();
+
+        for tracked in &self.models {
+            let key = path_key(&tracked.path);
+
+            if keys.contains(&key) {
+                continue;
+            }
+
+            if documents.contains(&key) || uses_any(&tracked.model, &replaced) {
+                keys.push(key);
+            }
+        }
+
+        for key in keys {
+            self.start_reload(key);
+        }
+
+        Ok(())
+    }
+
+    /// Import the document with the given key again on a worker thread, along with all tracked
+    /// models loaded from it.
+    fn start_reload(&mut self, key: String) {
+        // the running import might have read the files before they were saved.
+        if let Some(reload) = self.reloading.iter_mut().find(|r| r.key == key) {
+            reload.again = true;
+            return;
+        }
+
+        let mut path = None;
+        let mut nodes = Vec::new();
+
+        for (index, tracked) in self.models.iter().enumerate() {
+            if path_key(&tracked.path) == key {
+                path = Some(tracked.path.clone());
+                nodes.push((index, tracked.node.clone()));
+            }
+        }
+
+        let path = match path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let manager = self.loader.manager().clone();
+        let file = path.clone();
+
+        let pending = self.loader.spawn(path, move || {
+            let document = manager.reload_gltf(&file)?;
+            let mut models = Vec::new();
+
+            for (index, node) in nodes {
+                models.push((index, document.model_from_node(&node)?));
+            }
+
+            Ok((document, models))
+        });
+
+        self.reloading.push(Reload {
+            key: key,
+            pending: pending,
+            again: false,
+        });
+    }
+
+    /// Replace the models of all documents which are done importing.
+    fn finish_reloads(&mut self) {
+        let mut i = 0;
+
+        while i < self.reloading.len() {
+            let state = self.reloading[i].pending.get();
+
+            let result = match state {
+                Ok(None) => {
+                    i += 1;
+                    continue;
+                }
+                Ok(Some(reloaded)) => Ok(reloaded),
+                Err(e) => Err(e),
+            };
+
+            let reload = self.reloading.remove(i);
+
+            match result {
+                Ok((document, models)) => {
+                    self.watch(&document);
+
+                    for (index, model) in models {
+                        if let Err(e) = self.replace(index, model) {
+                            warn!("failed to reload {}: {}", self.models[index].path.display(), e);
+                        }
+                    }
+                }
+                Err(e) => warn!("failed to reload {}: {}", reload.key, e),
+            }
+
+            if reload.again {
+                self.start_reload(reload.key);
+            }
+        }
+    }
+
+    /// Replace the tracked model with the given index everywhere, keeping its mesh.
+    fn replace(&mut self, index: usize, model: Option<Model>) -> Result<()> {
+        let tracked = &mut self.models[index];
+        let mesh_id = tracked.model.mesh_id();
+
+        let model = model
+            .ok_or_else(|| ErrorKind::MissingNode(tracked.node.clone()))?
+            .with_mesh_id(mesh_id);
+
+        let model = self.loader.manager().models().insert(
+            &model_key(&tracked.path, &tracked.node),
+            model,
+        )?;
+
+        if let Some(ref gfx) = self.gfx {
+            gfx.replace_mesh(mesh_id, model.primitives())?;
+        }
+
+        for object in &tracked.objects {
+            object.replace_model((*model).clone())?;
+        }
+
+        info!("reloaded {}#{}", tracked.path.display(), tracked.node);
+        tracked.model = model;
+        Ok(())
+    }
+}
+
+/// Test if any primitive of the model uses any of the given textures.
+fn uses_any(model: &Model, textures: &[Arc<Texture>]) -> bool {
+    model.primitives.primitives.iter().any(|p| match p.base_color_texture {
+        Some(ref texture) => textures.iter().any(|t| Arc::ptr_eq(t, texture)),
+        None => false,
+    })
+}
+
+impl<S> SchedulerSetup<S> for HotReload {
+    fn setup_scheduler(&mut self, scheduler: &mut Scheduler<S>) {
+        let state = self.state.clone();
+
+        scheduler.run_at(
+            POLL_TICKS,
+            Box::new(move |s, _| {
+                state.try_borrow_mut()?.poll()?;
+                s.run_self_at(POLL_TICKS);
+                Ok(())
+            }),
+        );
+    }
+}
+
+/// Check if a texture key belongs to the changed file with the given key, where `prefix` starts
+/// the keys of textures derived from it.
+fn is_loaded_from(texture_key: &str, key: &str, prefix: &str) -> bool {
+    texture_key == key || texture_key.starts_with(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_loaded_from() {
+        assert!(is_loaded_from("a.png", "a.png", "a.png?"));
+        assert!(is_loaded_from("a.png?Sampler", "a.png", "a.png?"));
+        assert!(!is_loaded_from("a.png.bak", "a.png", "a.png?"));
+        assert!(!is_loaded_from("a.png2", "a.png", "a.png?"));
+
+        assert!(is_loaded_from("a.gltf#image1", "a.gltf", "a.gltf#image"));
+        assert!(!is_loaded_from("a.gltf2#image1", "a.gltf", "a.gltf#image"));
+    }
+}
@@ -0,0 +1,312 @@
+use super::cache::Handle;
+use super::manager::{AssetManager, path_key};
+use errors::*;
+use gltf_loader::GltfLoader;
+use model::Model;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use texture::Texture;
+
+type Job = Box<FnMut() + Send>;
+
+enum PendingState<T> {
+    Loading,
+    Loaded(T),
+    Failed(String),
+}
+
+/// An asset which is being loaded in the background.
+///
+/// Cheap to clone, all clones refer to the same asset.
+pub struct Pending<T> {
+    key: Arc<String>,
+    state: Arc<(Mutex<PendingState<T>>, Condvar)>,
+}
+
+impl<T: Clone> Pending<T> {
+    fn new(key: String) -> Pending<T> {
+        Pending {
+            key: Arc::new(key),
+            state: Arc::new((Mutex::new(PendingState::Loading), Condvar::new())),
+        }
+    }
+
+    /// The key of the asset, typically its path.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Get the asset if it has been loaded, or the error which loading it failed with.
+    pub fn get(&self) -> Result<Option<T>> {
+        let state = self.state.0.lock().map_err(|_| ErrorKind::PoisonError)?;
+        self.result(&state)
+    }
+
+    /// Block until the asset has been loaded.
+    pub fn wait(&self) -> Result<T> {
+        let (ref lock, ref condvar) = *self.state;
+        let mut state = lock.lock().map_err(|_| ErrorKind::PoisonError)?;
+
+        loop {
+            if let Some(value) = self.result(&state)? {
+                return Ok(value);
+            }
+
+            state = condvar.wait(state).map_err(|_| ErrorKind::PoisonError)?;
+        }
+    }
+
+    fn result(&self, state: &PendingState<T>) -> Result<Option<T>> {
+        match *state {
+            PendingState::Loading => Ok(None),
+            PendingState::Loaded(ref value) => Ok(Some(value.clone())),
+            PendingState::Failed(ref message) => Err(
+                ErrorKind::AssetLoad(self.key.to_string(), message.clone())
+                    .into(),
+            ),
+        }
+    }
+
+    fn complete(&self, result: Result<T>) {
+        let (ref lock, ref condvar) = *self.state;
+
+        // a poisoned lock is reported to whoever checks on the asset.
+        if let Ok(mut state) = lock.lock() {
+            *state = match result {
+                Ok(value) => PendingState::Loaded(value),
+                Err(e) => PendingState::Failed(e.to_string()),
+            };
+        }
+
+        condvar.notify_all();
+    }
+}
+
+impl<T> Clone for Pending<T> {
+    fn clone(&self) -> Pending<T> {
+        Pending {
+            key: self.key.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Something which has to finish loading, before a scene can start.
+pub trait Dependency {
+    /// Check if loading is done, failing if loading failed.
+    fn is_done(&self) -> Result<bool>;
+}
+
+impl<T: Clone> Dependency for Pending<T> {
+    fn is_done(&self) -> Result<bool> {
+        Ok(self.get()?.is_some())
+    }
+}
+
+/// A snapshot of how far background loading has come.
+///
+/// Progress is counted in requested assets, not in bytes, since a document pulls in buffers and
+/// images which are only known once it has been parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LoadProgress {
+    /// Number of assets which have been requested.
+    pub items: usize,
+    /// Number of assets which are done loading, successfully or not.
+    pub items_loaded: usize,
+}
+
+impl LoadProgress {
+    /// Fraction of requested assets which are done loading, in [0, 1].
+    pub fn fraction(&self) -> f32 {
+        if self.items == 0 {
+            return 1.0;
+        }
+
+        self.items_loaded as f32 / self.items as f32
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.items_loaded >= self.items
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    items: AtomicUsize,
+    items_loaded: AtomicUsize,
+}
+
+/// Shared view of the progress of an asset loader.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    counters: Arc<Counters>,
+}
+
+impl Progress {
+    pub fn get(&self) -> LoadProgress {
+        LoadProgress {
+            items: self.counters.items.load(Ordering::SeqCst),
+            items_loaded: self.counters.items_loaded.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Loads assets on worker threads, through an asset manager.
+///
+/// Workers finish all requested loads, even if the loader is dropped in the meantime.
+/// Cheap to clone, all clones share the same workers and progress.
+#[derive(Clone)]
+pub struct AssetLoader {
+    manager: AssetManager,
+    jobs: mpsc::Sender<Job>,
+    progress: Progress,
+}
+
+impl AssetLoader {
+    /// Start a loader with the given number of worker threads.
+    pub fn new(manager: AssetManager, threads: usize) -> Result<AssetLoader> {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for i in 0..usize::max(1, threads) {
+            let receiver = receiver.clone();
+
+            thread::Builder::new()
+                .name(format!("asset-loader-{}", i))
+                .spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+
+                    match job {
+                        Ok(mut job) => job(),
+                        // the loader has been dropped, and all jobs have been processed.
+                        Err(_) => return,
+                    }
+                })?;
+        }
+
+        Ok(AssetLoader {
+            manager: manager,
+            jobs: jobs,
+            progress: Progress::default(),
+        })
+    }
+
+    /// The manager assets are loaded through.
+    pub fn manager(&self) -> &AssetManager {
+        &self.manager
+    }
+
+    /// Progress of all loads requested so far, counted in assets.
+    pub fn progress(&self) -> Progress {
+        self.progress.clone()
+    }
+
+    /// Load the glTF document at the given path.
+    pub fn gltf<P: AsRef<Path>>(&self, path: P) -> Pending<Handle<GltfLoader>> {
+        let path = path.as_ref().to_owned();
+        let manager = self.manager.clone();
+        let file = path.clone();
+
+        self.spawn(path, move || manager.gltf(&file))
+    }
+
+    /// Load the model of the node with the given name, from the glTF document at the given path.
+    pub fn model<P: AsRef<Path>>(&self, path: P, node: &str) -> Pending<Handle<Model>> {
+        let path = path.as_ref().to_owned();
+        let manager = self.manager.clone();
+        let file = path.clone();
+        let node = node.to_string();
+
+        self.spawn(path, move || {
+            manager.model(&file, &node)?.ok_or_else(|| {
+                ErrorKind::MissingNode(node.clone()).into()
+            })
+        })
+    }
+
+    /// Load the texture at the given path.
+    pub fn texture<P: AsRef<Path>>(&self, path: P) -> Pending<Handle<Texture>> {
+        let path = path.as_ref().to_owned();
+        let manager = self.manager.clone();
+        let file = path.clone();
+
+        self.spawn(path, move || manager.texture(&file))
+    }
+
+    /// Run the given load on a worker thread, identified by the path it loads.
+    pub(crate) fn spawn<T, F>(&self, path: PathBuf, load: F) -> Pending<T>
+    where
+        T: 'static + Clone + Send,
+        F: 'static + FnOnce() -> Result<T> + Send,
+    {
+        let pending = Pending::new(path_key(&path));
+        let counters = self.progress.counters.clone();
+
+        counters.items.fetch_add(1, Ordering::SeqCst);
+
+        let result = pending.clone();
+        let mut load = Some(load);
+
+        let job: Job = Box::new(move || if let Some(load) = load.take() {
+            let loaded = load();
+            counters.items_loaded.fetch_add(1, Ordering::SeqCst);
+
+            result.complete(loaded);
+        });
+
+        if self.jobs.send(job).is_err() {
+            self.progress.counters.items_loaded.fetch_add(
+                1,
+                Ordering::SeqCst,
+            );
+
+            pending.complete(Err(ErrorKind::ThreadJoin.into()));
+        }
+
+        pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending() {
+        let pending = Pending::new("a.png".to_string());
+        assert_eq!(None, pending.get().unwrap());
+        assert!(!pending.is_done().unwrap());
+
+        pending.clone().complete(Ok(42));
+        assert_eq!(Some(42), pending.get().unwrap());
+        assert_eq!(42, pending.wait().unwrap());
+    }
+
+    #[test]
+    fn test_failed() {
+        let pending: Pending<u32> = Pending::new("a.png".to_string());
+        pending.complete(Err(ErrorKind::NoMesh.into()));
+        assert!(pending.get().is_err());
+        assert!(pending.is_done().is_err());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let loader = AssetLoader::new(AssetManager::new(), 2).unwrap();
+        let texture = loader.texture("does-not-exist.png");
+
+        assert!(texture.wait().is_err());
+
+        let progress = loader.progress().get();
+        assert_eq!(1, progress.items);
+        assert_eq!(1, progress.items_loaded);
+        assert!(progress.is_done());
+        assert_eq!(1.0, progress.fraction());
+    }
+}
@@ -0,0 +1,100 @@
+use super::cache::{AssetCache, Handle};
+use errors::*;
+use gltf_loader::GltfLoader;
+use model::Model;
+use std::fs;
+use std::path::Path;
+use texture::{self, Texture};
+
+/// Loads glTF documents, models and textures, sharing assets which are already loaded.
+///
+/// Cheap to clone, all clones share the same caches.
+#[derive(Debug, Clone, Default)]
+pub struct AssetManager {
+    documents: AssetCache<GltfLoader>,
+    models: AssetCache<Model>,
+    textures: AssetCache<Texture>,
+}
+
+impl AssetManager {
+    pub fn new() -> AssetManager {
+        AssetManager::default()
+    }
+
+    /// Load the glTF document at the given path.
+    ///
+    /// Textures of all models loaded from the document are shared with other documents.
+    pub fn gltf<P: AsRef<Path>>(&self, path: P) -> Result<Handle<GltfLoader>> {
+        let path = path.as_ref();
+        let textures = &self.textures;
+
+        self.documents.get_or_load(&path_key(path), || {
+            GltfLoader::with_textures(path, textures)
+        })
+    }
+
+    /// Import the glTF document at the given path again, replacing the cached document.
+    ///
+    /// Handles to the previous document, and models loaded from it, are not affected.
+    pub fn reload_gltf<P: AsRef<Path>>(&self, path: P) -> Result<Handle<GltfLoader>> {
+        let path = path.as_ref();
+        let loaded = GltfLoader::with_textures(path, &self.textures)?;
+        self.documents.insert(&path_key(path), loaded)
+    }
+
+    /// Load the model of the node with the given name, from the glTF document at the given path.
+    ///
+    /// All handles to the same model share the same mesh, so they are drawn using instancing.
+    pub fn model<P: AsRef<Path>>(&self, path: P, node: &str) -> Result<Option<Handle<Model>>> {
+        let path = path.as_ref();
+        let key = model_key(path, node);
+
+        if let Some(model) = self.models.get(&key)? {
+            return Ok(Some(model));
+        }
+
+        match self.gltf(path)?.model_from_node(node)? {
+            Some(model) => Ok(Some(self.models.get_or_insert(&key, model)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Load the texture at the given path, detecting its format from its contents, or from the
+    /// extension for formats without a signature.
+    pub fn texture<P: AsRef<Path>>(&self, path: P) -> Result<Handle<Texture>> {
+        let path = path.as_ref();
+
+        self.textures.get_or_load(
+            &path_key(path),
+            || Ok(texture::load_from_path(None, path)?),
+        )
+    }
+
+    /// Cache of all loaded glTF documents.
+    pub fn documents(&self) -> &AssetCache<GltfLoader> {
+        &self.documents
+    }
+
+    /// Cache of all loaded models.
+    pub fn models(&self) -> &AssetCache<Model> {
+        &self.models
+    }
+
+    /// Cache of all loaded textures.
+    pub fn textures(&self) -> &AssetCache<Texture> {
+        &self.textures
+    }
+}
+
+/// Key of the model of a node in the glTF document at the given path.
+pub fn model_key(path: &Path, node: &str) -> String {
+    format!("{}#{}", path_key(path), node)
+}
+
+/// Key of the asset at the given path, where different paths to the same file give the same key.
+pub fn path_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_owned())
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,26 @@
+//! # Asset management
+//!
+//! Loading the same glTF document, model or texture twice through the
+//! [AssetManager](manager/struct.AssetManager.html) returns a handle to the same decoded data.
+//! Caches only hold weak references, so assets are released once the last handle to them is
+//! dropped.
+//!
+//! An [AssetLoader](loader/struct.AssetLoader.html) loads assets on worker threads, returning
+//! [Pending](loader/struct.Pending.html) assets which a
+//! [LoadingScene](../loading_scene/struct.LoadingScene.html) can wait for. Its progress is only
+//! reported in assets, not in bytes.
+//!
+//! While developing, [HotReload](hot_reload/struct.HotReload.html) replaces models whose files
+//! change on disk.
+
+pub mod cache;
+pub mod hot_reload;
+pub mod loader;
+pub mod manager;
+pub mod watcher;
+
+pub use self::cache::{AssetCache, Handle};
+pub use self::hot_reload::{HotReload, ModelObject};
+pub use self::loader::{AssetLoader, Dependency, LoadProgress, Pending, Progress};
+pub use self::manager::AssetManager;
+pub use self::watcher::FileWatcher;
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Detects changes to files by polling their modification times.
+#[derive(Debug, Default)]
+pub struct FileWatcher {
+    /// Last seen modification time of each file, `None` if it did not exist.
+    files: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl FileWatcher {
+    pub fn new() -> FileWatcher {
+        FileWatcher::default()
+    }
+
+    /// Start watching the given file, changes from now on are reported.
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+
+        if !self.files.contains_key(path) {
+            self.files.insert(path.to_owned(), modified(path));
+        }
+    }
+
+    /// Files which have been modified, created or removed since the last call.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+
+        for (path, last) in &mut self.files {
+            let current = modified(path);
+
+            if current != *last {
+                *last = current;
+
+                // removed files are typically being replaced, and reported once they are back.
+                if current.is_some() {
+                    changed.push(path.clone());
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::File;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_created_and_removed() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let name = format!("threedge-watcher-{}-{}", now.as_secs(), now.subsec_nanos());
+        let path = env::temp_dir().join(name);
+
+        let mut watcher = FileWatcher::new();
+        watcher.watch(&path);
+        assert!(watcher.changed().is_empty());
+
+        File::create(&path).unwrap();
+        assert_eq!(vec![path.clone()], watcher.changed());
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.changed().is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+//! # Little-endian integers in binary files
+//!
+//! Shared by the GLB, KTX2 and DDS readers. Callers check the length of the data first, reading
+//! past its end panics.
+
+/// Read the little-endian `u32` at the given offset.
+pub fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from(bytes[offset]) | (u32::from(bytes[offset + 1]) << 8) |
+        (u32::from(bytes[offset + 2]) << 16) | (u32::from(bytes[offset + 3]) << 24)
+}
+
+/// Read the little-endian `u64` at the given offset.
+pub fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from(read_u32(bytes, offset)) | (u64::from(read_u32(bytes, offset + 4)) << 32)
+}
+
+/// Write a little-endian `u32` at the given offset, for building test files.
+#[cfg(test)]
+pub fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    for (i, byte) in bytes[offset..offset + 4].iter_mut().enumerate() {
+        *byte = (value >> (i * 8)) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut bytes = [0u8; 9];
+        write_u32(&mut bytes, 1, 0x0403_0201);
+        write_u32(&mut bytes, 5, 0x0807_0605);
+
+        assert_eq!([0, 1, 2, 3, 4, 5, 6, 7, 8], bytes);
+        assert_eq!(0x0403_0201, read_u32(&bytes, 1));
+        assert_eq!(0x0807_0605_0403_0201, read_u64(&bytes, 1));
+    }
+}
@@ -33,7 +33,17 @@ impl Camera {
 
     pub fn modify_zoom(&mut self, zoom: f32) {
         let new_zoom = self.zoom + zoom;
-        self.zoom = f32::min(0.9, f32::max(0.0, new_zoom));
+        self.set_zoom(new_zoom);
+    }
+
+    /// Current zoom, where 0 is furthest away from the player.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Set the zoom, clamped to the range supported by the camera.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = f32::min(0.9, f32::max(0.0, zoom));
     }
 }
 
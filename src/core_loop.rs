@@ -6,16 +6,28 @@ use super::gfx_thread::GfxThread;
 use super::into_boxed_scene::IntoBoxedScene;
 use super::pressed_keys::PressedKeys;
 use super::scheduler::Scheduler;
+use super::text::{Font, TextOptions};
+use super::ui::{Ui, UiInput};
 use cgmath::{Matrix4, SquareMatrix, Vector3};
 use cgmath::prelude::*;
 use gfx::{Gfx, GfxLoopBuilder};
+use gfx::debug_draw::DebugDraw;
+use gfx::overlay::Overlay;
+use gfx::render_mode::RenderMode;
 use shuteye;
 use std::cell::RefCell;
+use std::mem;
 use std::ops::DerefMut;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 
+/// Length of a tick of the core loop and all scene schedulers.
+pub const TICK_MILLIS: u64 = 10;
+/// Length of a tick in seconds, for animation and simulation time.
+pub const TICK_SECONDS: f32 = TICK_MILLIS as f32 / 1000.0;
+
 pub struct CoreLoop {
     gfx: Gfx,
     gfx_loop_builder: GfxLoopBuilder,
@@ -37,11 +49,19 @@ impl CoreLoop {
                 no_movement: Vector3::zero(),
                 pressed_keys: PressedKeys::new(),
                 focus_update: None,
+                render_mode: RenderMode::default(),
+                render_mode_update: None,
                 focused: true,
                 exit: false,
                 scroll: 0i32,
                 gfx: gfx.clone(),
                 gfx_thread: GfxThread::new(),
+                overlay: Overlay::new(),
+                debug_draw: DebugDraw::new(),
+                debug_font: None,
+                ui: Ui::new(),
+                ui_input: UiInput::new(),
+                ui_escape: false,
                 events: events,
             })),
             core_scheduler: Scheduler::new(),
@@ -55,6 +75,26 @@ impl CoreLoop {
         Ok(())
     }
 
+    /// Set the font used to draw debug information, like the frame rate.
+    ///
+    /// The same font is used by the user interface.
+    pub fn set_debug_font(&mut self, mut font: Font) -> Result<()> {
+        font.register(&self.gfx)?;
+        let font = Arc::new(font);
+
+        let mut core = self.core.try_borrow_mut()?;
+        core.ui.set_font(font.clone());
+        core.debug_font = Some(font);
+        Ok(())
+    }
+
+    /// Set the number of samples to use for multisample anti-aliasing at startup.
+    ///
+    /// Can be changed at runtime through `Gfx::set_samples`.
+    pub fn set_samples(&mut self, samples: u32) {
+        self.gfx_loop_builder.set_samples(samples);
+    }
+
     pub fn run(mut self) -> Result<()> {
         let gfx_loop_builder = self.gfx_loop_builder;
 
@@ -71,15 +111,36 @@ impl CoreLoop {
                 c.focused = state;
             }
 
+            if let Some(render_mode) = c.render_mode_update.take() {
+                c.gfx.set_render_mode(render_mode)?;
+            }
+
             Ok(())
         }));
 
         self.core_scheduler.on_every_tick(Box::new(|_, core| {
-            core.try_borrow_mut()?.update_pressed_keys();
+            let mut core = core.try_borrow_mut()?;
+            let c = core.deref_mut();
+
+            c.update_pressed_keys();
+            c.ui.begin(c.ui_input.clone());
+            c.ui_input.next_tick();
             Ok(())
         }));
 
-        let target_sleep = Duration::from_millis(10);
+        self.core_scheduler.on_every_tick(Box::new(|_, core| {
+            let mut core = core.try_borrow_mut()?;
+            let c = core.deref_mut();
+
+            if let Some(ref font) = c.debug_font {
+                let text = format!("fps = {}", c.gfx_thread.fps());
+                font.draw(&mut c.overlay, 10.0, 10.0, &text, &TextOptions::default())?;
+            }
+
+            Ok(())
+        }));
+
+        let target_sleep = Duration::from_millis(TICK_MILLIS);
         let mut sleep = target_sleep;
 
         loop {
@@ -98,6 +159,16 @@ impl CoreLoop {
                 scene.tick(self.core.clone())?;
             }
 
+            {
+                let mut core = self.core.try_borrow_mut()?;
+                let c = core.deref_mut();
+                c.ui.end(&mut c.overlay);
+                let overlay = mem::replace(&mut c.overlay, Overlay::new());
+                c.gfx.set_overlay(overlay)?;
+                let debug_draw = mem::replace(&mut c.debug_draw, DebugDraw::new());
+                c.gfx.debug_draw(debug_draw)?;
+            }
+
             let elapsed = before.elapsed();
 
             if let Some(s) = target_sleep.checked_sub(elapsed) {
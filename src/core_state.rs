@@ -1,12 +1,21 @@
+use super::assets::LoadProgress;
 use super::camera::CameraScroll;
 use super::errors::*;
 use super::events::winit::WinitEvents;
 use super::gfx_thread::GfxThread;
+use super::loading_scene::LoadingScreen;
 use super::player::PlayerTransform;
 use super::pressed_keys::{Key, PressedKeys};
-use cgmath::{Matrix4, Vector3};
+use super::text::{Font, TextOptions};
+use super::ui::{Ui, UiInput, UiKey};
+use cgmath::{Matrix4, Rad, Vector3};
 use gfx::Gfx;
+use gfx::debug_draw::DebugDraw;
+use gfx::color::Color;
+use gfx::overlay::{Overlay, Rect};
+use gfx::render_mode::RenderMode;
 use std::mem;
+use std::sync::Arc;
 use winit;
 
 pub struct CoreState {
@@ -18,6 +27,10 @@ pub struct CoreState {
     pub pressed_keys: PressedKeys,
     /// If focusing should be updated.
     pub focus_update: Option<bool>,
+    /// Current render mode, cycled through with F2.
+    pub render_mode: RenderMode,
+    /// If the render mode should be updated.
+    pub render_mode_update: Option<RenderMode>,
     /// If the game is focused.
     pub focused: bool,
     /// If the game should be exited.
@@ -30,6 +43,20 @@ pub struct CoreState {
     pub gfx: Gfx,
     /// Graphics thread handle.
     pub gfx_thread: GfxThread,
+    /// 2D overlay for the current tick, submitted once all scenes have drawn to it.
+    pub overlay: Overlay,
+    /// Debug lines for the current tick, submitted together with the overlay.
+    ///
+    /// Lines which should stay on screen for longer can be submitted through `Gfx::debug_draw`.
+    pub debug_draw: DebugDraw,
+    /// Font used to draw debug information, like the frame rate.
+    pub debug_font: Option<Arc<Font>>,
+    /// Immediate-mode user interface, drawn into the overlay.
+    pub ui: Ui,
+    /// Mouse and keyboard input collected for the user interface since the last tick.
+    pub ui_input: UiInput,
+    /// If escape was pressed while a widget had keyboard focus.
+    pub ui_escape: bool,
 }
 
 impl CoreState {
@@ -48,11 +75,32 @@ impl CoreState {
                 Event::WindowEvent { event: WindowEvent::Focused(state), .. } => {
                     self.focus_update = Some(state);
                 }
+                Event::WindowEvent { event: WindowEvent::MouseMoved { position, .. }, .. } => {
+                    self.ui_input.mouse = [position.0 as f32, position.1 as f32];
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::MouseInput {
+                        state,
+                        button: winit::MouseButton::Left,
+                        ..
+                    },
+                    ..
+                } => {
+                    self.ui_input.mouse_button(
+                        state == winit::ElementState::Pressed,
+                    );
+                }
+                Event::WindowEvent { event: WindowEvent::ReceivedCharacter(c), .. } => {
+                    self.ui_input.type_char(c);
+                }
+                Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } => {
+                    self.handle_ui_key(input);
+                }
                 Event::WindowEvent { .. } => {
                     // ignore other window events
                 }
                 Event::DeviceEvent { event: DeviceEvent::Key(input), .. } => {
-                    self.handle_device_event(input);
+                    self.handle_device_key(input);
                 }
                 Event::DeviceEvent {
                     event: DeviceEvent::Motion { axis: 3, value, .. }, ..
@@ -69,6 +117,49 @@ impl CoreState {
         }
     }
 
+    fn handle_device_key(&mut self, input: winit::KeyboardInput) {
+        use winit::ElementState;
+        use winit::VirtualKeyCode;
+
+        let escape = input.virtual_keycode == Some(VirtualKeyCode::Escape);
+
+        // widgets with keyboard focus take precedence, but releases pass through so that no keys
+        // get stuck.
+        if input.state == ElementState::Pressed && self.ui.wants_keyboard() {
+            if escape {
+                self.ui_escape = true;
+            }
+
+            return;
+        }
+
+        // escape was used to leave a widget, so it should not exit the game.
+        if escape && input.state == ElementState::Released && self.ui_escape {
+            self.ui_escape = false;
+            return;
+        }
+
+        self.handle_device_event(input);
+    }
+
+    fn handle_ui_key(&mut self, input: winit::KeyboardInput) {
+        use winit::ElementState;
+        use winit::VirtualKeyCode;
+
+        if input.state != ElementState::Pressed {
+            return;
+        }
+
+        let key = match input.virtual_keycode {
+            Some(VirtualKeyCode::Back) => UiKey::Backspace,
+            Some(VirtualKeyCode::Return) => UiKey::Enter,
+            Some(VirtualKeyCode::Escape) => UiKey::Escape,
+            _ => return,
+        };
+
+        self.ui_input.press_key(key);
+    }
+
     fn handle_device_event(&mut self, input: winit::KeyboardInput) {
         use winit::KeyboardInput;
         use winit::VirtualKeyCode;
@@ -85,6 +176,14 @@ impl CoreState {
                     self.exit = self.focused;
                 }
             }
+            KeyboardInput {
+                virtual_keycode: Some(VirtualKeyCode::F2),
+                state: ElementState::Released,
+                ..
+            } => {
+                self.render_mode = self.render_mode.next();
+                self.render_mode_update = Some(self.render_mode);
+            }
             KeyboardInput {
                 virtual_keycode: Some(VirtualKeyCode::A),
                 state,
@@ -152,6 +251,33 @@ impl CoreState {
     }
 }
 
+impl LoadingScreen for CoreState {
+    /// Draw a progress bar, with the number of loaded assets underneath if there is a debug font.
+    fn draw_loading_screen(&mut self, progress: &LoadProgress) -> Result<()> {
+        let bar = Rect::new(40.0, 40.0, 300.0, 16.0);
+
+        self.overlay.rect(bar, Color::from_rgba(0.2, 0.2, 0.2, 0.8));
+        self.overlay.rect(
+            Rect::new(bar.x, bar.y, bar.width * progress.fraction(), bar.height),
+            Color::from_rgb(0.8, 0.8, 0.8),
+        );
+
+        if let Some(ref font) = self.debug_font {
+            let text = format!("loading {}/{}", progress.items_loaded, progress.items);
+
+            font.draw(
+                &mut self.overlay,
+                bar.x,
+                bar.y + bar.height + 8.0,
+                &text,
+                &TextOptions::default(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 impl CameraScroll for CoreState {
     fn take_scroll(&mut self) -> Result<i32> {
         if self.scroll != 0 {
@@ -162,8 +288,14 @@ impl CameraScroll for CoreState {
     }
 }
 
+/// Radians the player turns every tick while a roll key is held.
+const TURN_SPEED: f32 = 0.03;
+
 impl PlayerTransform for CoreState {
     /// Build player transform for a given frame.
+    ///
+    /// The transform is relative to the facing direction of the player, where positive z is
+    /// forward.
     fn player_transform(&mut self) -> Result<Option<Matrix4<f32>>> {
         let mut translation = None;
 
@@ -193,6 +325,16 @@ impl PlayerTransform for CoreState {
             );
         }
 
+        let mut turn = None;
+
+        if pressed_keys.test(Key::RollLeft) {
+            turn = Some(turn.unwrap_or(0.0) + TURN_SPEED);
+        }
+
+        if pressed_keys.test(Key::RollRight) {
+            turn = Some(turn.unwrap_or(0.0) - TURN_SPEED);
+        }
+
         let mut transform = None;
 
         if let Some(translation) = translation {
@@ -202,6 +344,12 @@ impl PlayerTransform for CoreState {
             )
         }
 
+        if let Some(turn) = turn {
+            transform = Some(
+                transform.unwrap_or(self.no_transform) * Matrix4::from_angle_y(Rad(turn)),
+            )
+        }
+
         Ok(transform)
     }
 }
@@ -0,0 +1,170 @@
+//! `data:` URIs, which embed buffers and images directly in a glTF document.
+
+use super::errors::*;
+
+const PREFIX: &'static str = "data:";
+
+/// The decoded contents of a data URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUri {
+    /// Mime type of the data, if specified.
+    pub mime_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Test if the given URI embeds its data, rather than referring to a file.
+pub fn is_data_uri(uri: &str) -> bool {
+    uri.starts_with(PREFIX)
+}
+
+/// Decode a data URI, either base64 or percent-encoded.
+pub fn parse(uri: &str) -> Result<DataUri> {
+    if !is_data_uri(uri) {
+        return Err(ErrorKind::InvalidDataUri("missing data: prefix").into());
+    }
+
+    let rest = &uri[PREFIX.len()..];
+    let comma = rest.find(',').ok_or(
+        ErrorKind::InvalidDataUri("missing comma"),
+    )?;
+
+    let (header, payload) = (&rest[..comma], &rest[comma + 1..]);
+
+    let mut parameters = header.split(';');
+    let mime_type = parameters.next().and_then(|m| if m.is_empty() {
+        None
+    } else {
+        Some(m.to_string())
+    });
+
+    let data = if parameters.any(|p| p == "base64") {
+        decode_base64(payload).ok_or(
+            ErrorKind::InvalidDataUri("invalid base64"),
+        )?
+    } else {
+        decode_percent(payload).ok_or(ErrorKind::InvalidDataUri(
+            "invalid percent-encoding",
+        ))?
+    };
+
+    Ok(DataUri {
+        mime_type: mime_type,
+        data: data,
+    })
+}
+
+fn base64_value(c: u8) -> Option<u32> {
+    match c {
+        b'A'...b'Z' => Some((c - b'A') as u32),
+        b'a'...b'z' => Some((c - b'a') as u32 + 26),
+        b'0'...b'9' => Some((c - b'0') as u32 + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decode standard or URL-safe base64, with or without padding.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim_right_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for &c in input {
+        let value = match base64_value(c) {
+            Some(value) => value,
+            None => return None,
+        };
+
+        buffer = (buffer << 6) | value;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // a single leftover character can not encode a full byte.
+    if bits >= 6 {
+        return None;
+    }
+
+    Some(out)
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'...b'9' => Some(c - b'0'),
+        b'a'...b'f' => Some(c - b'a' + 10),
+        b'A'...b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn decode_percent(input: &str) -> Option<Vec<u8>> {
+    let input = input.as_bytes();
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        if input[i] == b'%' {
+            let digits = (
+                input.get(i + 1).and_then(|c| hex_value(*c)),
+                input.get(i + 2).and_then(|c| hex_value(*c)),
+            );
+
+            match digits {
+                (Some(high), Some(low)) => out.push((high << 4) | low),
+                _ => return None,
+            }
+
+            i += 3;
+        } else {
+            out.push(input[i]);
+            i += 1;
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(Some(b"".to_vec()), decode_base64(""));
+        assert_eq!(Some(b"f".to_vec()), decode_base64("Zg=="));
+        assert_eq!(Some(b"fo".to_vec()), decode_base64("Zm8="));
+        assert_eq!(Some(b"foo".to_vec()), decode_base64("Zm9v"));
+        assert_eq!(Some(b"foobar".to_vec()), decode_base64("Zm9vYmFy"));
+        assert_eq!(Some(vec![0xfb, 0xff]), decode_base64("-_8"));
+        assert_eq!(None, decode_base64("Z"));
+        assert_eq!(None, decode_base64("Zm9v!"));
+    }
+
+    #[test]
+    fn test_parse() {
+        let uri = parse("data:application/octet-stream;base64,AAEC").unwrap();
+        assert_eq!(Some("application/octet-stream".to_string()), uri.mime_type);
+        assert_eq!(vec![0, 1, 2], uri.data);
+
+        let uri = parse("data:,hello%20world").unwrap();
+        assert_eq!(None, uri.mime_type);
+        assert_eq!(b"hello world".to_vec(), uri.data);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(!is_data_uri("buffer.bin"));
+        assert!(parse("buffer.bin").is_err());
+        assert!(parse("data:base64").is_err());
+        assert!(parse("data:;base64,@@@@").is_err());
+        assert!(parse("data:,%2").is_err());
+    }
+}
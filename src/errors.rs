@@ -1,16 +1,17 @@
 use events::errors as events;
 use gfx;
+use text::errors as text;
 use texture::errors as texture;
 
 error_chain! {
     foreign_links {
-        GltfImporter(::gltf_importer::Error);
         BorrowMutError(::std::cell::BorrowMutError);
         BorrowError(::std::cell::BorrowError);
         IoError(::std::io::Error);
         Gltf(::gltf::Error);
         Events(events::Error);
         Texture(texture::Error);
+        Text(text::Error);
         SystemTimeError(::std::time::SystemTimeError);
     }
 
@@ -44,5 +45,40 @@ error_chain! {
             description("no node")
             display("no node: {}", name)
         }
+
+        InvalidGlb(reason: &'static str) {
+            description("invalid binary glTF")
+            display("invalid binary glTF: {}", reason)
+        }
+
+        InvalidDataUri(reason: &'static str) {
+            description("invalid data uri")
+            display("invalid data uri: {}", reason)
+        }
+
+        InvalidBuffer(index: usize, reason: &'static str) {
+            description("invalid buffer")
+            display("invalid buffer #{}: {}", index, reason)
+        }
+
+        TooManyJoints(count: usize) {
+            description("too many joints")
+            display("skin has {} joints, at most {} are supported", count, gfx::MAX_JOINTS)
+        }
+
+        MissingNode(name: String) {
+            description("missing node")
+            display("missing node: {}", name)
+        }
+
+        AssetLoad(key: String, message: String) {
+            description("failed to load asset")
+            display("failed to load asset {}: {}", key, message)
+        }
+
+        UnsupportedAnimation(what: &'static str) {
+            description("unsupported animation")
+            display("unsupported animation: {}", what)
+        }
     }
 }
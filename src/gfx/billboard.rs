@@ -0,0 +1,41 @@
+//! # Camera-facing quads drawn in the 3D scene
+
+use super::TextureId;
+use super::color::Color;
+use super::errors::*;
+use std::fmt;
+
+/// A square, always facing the camera.
+#[derive(Debug, Clone, Copy)]
+pub struct Billboard {
+    /// Center of the billboard.
+    pub position: [f32; 3],
+    /// Width and height of the billboard, in world units.
+    pub size: f32,
+    /// Color multiplied with the texture.
+    pub color: Color,
+}
+
+pub trait CloneBillboardSource {
+    fn clone_billboard_source(&self) -> Box<BillboardSource>;
+}
+
+/// Provides billboards, like the particles of an emitter.
+///
+/// Needs to be thread-safe to be read by the render thread every frame.
+pub trait BillboardSource: fmt::Debug + Send + Sync + CloneBillboardSource {
+    /// Texture shared by all billboards, or `None` for solid colors.
+    fn texture(&self) -> Result<Option<TextureId>>;
+
+    /// Append all current billboards to the given vector.
+    fn billboards(&self, out: &mut Vec<Billboard>) -> Result<()>;
+}
+
+impl<T> CloneBillboardSource for T
+where
+    T: 'static + Clone + BillboardSource,
+{
+    fn clone_billboard_source(&self) -> Box<BillboardSource> {
+        Box::new(self.clone())
+    }
+}
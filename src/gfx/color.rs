@@ -1,3 +1,5 @@
+use std::ops;
+
 #[derive(Debug, Copy, Clone)]
 pub struct Color {
     r: f32,
@@ -24,6 +26,82 @@ impl Color {
             a: a,
         }
     }
+
+    /// Linear interpolation towards another color, where `t` is in [0, 1].
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        Color {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+
+    /// Convert a color from sRGB to linear, like colors picked in image editors.
+    ///
+    /// Alpha is always linear and stays as is.
+    pub fn srgb_to_linear(self) -> Color {
+        Color {
+            r: srgb_to_linear(self.r),
+            g: srgb_to_linear(self.g),
+            b: srgb_to_linear(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Convert a linear color to sRGB.
+    pub fn linear_to_srgb(self) -> Color {
+        Color {
+            r: linear_to_srgb(self.r),
+            g: linear_to_srgb(self.g),
+            b: linear_to_srgb(self.b),
+            a: self.a,
+        }
+    }
+}
+
+/// Decode a single sRGB component in [0, 1].
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a single linear component in [0, 1] to sRGB.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl ops::Add for Color {
+    type Output = Color;
+
+    fn add(self, other: Color) -> Color {
+        Color {
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+            a: self.a + other.a,
+        }
+    }
+}
+
+impl ops::Mul<f32> for Color {
+    type Output = Color;
+
+    fn mul(self, factor: f32) -> Color {
+        Color {
+            r: self.r * factor,
+            g: self.g * factor,
+            b: self.b * factor,
+            a: self.a * factor,
+        }
+    }
 }
 
 impl From<[f32; 4]> for Color {
@@ -37,3 +115,38 @@ impl From<Color> for [f32; 4] {
         [value.r, value.g, value.b, value.a]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_srgb_to_linear() {
+        assert_close(0.0, srgb_to_linear(0.0));
+        assert_close(1.0, srgb_to_linear(1.0));
+        assert_close(0.214_041, srgb_to_linear(0.5));
+        assert_close(0.001_548, srgb_to_linear(0.02));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for i in 0..256 {
+            let c = i as f32 / 255.0;
+            assert_close(c, linear_to_srgb(srgb_to_linear(c)));
+        }
+    }
+
+    #[test]
+    fn test_color() {
+        let color: [f32; 4] = Color::from_rgba(0.5, 1.0, 0.0, 0.5).srgb_to_linear().into();
+
+        assert_close(0.214_041, color[0]);
+        assert_close(1.0, color[1]);
+        assert_close(0.0, color[2]);
+        assert_close(0.5, color[3]);
+    }
+}
@@ -1,9 +1,32 @@
+use super::{GeometryId, MeshId, TextureId};
+use super::billboard::BillboardSource;
 use super::camera_object::CameraObject;
+use super::environment::Environment;
 use super::geometry::Geometry;
+use super::post_process::PostProcess;
+use super::primitives::Primitives;
+use super::render_mode::RenderMode;
+use texture::Texture;
 
 #[derive(Debug)]
 pub enum Command {
     ClearCamera,
     SetCamera(Box<CameraObject>),
     AddGeometry(Box<Geometry>),
+    /// Stop drawing a geometry, releasing its mesh once no other geometry draws it.
+    RemoveGeometry(GeometryId),
+    /// Replace the primitives of a mesh, for all geometry using it.
+    ReplaceMesh(MeshId, Primitives),
+    /// Register a source of billboards, which is read every frame.
+    AddBillboards(Box<BillboardSource>),
+    /// Register a texture to be used by the overlay.
+    AddTexture(TextureId, Texture),
+    /// Change the number of samples used for multisample anti-aliasing.
+    SetSamples(u32),
+    /// Change how geometry is rendered.
+    SetRenderMode(RenderMode),
+    /// Replace the chain of post-processing effects.
+    SetPostProcess(PostProcess),
+    /// Change the background and ambient lighting.
+    SetEnvironment(Environment),
 }
@@ -0,0 +1,220 @@
+//! # Debug lines and shapes drawn in the 3D scene
+//!
+//! Used to visualize things like bounding volumes, camera frustums and paths during development.
+
+use super::color::Color;
+use cgmath::{InnerSpace, Matrix4, Vector3, Vector4};
+use std::f32;
+use std::time::Duration;
+
+/// Number of segments used for each circle of a sphere.
+const SPHERE_SEGMENTS: usize = 24;
+
+/// Length of an arrow head, relative to the length of the arrow.
+const ARROW_HEAD: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DebugLine {
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+    pub color: Color,
+}
+
+/// A batch of debug lines.
+///
+/// Batches without a duration only live until the next batch without a duration is submitted,
+/// which is expected to happen once per tick. Batches with a duration stay on screen until it
+/// has elapsed.
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+    pub lines: Vec<DebugLine>,
+    pub duration: Option<Duration>,
+}
+
+impl DebugDraw {
+    pub fn new() -> DebugDraw {
+        DebugDraw {
+            lines: Vec::new(),
+            duration: None,
+        }
+    }
+
+    /// Build a batch which stays on screen for the given duration.
+    pub fn for_duration(duration: Duration) -> DebugDraw {
+        DebugDraw {
+            lines: Vec::new(),
+            duration: Some(duration),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Draw a line between two points.
+    pub fn line(&mut self, from: Vector3<f32>, to: Vector3<f32>, color: Color) {
+        self.lines.push(DebugLine {
+            from: from.into(),
+            to: to.into(),
+            color: color,
+        });
+    }
+
+    /// Draw the edges of an axis-aligned box.
+    pub fn wire_box(&mut self, min: Vector3<f32>, max: Vector3<f32>, color: Color) {
+        let corner = |x: bool, y: bool, z: bool| {
+            Vector3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+
+        for &a in &[false, true] {
+            for &b in &[false, true] {
+                self.line(corner(false, a, b), corner(true, a, b), color);
+                self.line(corner(a, false, b), corner(a, true, b), color);
+                self.line(corner(a, b, false), corner(a, b, true), color);
+            }
+        }
+    }
+
+    /// Draw a sphere as three circles, one around each axis.
+    pub fn sphere(&mut self, center: Vector3<f32>, radius: f32, color: Color) {
+        let point = |axis: usize, angle: f32| {
+            let (s, c) = angle.sin_cos();
+
+            let offset = match axis {
+                0 => Vector3::new(0.0, c, s),
+                1 => Vector3::new(c, 0.0, s),
+                _ => Vector3::new(c, s, 0.0),
+            };
+
+            center + offset * radius
+        };
+
+        let step = f32::consts::PI * 2.0 / SPHERE_SEGMENTS as f32;
+
+        for axis in 0..3 {
+            for i in 0..SPHERE_SEGMENTS {
+                let from = point(axis, step * i as f32);
+                let to = point(axis, step * (i + 1) as f32);
+                self.line(from, to, color);
+            }
+        }
+    }
+
+    /// Draw the axes of a transformation, x in red, y in green and z in blue.
+    pub fn axes(&mut self, transform: Matrix4<f32>, size: f32) {
+        let point = |x: f32, y: f32, z: f32| (transform * Vector4::new(x, y, z, 1.0)).truncate();
+
+        let origin = point(0.0, 0.0, 0.0);
+
+        self.line(
+            origin,
+            point(size, 0.0, 0.0),
+            Color::from_rgb(1.0, 0.0, 0.0),
+        );
+
+        self.line(
+            origin,
+            point(0.0, size, 0.0),
+            Color::from_rgb(0.0, 1.0, 0.0),
+        );
+
+        self.line(
+            origin,
+            point(0.0, 0.0, size),
+            Color::from_rgb(0.0, 0.0, 1.0),
+        );
+    }
+
+    /// Draw an arrow pointing from one point to another.
+    pub fn arrow(&mut self, from: Vector3<f32>, to: Vector3<f32>, color: Color) {
+        self.line(from, to, color);
+
+        let direction = to - from;
+        let length = direction.magnitude();
+
+        if length <= 0.0 {
+            return;
+        }
+
+        let direction = direction / length;
+
+        // any axis which is not parallel to the direction works for building the head.
+        let up = if direction.y.abs() < 0.99 {
+            Vector3::unit_y()
+        } else {
+            Vector3::unit_x()
+        };
+
+        let head = length * ARROW_HEAD;
+        let side = direction.cross(up).normalize() * head * 0.5;
+        let up = side.cross(direction).normalize() * head * 0.5;
+        let base = to - direction * head;
+
+        self.line(to, base + side, color);
+        self.line(to, base - side, color);
+        self.line(to, base + up, color);
+        self.line(to, base - up, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white() -> Color {
+        Color::from_rgb(1.0, 1.0, 1.0)
+    }
+
+    #[test]
+    fn test_wire_box() {
+        let mut draw = DebugDraw::new();
+        draw.wire_box(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0), white());
+
+        assert_eq!(12, draw.lines.len());
+
+        for line in &draw.lines {
+            let from = Vector3::from(line.from);
+            let to = Vector3::from(line.to);
+            assert_eq!(2.0, (to - from).magnitude());
+        }
+    }
+
+    #[test]
+    fn test_sphere() {
+        let mut draw = DebugDraw::new();
+        let center = Vector3::new(1.0, 2.0, 3.0);
+        draw.sphere(center, 2.0, white());
+
+        assert_eq!(SPHERE_SEGMENTS * 3, draw.lines.len());
+
+        for line in &draw.lines {
+            let distance = (Vector3::from(line.from) - center).magnitude();
+            assert!((distance - 2.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_arrow() {
+        let mut draw = DebugDraw::new();
+        draw.arrow(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 10.0, 0.0), white());
+
+        assert_eq!(5, draw.lines.len());
+
+        for line in &draw.lines[1..] {
+            assert_eq!([0.0, 10.0, 0.0], line.from);
+            assert!((line.to[1] - 8.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_zero_length_arrow() {
+        let mut draw = DebugDraw::new();
+        let point = Vector3::new(1.0, 1.0, 1.0);
+        draw.arrow(point, point, white());
+        assert_eq!(1, draw.lines.len());
+    }
+}
@@ -0,0 +1,41 @@
+//! # Background and ambient lighting of a scene
+
+use super::color::Color;
+use texture::{Skybox, Texture};
+
+#[derive(Debug, Clone)]
+pub struct Environment {
+    /// Color of the background where there is no skybox.
+    pub clear_color: Color,
+    /// Equirectangular texture drawn behind all geometry.
+    pub skybox: Option<Texture>,
+    /// Strength of the ambient light taken from the average color of the skybox.
+    pub ambient: Option<f32>,
+}
+
+impl Environment {
+    /// An environment with a solid background color.
+    pub fn new(clear_color: Color) -> Environment {
+        Environment {
+            clear_color: clear_color,
+            skybox: None,
+            ambient: None,
+        }
+    }
+
+    /// Draw the given skybox behind all geometry.
+    pub fn set_skybox(&mut self, skybox: Skybox) {
+        self.skybox = Some(skybox.into_texture());
+    }
+
+    /// Use the skybox for ambient lighting, with the given strength.
+    pub fn set_ambient(&mut self, ambient: f32) {
+        self.ambient = Some(ambient);
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Environment {
+        Environment::new(Color::from_rgb(0.0, 0.0, 0.0))
+    }
+}
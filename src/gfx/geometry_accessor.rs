@@ -1,6 +1,6 @@
 use super::primitives::Primitives;
 use cgmath::{Matrix4, Point3};
-use gfx::GeometryId;
+use gfx::{GeometryId, MeshId};
 use gfx::errors::*;
 
 pub trait GeometryAccessor {
@@ -12,6 +12,25 @@ pub trait GeometryAccessor {
     /// Position (to origin) of the geometry object.
     fn position(&self) -> Result<Point3<f32>>;
 
+    /// Get the mesh drawn by this geometry.
+    ///
+    /// Geometries sharing the same mesh are drawn together using instancing.
+    fn mesh_id(&self) -> MeshId;
+
     /// Get all vertices associated with the geometry.
     fn primitives(&self) -> Result<Primitives>;
+
+    /// Joint matrices used to skin the vertices of the geometry, if it is animated.
+    ///
+    /// Skinned geometries are drawn one at a time instead of being instanced.
+    fn joints(&self) -> Result<Option<Vec<Matrix4<f32>>>> {
+        Ok(None)
+    }
+
+    /// Weights of the morph targets of the primitives, if the geometry is deformed.
+    ///
+    /// Deformed geometries are drawn one at a time instead of being instanced.
+    fn morph_weights(&self) -> Result<Option<Vec<f32>>> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,17 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Identifies a shared mesh, which may be drawn by any number of geometries.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct MeshId(usize);
+
+lazy_static! {
+    static ref IDS: AtomicUsize = {
+        AtomicUsize::new(0usize)
+    };
+}
+
+impl MeshId {
+    pub fn allocate() -> MeshId {
+        MeshId(IDS.fetch_add(1usize, Ordering::Relaxed))
+    }
+}
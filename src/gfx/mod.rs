@@ -4,23 +4,47 @@ pub mod vulkan;
 pub mod primitive;
 pub mod primitives;
 mod command;
+pub mod billboard;
 pub mod camera_accessor;
 pub mod camera_object;
 pub mod color;
+pub mod debug_draw;
+pub mod environment;
 pub mod errors;
 pub mod geometry;
 pub mod geometry_object;
 pub mod geometry_accessor;
+pub mod morph;
 mod geometry_id;
+mod mesh_id;
+mod texture_id;
+pub mod overlay;
+pub mod post_process;
+pub mod render_mode;
 pub mod vertices;
 
 pub use self::geometry_id::GeometryId;
+pub use self::mesh_id::MeshId;
+pub use self::texture_id::TextureId;
+
+/// Maximum number of joints in a skin, must match the size of the palette in the shader.
+pub const MAX_JOINTS: usize = 64;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
     pub tex_coord: [f32; 2],
+    /// Indices of the joints influencing the vertex, for skinned meshes.
+    pub joints: [f32; 4],
+    /// Influence of each joint, all zero for vertices which are not skinned.
+    pub weights: [f32; 4],
+}
+
+/// Per-instance data, used when drawing many geometries sharing the same mesh.
+#[derive(Copy, Clone, Debug)]
+pub struct Instance {
+    pub transformation: [[f32; 4]; 4],
 }
 
 #[cfg(feature = "gfx-vulkan")]
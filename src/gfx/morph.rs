@@ -0,0 +1,97 @@
+//! # Morph targets
+//!
+//! Morph targets deform a primitive by adding weighted offsets to the positions and normals of
+//! its vertices.
+
+use super::Vertex;
+
+/// Offsets for every vertex of a primitive.
+#[derive(Debug, Clone)]
+pub struct MorphTarget {
+    /// Position offsets, empty if the target does not move vertices.
+    pub positions: Vec<[f32; 3]>,
+    /// Normal offsets, empty if the target does not change normals.
+    pub normals: Vec<[f32; 3]>,
+}
+
+/// Blend the given morph targets into the vertices, writing the result to `out`.
+///
+/// Missing weights are treated as zero.
+pub fn blend(vertices: &[Vertex], targets: &[MorphTarget], weights: &[f32], out: &mut Vec<Vertex>) {
+    out.clear();
+    out.extend_from_slice(vertices);
+
+    for (target, weight) in targets.iter().zip(weights.iter()) {
+        let weight = *weight;
+
+        if weight == 0.0 {
+            continue;
+        }
+
+        for (v, d) in out.iter_mut().zip(target.positions.iter()) {
+            v.position[0] += d[0] * weight;
+            v.position[1] += d[1] * weight;
+            v.position[2] += d[2] * weight;
+        }
+
+        for (v, d) in out.iter_mut().zip(target.normals.iter()) {
+            v.normal[0] += d[0] * weight;
+            v.normal[1] += d[1] * weight;
+            v.normal[2] += d[2] * weight;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32) -> Vertex {
+        Vertex {
+            position: [x, 0.0, 0.0],
+            normal: [0.0, 1.0, 0.0],
+            tex_coord: [0.0, 0.0],
+            joints: [0.0; 4],
+            weights: [0.0; 4],
+        }
+    }
+
+    #[test]
+    fn test_blend() {
+        let vertices = vec![vertex(0.0), vertex(1.0)];
+
+        let targets = vec![
+            MorphTarget {
+                positions: vec![[0.0, 2.0, 0.0], [0.0, 2.0, 0.0]],
+                normals: vec![],
+            },
+            MorphTarget {
+                positions: vec![[1.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+                normals: vec![[1.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+            },
+        ];
+
+        let mut out = Vec::new();
+        blend(&vertices, &targets, &[0.5, 1.0], &mut out);
+
+        assert_eq!([1.0, 1.0, 0.0], out[0].position);
+        assert_eq!([2.0, 1.0, 0.0], out[1].position);
+        assert_eq!([1.0, 1.0, 0.0], out[1].normal);
+    }
+
+    #[test]
+    fn test_missing_weights() {
+        let vertices = vec![vertex(0.0)];
+
+        let targets = vec![
+            MorphTarget {
+                positions: vec![[0.0, 2.0, 0.0]],
+                normals: vec![],
+            },
+        ];
+
+        let mut out = Vec::new();
+        blend(&vertices, &targets, &[], &mut out);
+        assert_eq!([0.0, 0.0, 0.0], out[0].position);
+    }
+}
@@ -0,0 +1,223 @@
+//! # 2D overlay drawn on top of the 3D scene
+//!
+//! All coordinates are in pixels, with the origin in the top-left corner of the window.
+
+use super::TextureId;
+use super::color::Color;
+
+/// A rectangle in screen space, or in texture space when used for texture coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    /// The full [0, 1] range of texture coordinates.
+    pub fn unit() -> Rect {
+        Rect::new(0.0, 0.0, 1.0, 1.0)
+    }
+
+    /// Test if the given point is inside of the rectangle.
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        point[0] >= self.x && point[0] < self.x + self.width && point[1] >= self.y &&
+            point[1] < self.y + self.height
+    }
+
+    /// Intersection between two rectangles, which might be empty.
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = f32::max(self.x, other.x);
+        let y = f32::max(self.y, other.y);
+        let right = f32::min(self.x + self.width, other.x + other.width);
+        let bottom = f32::min(self.y + self.height, other.y + other.height);
+        Rect::new(x, y, f32::max(0.0, right - x), f32::max(0.0, bottom - y))
+    }
+}
+
+/// A region of a registered texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    pub texture: TextureId,
+    /// Texture coordinates of the sprite.
+    pub uv: Rect,
+}
+
+impl Sprite {
+    /// A sprite covering the whole texture.
+    pub fn new(texture: TextureId) -> Sprite {
+        Sprite {
+            texture: texture,
+            uv: Rect::unit(),
+        }
+    }
+}
+
+/// A single quad to draw.
+#[derive(Debug, Clone, Copy)]
+pub struct Quad {
+    pub rect: Rect,
+    /// Texture to sample, or `None` for a solid color.
+    pub texture: Option<TextureId>,
+    pub uv: Rect,
+    /// Color which the texture is multiplied with.
+    pub color: Color,
+    /// Only the part of the quad inside of this rectangle is drawn.
+    pub clip: Option<Rect>,
+}
+
+/// A list of 2D elements, drawn in order on top of the 3D scene.
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    pub quads: Vec<Quad>,
+    clip_stack: Vec<Rect>,
+}
+
+impl Overlay {
+    pub fn new() -> Overlay {
+        Overlay {
+            quads: Vec::new(),
+            clip_stack: Vec::new(),
+        }
+    }
+
+    /// Draw a solid rectangle.
+    pub fn rect(&mut self, rect: Rect, color: Color) {
+        self.push(rect, None, Rect::unit(), color);
+    }
+
+    /// Draw the given region of a texture.
+    pub fn quad(&mut self, rect: Rect, texture: TextureId, uv: Rect, color: Color) {
+        self.push(rect, Some(texture), uv, color);
+    }
+
+    /// Draw a sprite.
+    pub fn sprite(&mut self, rect: Rect, sprite: &Sprite) {
+        self.push(
+            rect,
+            Some(sprite.texture),
+            sprite.uv,
+            Color::from_rgba(1.0, 1.0, 1.0, 1.0),
+        );
+    }
+
+    /// Restrict all subsequent drawing to the given rectangle, until `pop_clip` is called.
+    ///
+    /// Nested clipping rectangles are intersected with each other.
+    pub fn push_clip(&mut self, rect: Rect) {
+        let rect = match self.clip_stack.last() {
+            Some(current) => current.intersect(&rect),
+            None => rect,
+        };
+
+        self.clip_stack.push(rect);
+    }
+
+    /// Remove the last clipping rectangle.
+    pub fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+    }
+
+    fn push(&mut self, rect: Rect, texture: Option<TextureId>, uv: Rect, color: Color) {
+        let clip = self.clip_stack.last().cloned();
+
+        self.quads.push(Quad {
+            rect: rect,
+            texture: texture,
+            uv: uv,
+            color: color,
+            clip: clip,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersect_disjoint() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(20.0, 30.0, 5.0, 5.0);
+
+        let clipped = a.intersect(&b);
+        assert_eq!(0.0, clipped.width);
+        assert_eq!(0.0, clipped.height);
+        assert!(!clipped.contains([20.0, 30.0]));
+    }
+
+    #[test]
+    fn test_intersect_nested() {
+        let outer = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let inner = Rect::new(10.0, 20.0, 30.0, 40.0);
+
+        assert_eq!(inner, outer.intersect(&inner));
+        assert_eq!(inner, inner.intersect(&outer));
+    }
+
+    #[test]
+    fn test_intersect_overlapping() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(5.0, -5.0, 10.0, 10.0);
+
+        assert_eq!(Rect::new(5.0, 0.0, 5.0, 5.0), a.intersect(&b));
+    }
+
+    #[test]
+    fn test_intersect_touching() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(10.0, 0.0, 10.0, 10.0);
+
+        // sharing an edge leaves nothing to draw.
+        assert_eq!(Rect::new(10.0, 0.0, 0.0, 10.0), a.intersect(&b));
+    }
+
+    #[test]
+    fn test_clip_stack() {
+        let white = Color::from_rgba(1.0, 1.0, 1.0, 1.0);
+        let rect = Rect::new(0.0, 0.0, 200.0, 200.0);
+        let mut overlay = Overlay::new();
+
+        overlay.rect(rect, white);
+        overlay.push_clip(Rect::new(0.0, 0.0, 100.0, 100.0));
+        overlay.rect(rect, white);
+        overlay.push_clip(Rect::new(50.0, 50.0, 100.0, 100.0));
+        overlay.rect(rect, white);
+        overlay.pop_clip();
+        overlay.rect(rect, white);
+        overlay.pop_clip();
+        overlay.rect(rect, white);
+
+        let clips: Vec<_> = overlay.quads.iter().map(|q| q.clip).collect();
+
+        assert_eq!(
+            vec![
+                None,
+                Some(Rect::new(0.0, 0.0, 100.0, 100.0)),
+                Some(Rect::new(50.0, 50.0, 50.0, 50.0)),
+                Some(Rect::new(0.0, 0.0, 100.0, 100.0)),
+                None,
+            ],
+            clips
+        );
+    }
+
+    #[test]
+    fn test_pop_empty_clip_stack() {
+        let mut overlay = Overlay::new();
+        overlay.pop_clip();
+        overlay.rect(Rect::unit(), Color::from_rgba(1.0, 1.0, 1.0, 1.0));
+
+        assert_eq!(None, overlay.quads[0].clip);
+    }
+}
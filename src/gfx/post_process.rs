@@ -0,0 +1,106 @@
+//! # Post-processing
+//!
+//! The scene is rendered into an offscreen HDR image, which is then passed through a chain of
+//! full-screen effects before ending up on screen.
+
+/// A single full-screen pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Map HDR colors into displayable range, using a filmic curve.
+    Tonemap {
+        /// Scale applied to all colors before mapping them.
+        exposure: f32,
+    },
+    /// Let bright areas bleed into their surroundings.
+    ///
+    /// Should be applied before tonemapping, while colors are still in HDR.
+    Bloom {
+        /// Brightness above which colors bleed.
+        threshold: f32,
+        /// Strength of the bleeding.
+        intensity: f32,
+        /// How far colors bleed, in pixels.
+        radius: f32,
+    },
+    /// Gamma correction.
+    ///
+    /// Only needed when the swapchain image is not in an sRGB format.
+    Gamma { gamma: f32 },
+    /// Darken the edges of the screen.
+    Vignette {
+        /// How dark the edges become, where 0 is no darkening.
+        strength: f32,
+        /// Distance from the center where darkening starts, relative to the corners.
+        radius: f32,
+    },
+    /// Fast approximate anti-aliasing.
+    ///
+    /// Should be applied after tonemapping.
+    Fxaa,
+}
+
+impl Effect {
+    /// Tonemapping with no exposure adjustment.
+    pub fn tonemap() -> Effect {
+        Effect::Tonemap { exposure: 1.0 }
+    }
+
+    /// Bloom with reasonable defaults.
+    pub fn bloom() -> Effect {
+        Effect::Bloom {
+            threshold: 1.0,
+            intensity: 0.5,
+            radius: 8.0,
+        }
+    }
+
+    /// Gamma correction for a gamma of 2.2.
+    pub fn gamma() -> Effect {
+        Effect::Gamma { gamma: 2.2 }
+    }
+
+    /// Vignette with reasonable defaults.
+    pub fn vignette() -> Effect {
+        Effect::Vignette {
+            strength: 0.5,
+            radius: 0.5,
+        }
+    }
+}
+
+/// A chain of effects, applied in order.
+///
+/// An empty chain draws the scene as-is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostProcess {
+    pub effects: Vec<Effect>,
+}
+
+impl PostProcess {
+    pub fn new() -> PostProcess {
+        PostProcess { effects: Vec::new() }
+    }
+
+    /// A chain with bloom, tonemapping, vignette and anti-aliasing.
+    pub fn cinematic() -> PostProcess {
+        PostProcess {
+            effects: vec![
+                Effect::bloom(),
+                Effect::tonemap(),
+                Effect::vignette(),
+                Effect::Fxaa,
+            ],
+        }
+    }
+
+    /// Add an effect to the end of the chain.
+    pub fn push(&mut self, effect: Effect) {
+        self.effects.push(effect);
+    }
+}
+
+impl Default for PostProcess {
+    fn default() -> PostProcess {
+        PostProcess::new()
+    }
+}
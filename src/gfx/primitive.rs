@@ -1,6 +1,8 @@
 use super::Vertex;
 use super::color::Color;
+use super::morph::MorphTarget;
 use std::fmt;
+use std::sync::Arc;
 use texture::Texture;
 
 #[derive(Clone)]
@@ -8,7 +10,10 @@ pub struct Primitive {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
     pub base_color_factor: Color,
-    pub base_color_texture: Option<Texture>,
+    /// Shared between all primitives using the same image.
+    pub base_color_texture: Option<Arc<Texture>>,
+    /// Morph targets deforming the primitive, blended by the weights of the geometry.
+    pub morph_targets: Vec<MorphTarget>,
 }
 
 impl Primitive {
@@ -16,13 +21,14 @@ impl Primitive {
         vertices: Vec<Vertex>,
         indices: Vec<u32>,
         base_color_factor: Color,
-        base_color_texture: Option<Texture>,
+        base_color_texture: Option<Arc<Texture>>,
     ) -> Primitive {
         Primitive {
             vertices: vertices,
             indices: indices,
             base_color_factor: base_color_factor,
             base_color_texture: base_color_texture,
+            morph_targets: Vec::new(),
         }
     }
 }
@@ -31,9 +37,10 @@ impl fmt::Debug for Primitive {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmt,
-            "Primitive {{ vertices: <{}>, indices: <{}> }}",
+            "Primitive {{ vertices: <{}>, indices: <{}>, morph_targets: <{}> }}",
             self.vertices.len(),
-            self.indices.len()
+            self.indices.len(),
+            self.morph_targets.len()
         )
     }
 }
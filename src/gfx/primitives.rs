@@ -1,12 +1,16 @@
 use super::primitive::Primitive;
+use std::sync::Arc;
 
+/// Primitives of a mesh.
+///
+/// Cheap to clone, since the primitives are shared between all clones.
 #[derive(Debug, Clone)]
 pub struct Primitives {
-    pub primitives: Vec<Primitive>,
+    pub primitives: Arc<Vec<Primitive>>,
 }
 
 impl Primitives {
     pub fn new(primitives: Vec<Primitive>) -> Primitives {
-        Primitives { primitives: primitives }
+        Primitives { primitives: Arc::new(primitives) }
     }
 }
@@ -0,0 +1,35 @@
+/// How geometry is rendered, used to inspect models in-engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Regular shading, with materials and lighting.
+    Shaded,
+    /// Only the edges of all triangles.
+    Wireframe,
+    /// Vertex normals, as colors.
+    Normals,
+    /// A checker texture mapped using the texture coordinates of the geometry.
+    UvChecker,
+    /// Distance from the camera, with closer geometry being brighter.
+    Depth,
+}
+
+impl RenderMode {
+    /// The mode following this one, wrapping around to `Shaded`.
+    pub fn next(self) -> RenderMode {
+        use self::RenderMode::*;
+
+        match self {
+            Shaded => Wireframe,
+            Wireframe => Normals,
+            Normals => UvChecker,
+            UvChecker => Depth,
+            Depth => Shaded,
+        }
+    }
+}
+
+impl Default for RenderMode {
+    fn default() -> RenderMode {
+        RenderMode::Shaded
+    }
+}
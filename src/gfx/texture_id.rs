@@ -0,0 +1,17 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Identifies a texture registered with the graphics subsystem.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct TextureId(usize);
+
+lazy_static! {
+    static ref IDS: AtomicUsize = {
+        AtomicUsize::new(0usize)
+    };
+}
+
+impl TextureId {
+    pub fn allocate() -> TextureId {
+        TextureId(IDS.fetch_add(1usize, Ordering::Relaxed))
+    }
+}
@@ -21,6 +21,7 @@ mod command_buffer {
             DrawIndexedError(DrawIndexedError);
             BeginRenderPassError(BeginRenderPassError);
             CommandBufferExecError(CommandBufferExecError);
+            CopyBufferImageError(CopyBufferImageError);
         }
     }
 
@@ -30,6 +31,7 @@ mod command_buffer {
     vulkan_link_error!(DrawIndexedError);
     vulkan_link_error!(BeginRenderPassError);
     vulkan_link_error!(CommandBufferExecError);
+    vulkan_link_error!(CopyBufferImageError);
 }
 
 mod device {
@@ -8,17 +8,41 @@ pub mod vulkan_gfx;
 mod vulkan_primitive;
 mod vulkan_primitives;
 mod vulkan_geometry;
+mod vulkan_instance_ring;
+mod vulkan_frame_stats;
+mod vulkan_textures;
+mod vulkan_texture_cache;
+mod vulkan_pipeline;
+mod vulkan_overlay;
+mod vulkan_debug_lines;
+mod vulkan_billboards;
+mod vulkan_skybox;
+mod vulkan_screen_triangle;
+mod vulkan_post_process;
 
-use self::shaders::basic::vs;
+use self::shaders::basic::{fs, vs};
+use self::shaders::billboard;
+use self::shaders::lines;
+use self::shaders::overlay;
+use self::shaders::post;
+use self::shaders::sky;
 pub use self::vulkan_gfx_instance::VulkanGfxInstance;
-use gfx::Vertex;
+use gfx::{Instance, Vertex};
 use vulkano::framebuffer;
 use vulkano::pipeline;
 
-impl_vertex!(Vertex, position, normal, tex_coord);
+impl_vertex!(Vertex, position, normal, tex_coord, joints, weights);
+impl_vertex!(Instance, transformation);
 
 pub type UniformGlobal = vs::ty::Global;
-pub type UniformModel = vs::ty::Model;
+pub type UniformMaterial = fs::ty::Material;
+pub type UniformSkin = vs::ty::Skin;
+pub type PushConstants = fs::ty::Constants;
+pub type UniformScreen = overlay::vs::ty::Screen;
+pub type UniformLines = lines::vs::ty::Lines;
+pub type UniformBillboards = billboard::vs::ty::Billboards;
+pub type UniformSky = sky::fs::ty::Sky;
+pub type PushEffect = post::fs::ty::Effect;
 
 pub type Rp = framebuffer::RenderPassAbstract + Send + ::std::marker::Sync;
 pub type Pl = pipeline::GraphicsPipelineAbstract + Send + ::std::marker::Sync;
@@ -9,26 +9,40 @@ pub(crate) mod vs {
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec3 normal;
 layout(location = 2) in vec2 tex_coord;
+layout(location = 3) in vec4 joints;
+layout(location = 4) in vec4 weights;
+layout(location = 5) in mat4 transformation;
 
 layout(location = 0) out vec3 v_normal;
 layout(location = 1) out vec2 v_tex_coord;
 
-layout(set = 0, binding = 1) uniform Model {
-    mat4 model;
-    vec4 base_color_factor;
-    bool use_base_color_texture;
-} model;
-
 layout(set = 0, binding = 0) uniform Global {
     mat4 camera;
     mat4 view;
     mat4 projection;
 } global;
 
+const int MAX_JOINTS = 64;
+
+layout(set = 2, binding = 0) uniform Skin {
+    mat4 palette[MAX_JOINTS];
+} skin;
+
 void main() {
+    mat4 skinning = mat4(1.0);
+
+    // vertices without weights are not skinned.
+    if (weights != vec4(0.0)) {
+        skinning =
+            weights.x * skin.palette[int(joints.x)] +
+            weights.y * skin.palette[int(joints.y)] +
+            weights.z * skin.palette[int(joints.z)] +
+            weights.w * skin.palette[int(joints.w)];
+    }
+
     mat4 worldview = global.view * global.camera;
-    gl_Position = global.projection * worldview * model.model * vec4(position, 1.0);
-    v_normal = transpose(inverse(mat3(worldview))) * normal;
+    gl_Position = global.projection * worldview * transformation * skinning * vec4(position, 1.0);
+    v_normal = transpose(inverse(mat3(worldview))) * mat3(skinning) * normal;
     v_tex_coord = tex_coord;
 }
 "]
@@ -47,26 +61,67 @@ layout(location = 0) in vec3 normal;
 layout(location = 1) in vec2 tex_coord;
 layout(location = 0) out vec4 f_color;
 
-layout(set = 0, binding = 1) uniform Model {
-    mat4 model;
+layout(set = 0, binding = 1) uniform sampler2D checker;
+
+layout(set = 1, binding = 0) uniform Material {
     vec4 base_color_factor;
     bool use_base_color_texture;
-} model;
+} material;
+
+layout(set = 1, binding = 1) uniform sampler2D tex;
 
-layout(set = 1, binding = 0) uniform sampler2D tex;
+layout(push_constant) uniform Constants {
+    vec4 ambient;
+    uint mode;
+    float near;
+    float far;
+} constants;
 
 const vec3 LIGHT = vec3(1.0, 0.0, 1.0);
 
+const uint MODE_WIREFRAME = 1;
+const uint MODE_NORMALS = 2;
+const uint MODE_UV_CHECKER = 3;
+const uint MODE_DEPTH = 4;
+
 void main() {
-    if (model.use_base_color_texture) {
-        f_color = texture(tex, tex_coord);
+    if (constants.mode == MODE_WIREFRAME) {
+        f_color = vec4(1.0, 1.0, 1.0, 1.0);
+        return;
+    }
+
+    if (constants.mode == MODE_NORMALS) {
+        f_color = vec4(normalize(normal) * 0.5 + 0.5, 1.0);
+        return;
+    }
+
+    if (constants.mode == MODE_UV_CHECKER) {
+        f_color = texture(checker, tex_coord);
+        return;
+    }
+
+    if (constants.mode == MODE_DEPTH) {
+        // the projection maps depth to [-1, 1], of which vulkan keeps [0, 1].
+        float z = gl_FragCoord.z;
+        float depth = (2.0 * constants.near * constants.far) /
+            (constants.far + constants.near - z * (constants.far - constants.near));
+        float value = 1.0 - (depth - constants.near) / (constants.far - constants.near);
+        f_color = vec4(value, value, value, 1.0);
+        return;
+    }
+
+    vec4 base_color;
+
+    if (material.use_base_color_texture) {
+        base_color = texture(tex, tex_coord);
     } else {
-        f_color = model.base_color_factor;
+        base_color = material.base_color_factor;
     }
 
     float brightness = dot(normalize(normal), normalize(LIGHT));
     vec4 dark_color = vec4(0.0, 0.0, 0.0, 0.1);
-    f_color = vec4(mix(f_color, dark_color, brightness));
+    f_color = vec4(mix(base_color, dark_color, brightness));
+    f_color.rgb += base_color.rgb * constants.ambient.rgb;
 }
 "]
     struct Dummy;
@@ -0,0 +1,42 @@
+pub(crate) mod vs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "vertex"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 color;
+
+layout(location = 0) out vec4 v_color;
+
+layout(set = 0, binding = 0) uniform Lines {
+    mat4 view_projection;
+} lines;
+
+void main() {
+    gl_Position = lines.view_projection * vec4(position, 1.0);
+    v_color = color;
+}
+"]
+    struct Dummy;
+}
+
+pub(crate) mod fs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "fragment"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec4 color;
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    f_color = color;
+}
+"]
+    struct Dummy;
+}
@@ -1 +1,6 @@
 pub(crate) mod basic;
+pub(crate) mod billboard;
+pub(crate) mod lines;
+pub(crate) mod overlay;
+pub(crate) mod post;
+pub(crate) mod sky;
@@ -0,0 +1,48 @@
+pub(crate) mod vs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "vertex"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 tex_coord;
+layout(location = 2) in vec4 color;
+
+layout(location = 0) out vec2 v_tex_coord;
+layout(location = 1) out vec4 v_color;
+
+layout(set = 0, binding = 0) uniform Screen {
+    mat4 projection;
+} screen;
+
+void main() {
+    gl_Position = screen.projection * vec4(position, 0.0, 1.0);
+    v_tex_coord = tex_coord;
+    v_color = color;
+}
+"]
+    struct Dummy;
+}
+
+pub(crate) mod fs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "fragment"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 tex_coord;
+layout(location = 1) in vec4 color;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 1, binding = 0) uniform sampler2D tex;
+
+void main() {
+    f_color = texture(tex, tex_coord) * color;
+}
+"]
+    struct Dummy;
+}
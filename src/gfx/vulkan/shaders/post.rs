@@ -0,0 +1,148 @@
+pub(crate) mod vs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "vertex"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 position;
+
+layout(location = 0) out vec2 v_uv;
+
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+    v_uv = position * 0.5 + 0.5;
+}
+"]
+    struct Dummy;
+}
+
+pub(crate) mod fs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "fragment"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D source;
+
+layout(push_constant) uniform Effect {
+    vec4 params;
+    vec2 texel_size;
+    uint effect;
+} effect;
+
+const uint EFFECT_COPY = 0;
+const uint EFFECT_TONEMAP = 1;
+const uint EFFECT_BLOOM = 2;
+const uint EFFECT_GAMMA = 3;
+const uint EFFECT_VIGNETTE = 4;
+const uint EFFECT_FXAA = 5;
+
+const float PI = 3.14159265359;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+// filmic curve fitted to ACES, by Krzysztof Narkowicz.
+vec3 tonemap(vec3 color) {
+    color *= effect.params.x;
+    vec3 mapped = (color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14);
+    return clamp(mapped, 0.0, 1.0);
+}
+
+vec3 bloom(vec3 color) {
+    float threshold = effect.params.x;
+    float intensity = effect.params.y;
+    float radius = effect.params.z;
+
+    vec3 sum = vec3(0.0);
+
+    // two rings of samples, offset from each other.
+    for (int ring = 1; ring <= 2; ring++) {
+        for (int i = 0; i < 8; i++) {
+            float angle = (float(i) + float(ring) * 0.5) * PI / 4.0;
+            vec2 offset = vec2(cos(angle), sin(angle)) * radius * float(ring) / 2.0;
+            vec3 s = texture(source, uv + offset * effect.texel_size).rgb;
+            sum += max(s - vec3(threshold), vec3(0.0));
+        }
+    }
+
+    return color + sum / 16.0 * intensity;
+}
+
+vec3 vignette(vec3 color) {
+    float strength = effect.params.x;
+    float radius = effect.params.y;
+    float distance = length(uv - 0.5) * 1.41421356;
+    return color * (1.0 - strength * smoothstep(radius, 1.0, distance));
+}
+
+// FXAA, based on the simplified version of FXAA 3.11 by Timothy Lottes.
+vec3 fxaa(vec3 color) {
+    const float REDUCE_MIN = 1.0 / 128.0;
+    const float REDUCE_MUL = 1.0 / 8.0;
+    const float SPAN_MAX = 8.0;
+
+    vec2 texel = effect.texel_size;
+
+    float nw = luma(texture(source, uv + vec2(-1.0, -1.0) * texel).rgb);
+    float ne = luma(texture(source, uv + vec2(1.0, -1.0) * texel).rgb);
+    float sw = luma(texture(source, uv + vec2(-1.0, 1.0) * texel).rgb);
+    float se = luma(texture(source, uv + vec2(1.0, 1.0) * texel).rgb);
+    float m = luma(color);
+
+    float luma_min = min(m, min(min(nw, ne), min(sw, se)));
+    float luma_max = max(m, max(max(nw, ne), max(sw, se)));
+
+    vec2 dir = vec2(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+
+    float reduce = max((nw + ne + sw + se) * (0.25 * REDUCE_MUL), REDUCE_MIN);
+    float rcp_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+    dir = clamp(dir * rcp_min, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+    vec3 a = 0.5 * (
+        texture(source, uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+        texture(source, uv + dir * (2.0 / 3.0 - 0.5)).rgb
+    );
+
+    vec3 b = a * 0.5 + 0.25 * (
+        texture(source, uv + dir * -0.5).rgb +
+        texture(source, uv + dir * 0.5).rgb
+    );
+
+    float luma_b = luma(b);
+
+    if (luma_b < luma_min || luma_b > luma_max) {
+        return a;
+    }
+
+    return b;
+}
+
+void main() {
+    vec4 color = texture(source, uv);
+
+    if (effect.effect == EFFECT_TONEMAP) {
+        color.rgb = tonemap(color.rgb);
+    } else if (effect.effect == EFFECT_BLOOM) {
+        color.rgb = bloom(color.rgb);
+    } else if (effect.effect == EFFECT_GAMMA) {
+        color.rgb = pow(max(color.rgb, vec3(0.0)), vec3(1.0 / effect.params.x));
+    } else if (effect.effect == EFFECT_VIGNETTE) {
+        color.rgb = vignette(color.rgb);
+    } else if (effect.effect == EFFECT_FXAA) {
+        color.rgb = fxaa(color.rgb);
+    }
+
+    f_color = vec4(color.rgb, 1.0);
+}
+"]
+    struct Dummy;
+}
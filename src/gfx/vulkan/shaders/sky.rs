@@ -0,0 +1,55 @@
+pub(crate) mod vs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "vertex"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 position;
+
+layout(location = 0) out vec2 v_position;
+
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+    v_position = position;
+}
+"]
+    struct Dummy;
+}
+
+pub(crate) mod fs {
+    #![allow(dead_code)]
+
+    #[derive(VulkanoShader)]
+    #[ty = "fragment"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform Sky {
+    mat4 inverse_projection;
+    mat4 inverse_view;
+} sky;
+
+layout(set = 1, binding = 0) uniform sampler2D environment;
+
+const float PI = 3.14159265359;
+
+void main() {
+    vec4 ray = sky.inverse_projection * vec4(position, 1.0, 1.0);
+    vec3 direction = normalize(mat3(sky.inverse_view) * (ray.xyz / ray.w));
+
+    vec2 uv = vec2(
+        atan(direction.x, -direction.z) / (2.0 * PI) + 0.5,
+        acos(clamp(direction.y, -1.0, 1.0)) / PI
+    );
+
+    // explicit level, since derivatives break down where longitude wraps around.
+    f_color = textureLod(environment, uv, 0.0);
+}
+"]
+    struct Dummy;
+}
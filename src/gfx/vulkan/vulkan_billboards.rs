@@ -0,0 +1,181 @@
+use super::{Pl, Rp, UniformBillboards};
+use super::shaders::billboard::{fs, vs};
+use super::vulkan_frame_stats::VulkanFrameStats;
+use super::vulkan_overlay::VulkanOverlay;
+use super::vulkan_pipeline::build_billboard_pipeline;
+use cgmath::{Matrix4, Vector3, Vector4};
+use gfx::billboard::{Billboard, BillboardSource};
+use gfx::errors::*;
+use std::cmp::Ordering;
+use std::sync::Arc;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Device;
+
+#[derive(Copy, Clone, Debug)]
+pub struct BillboardVertex {
+    pub position: [f32; 3],
+    pub tex_coord: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl_vertex!(BillboardVertex, position, tex_coord, color);
+
+/// Renders camera-facing billboards in the 3D scene.
+pub struct VulkanBillboards {
+    vs: vs::Shader,
+    fs: fs::Shader,
+    pipeline: Arc<Pl>,
+    billboards_pool: CpuBufferPool<UniformBillboards>,
+    /// All registered sources, read every frame.
+    sources: Vec<Box<BillboardSource>>,
+    /// Billboards of the source currently being drawn, kept to avoid reallocating every frame.
+    scratch: Vec<Billboard>,
+}
+
+impl VulkanBillboards {
+    pub fn new(device: &Arc<Device>, render_pass: &Arc<Rp>) -> Result<VulkanBillboards> {
+        let vs = vs::Shader::load(device.clone())?;
+        let fs = fs::Shader::load(device.clone())?;
+        let pipeline = build_billboard_pipeline(device, render_pass, &vs, &fs)?;
+
+        Ok(VulkanBillboards {
+            vs: vs,
+            fs: fs,
+            pipeline: pipeline,
+            billboards_pool: CpuBufferPool::uniform_buffer(device.clone()),
+            sources: Vec::new(),
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Rebuild the pipeline for a new render pass.
+    pub fn rebuild(&mut self, device: &Arc<Device>, render_pass: &Arc<Rp>) -> Result<()> {
+        self.pipeline = build_billboard_pipeline(device, render_pass, &self.vs, &self.fs)?;
+        Ok(())
+    }
+
+    /// Register a source of billboards.
+    pub fn add(&mut self, source: Box<BillboardSource>) {
+        self.sources.push(source);
+    }
+
+    /// Draw the billboards of all sources.
+    ///
+    /// Textures are shared with the overlay, so billboards can use any registered texture.
+    pub fn draw(
+        &mut self,
+        mut cb: AutoCommandBufferBuilder,
+        device: &Arc<Device>,
+        state: DynamicState,
+        overlay: &VulkanOverlay,
+        projection: Matrix4<f32>,
+        view: Matrix4<f32>,
+        stats: &mut VulkanFrameStats,
+    ) -> Result<AutoCommandBufferBuilder> {
+        if self.sources.is_empty() {
+            return Ok(cb);
+        }
+
+        // the rows of the view matrix are the axes of the camera in world space.
+        let right = Vector3::new(view.x.x, view.y.x, view.z.x);
+        let up = Vector3::new(view.x.y, view.y.y, view.z.y);
+
+        let uniform = self.billboards_pool.next(UniformBillboards {
+            view_projection: (projection * view).into(),
+        });
+
+        let billboards_set = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+            .add_buffer(uniform)?
+            .build()?);
+
+        stats.descriptor_set_allocations += 1;
+
+        for source in &self.sources {
+            self.scratch.clear();
+            source.billboards(&mut self.scratch)?;
+
+            if self.scratch.is_empty() {
+                continue;
+            }
+
+            let texture = source.texture()?;
+
+            let texture_set = match overlay.texture_set(texture) {
+                Some(set) => set,
+                None => {
+                    warn!("{:?}: texture not registered", texture);
+                    continue;
+                }
+            };
+
+            // translucent billboards have to be drawn back to front.
+            self.scratch.sort_by(|a, b| {
+                let a = depth(&view, a);
+                let b = depth(&view, b);
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            });
+
+            let mut vertices = Vec::with_capacity(self.scratch.len() * 6);
+
+            for billboard in &self.scratch {
+                vertices.extend(billboard_vertices(billboard, right, up).iter().cloned());
+            }
+
+            let vertex_buffer = CpuAccessibleBuffer::from_iter(
+                device.clone(),
+                BufferUsage::vertex_buffer(),
+                vertices.into_iter(),
+            )?;
+
+            stats.buffer_allocations += 1;
+
+            cb = cb.draw(
+                self.pipeline.clone(),
+                state.clone(),
+                vec![vertex_buffer as Arc<BufferAccess + Send + Sync>],
+                (billboards_set.clone(), texture_set),
+                (),
+            )?;
+        }
+
+        Ok(cb)
+    }
+}
+
+/// Depth of a billboard in view space, where more negative is further away.
+fn depth(view: &Matrix4<f32>, billboard: &Billboard) -> f32 {
+    let p = billboard.position;
+    (view * Vector4::new(p[0], p[1], p[2], 1.0)).z
+}
+
+/// Two triangles covering the billboard, spanned by the right and up vectors of the camera.
+fn billboard_vertices(
+    billboard: &Billboard,
+    right: Vector3<f32>,
+    up: Vector3<f32>,
+) -> [BillboardVertex; 6] {
+    let center = Vector3::from(billboard.position);
+    let half = billboard.size * 0.5;
+    let color: [f32; 4] = billboard.color.into();
+
+    let vertex = |x: f32, y: f32| {
+        let position = center + right * ((x * 2.0 - 1.0) * half) + up * ((1.0 - y * 2.0) * half);
+
+        BillboardVertex {
+            position: position.into(),
+            tex_coord: [x, y],
+            color: color,
+        }
+    };
+
+    [
+        vertex(0.0, 0.0),
+        vertex(1.0, 0.0),
+        vertex(0.0, 1.0),
+        vertex(0.0, 1.0),
+        vertex(1.0, 0.0),
+        vertex(1.0, 1.0),
+    ]
+}
@@ -0,0 +1,135 @@
+use super::{Pl, Rp, UniformLines};
+use super::shaders::lines::{fs, vs};
+use super::vulkan_frame_stats::VulkanFrameStats;
+use super::vulkan_pipeline::build_lines_pipeline;
+use cgmath::Matrix4;
+use gfx::debug_draw::{DebugDraw, DebugLine};
+use gfx::errors::*;
+use std::sync::Arc;
+use std::time::Instant;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Device;
+
+#[derive(Copy, Clone, Debug)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl_vertex!(LineVertex, position, color);
+
+/// Renders debug lines in the 3D scene.
+pub struct VulkanDebugLines {
+    vs: vs::Shader,
+    fs: fs::Shader,
+    pipeline: Arc<Pl>,
+    lines_pool: CpuBufferPool<UniformLines>,
+    /// Lines which are replaced by the next batch without a duration.
+    frame: Vec<DebugLine>,
+    /// Lines which are drawn until the given instant.
+    timed: Vec<(Instant, Vec<DebugLine>)>,
+}
+
+impl VulkanDebugLines {
+    pub fn new(device: &Arc<Device>, render_pass: &Arc<Rp>) -> Result<VulkanDebugLines> {
+        let vs = vs::Shader::load(device.clone())?;
+        let fs = fs::Shader::load(device.clone())?;
+        let pipeline = build_lines_pipeline(device, render_pass, &vs, &fs)?;
+
+        Ok(VulkanDebugLines {
+            vs: vs,
+            fs: fs,
+            pipeline: pipeline,
+            lines_pool: CpuBufferPool::uniform_buffer(device.clone()),
+            frame: Vec::new(),
+            timed: Vec::new(),
+        })
+    }
+
+    /// Rebuild the pipeline for a new render pass.
+    pub fn rebuild(&mut self, device: &Arc<Device>, render_pass: &Arc<Rp>) -> Result<()> {
+        self.pipeline = build_lines_pipeline(device, render_pass, &self.vs, &self.fs)?;
+        Ok(())
+    }
+
+    /// Add a batch of lines.
+    pub fn add(&mut self, draw: DebugDraw) {
+        match draw.duration {
+            Some(duration) => {
+                if !draw.lines.is_empty() {
+                    self.timed.push((Instant::now() + duration, draw.lines));
+                }
+            }
+            None => self.frame = draw.lines,
+        }
+    }
+
+    /// Draw all current lines, and forget the ones which have expired.
+    pub fn draw(
+        &mut self,
+        cb: AutoCommandBufferBuilder,
+        device: &Arc<Device>,
+        state: DynamicState,
+        view_projection: Matrix4<f32>,
+        stats: &mut VulkanFrameStats,
+    ) -> Result<AutoCommandBufferBuilder> {
+        let now = Instant::now();
+        self.timed.retain(|&(until, _)| until > now);
+
+        let count = self.frame.len() + self.timed.iter().map(|t| t.1.len()).sum::<usize>();
+
+        if count == 0 {
+            return Ok(cb);
+        }
+
+        let mut vertices = Vec::with_capacity(count * 2);
+
+        {
+            let lines = self.frame.iter().chain(
+                self.timed.iter().flat_map(|t| t.1.iter()),
+            );
+
+            for line in lines {
+                let color: [f32; 4] = line.color.into();
+
+                vertices.push(LineVertex {
+                    position: line.from,
+                    color: color,
+                });
+
+                vertices.push(LineVertex {
+                    position: line.to,
+                    color: color,
+                });
+            }
+        }
+
+        let vertex_buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::vertex_buffer(),
+            vertices.into_iter(),
+        )?;
+
+        stats.buffer_allocations += 1;
+
+        let uniform = self.lines_pool.next(
+            UniformLines { view_projection: view_projection.into() },
+        );
+
+        let lines_set = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+            .add_buffer(uniform)?
+            .build()?);
+
+        stats.descriptor_set_allocations += 1;
+
+        Ok(cb.draw(
+            self.pipeline.clone(),
+            state,
+            vec![vertex_buffer as Arc<BufferAccess + Send + Sync>],
+            lines_set,
+            (),
+        )?)
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+/// Statistics collected over a number of frames, reported roughly once every second.
+pub struct VulkanFrameStats {
+    start: Instant,
+    frames: u64,
+    frame_time: Duration,
+    /// Buffers allocated while building frames.
+    pub buffer_allocations: u64,
+    /// Descriptor sets allocated while building frames.
+    pub descriptor_set_allocations: u64,
+}
+
+impl VulkanFrameStats {
+    pub fn new() -> VulkanFrameStats {
+        VulkanFrameStats {
+            start: Instant::now(),
+            frames: 0u64,
+            frame_time: Duration::new(0, 0),
+            buffer_allocations: 0u64,
+            descriptor_set_allocations: 0u64,
+        }
+    }
+
+    /// Record a completed frame, and how long it took to build.
+    pub fn frame(&mut self, frame_time: Duration) {
+        self.frames += 1;
+        self.frame_time += frame_time;
+
+        if self.start.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+
+        let frames = self.frames as f64;
+        let frame_time = self.frame_time / self.frames as u32;
+
+        debug!(
+            "frame time = {}.{:03}ms, buffer allocations/frame = {:.2}, descriptor sets/frame = {:.2}",
+            frame_time.as_secs() * 1000 + frame_time.subsec_nanos() as u64 / 1_000_000,
+            (frame_time.subsec_nanos() / 1_000) % 1_000,
+            self.buffer_allocations as f64 / frames,
+            self.descriptor_set_allocations as f64 / frames
+        );
+
+        *self = VulkanFrameStats::new();
+    }
+}
@@ -1,16 +1,16 @@
-use super::vulkan_primitives::VulkanPrimitives;
+use gfx::MeshId;
 use gfx::geometry::Geometry;
 
 pub struct VulkanGeometry {
     pub geometry: Box<Geometry>,
-    pub primitives: VulkanPrimitives,
+    pub mesh_id: MeshId,
 }
 
 impl VulkanGeometry {
-    pub fn new(geometry: Box<Geometry>, primitives: VulkanPrimitives) -> VulkanGeometry {
+    pub fn new(geometry: Box<Geometry>, mesh_id: MeshId) -> VulkanGeometry {
         VulkanGeometry {
             geometry: geometry,
-            primitives: primitives,
+            mesh_id: mesh_id,
         }
     }
 }
@@ -1,17 +1,38 @@
 use gfx::camera_object::CameraObject;
+use gfx::{MeshId, TextureId};
+use gfx::billboard::BillboardSource;
 use gfx::command::Command;
+use gfx::debug_draw::DebugDraw;
+use gfx::environment::Environment;
 use gfx::errors::*;
 use gfx::geometry_object::GeometryObject;
+use gfx::overlay::Overlay;
+use gfx::post_process::PostProcess;
+use gfx::primitives::Primitives;
+use gfx::render_mode::RenderMode;
 use std::sync::mpsc;
+use texture::Texture;
 
 #[derive(Clone)]
 pub struct VulkanGfx {
     send: mpsc::Sender<Command>,
+    /// Overlays are sent separately, since only the most recent one is drawn.
+    overlay_send: mpsc::Sender<Overlay>,
+    /// Debug lines are sent separately, so that they are never held back by uploads.
+    debug_send: mpsc::Sender<DebugDraw>,
 }
 
 impl VulkanGfx {
-    pub fn new(send: mpsc::Sender<Command>) -> VulkanGfx {
-        VulkanGfx { send: send }
+    pub fn new(
+        send: mpsc::Sender<Command>,
+        overlay_send: mpsc::Sender<Overlay>,
+        debug_send: mpsc::Sender<DebugDraw>,
+    ) -> VulkanGfx {
+        VulkanGfx {
+            send: send,
+            overlay_send: overlay_send,
+            debug_send: debug_send,
+        }
     }
 
     pub fn clear(&self) -> Result<()> {
@@ -30,10 +51,111 @@ impl VulkanGfx {
         Ok(())
     }
 
+    /// Change the number of samples used for multisample anti-aliasing.
+    ///
+    /// The closest sample count supported by the device will be used.
+    pub fn set_samples(&self, samples: u32) -> Result<()> {
+        self.send.send(Command::SetSamples(samples)).map_err(
+            |_| ErrorKind::SendError,
+        )?;
+
+        Ok(())
+    }
+
+    /// Change how geometry is rendered, used to inspect topology, normals, texture coordinates
+    /// and depth.
+    pub fn set_render_mode(&self, render_mode: RenderMode) -> Result<()> {
+        self.send
+            .send(Command::SetRenderMode(render_mode))
+            .map_err(|_| ErrorKind::SendError)?;
+
+        Ok(())
+    }
+
+    /// Change the background and ambient lighting of the scene.
+    pub fn set_environment(&self, environment: Environment) -> Result<()> {
+        self.send
+            .send(Command::SetEnvironment(environment))
+            .map_err(|_| ErrorKind::SendError)?;
+
+        Ok(())
+    }
+
+    /// Replace the chain of post-processing effects applied to the scene.
+    pub fn set_post_process(&self, post_process: PostProcess) -> Result<()> {
+        self.send
+            .send(Command::SetPostProcess(post_process))
+            .map_err(|_| ErrorKind::SendError)?;
+
+        Ok(())
+    }
+
+    /// Register a texture, which can then be drawn in the overlay.
+    pub fn register_texture(&self, texture: Texture) -> Result<TextureId> {
+        let id = TextureId::allocate();
+
+        self.send.send(Command::AddTexture(id, texture)).map_err(
+            |_| ErrorKind::SendError,
+        )?;
+
+        Ok(id)
+    }
+
+    /// Replace the 2D overlay drawn on top of the scene.
+    ///
+    /// The overlay stays on screen until it is replaced.
+    pub fn set_overlay(&self, overlay: Overlay) -> Result<()> {
+        self.overlay_send.send(overlay).map_err(
+            |_| ErrorKind::SendError,
+        )?;
+
+        Ok(())
+    }
+
+    /// Draw a batch of debug lines in the scene.
+    ///
+    /// A batch without a duration replaces the previous batch without a duration, while batches
+    /// with a duration stay on screen until it has elapsed.
+    pub fn debug_draw(&self, draw: DebugDraw) -> Result<()> {
+        self.debug_send.send(draw).map_err(|_| ErrorKind::SendError)?;
+        Ok(())
+    }
+
+    /// Register a source of billboards, like a particle emitter, which is drawn until the end
+    /// of the program.
+    pub fn register_billboards(&self, source: &BillboardSource) -> Result<()> {
+        self.send
+            .send(Command::AddBillboards(source.clone_billboard_source()))
+            .map_err(|_| ErrorKind::SendError)?;
+        Ok(())
+    }
+
+    /// Replace the primitives of an uploaded mesh, like when its model has been reloaded.
+    pub fn replace_mesh(&self, mesh_id: MeshId, primitives: Primitives) -> Result<()> {
+        self.send
+            .send(Command::ReplaceMesh(mesh_id, primitives))
+            .map_err(|_| ErrorKind::SendError)?;
+        Ok(())
+    }
+
     pub fn register_geometry(&self, geometry_object: &GeometryObject) -> Result<()> {
         self.send
             .send(Command::AddGeometry(geometry_object.geometry()))
             .map_err(|_| ErrorKind::SendError)?;
         Ok(())
     }
+
+    /// Stop drawing the geometry of the given object.
+    ///
+    /// The mesh of the geometry is released on the GPU once no other geometry draws it.
+    pub fn unregister_geometry(&self, geometry_object: &GeometryObject) -> Result<()> {
+        let geometry = geometry_object.geometry();
+        let id = geometry.read_lock()?.id();
+
+        self.send.send(Command::RemoveGeometry(id)).map_err(
+            |_| ErrorKind::SendError,
+        )?;
+
+        Ok(())
+    }
 }
@@ -34,9 +34,16 @@ impl VulkanGfxInstance {
 
     pub fn build_gfx(&self, window: Arc<Window>) -> Result<(VulkanGfx, VulkanGfxLoopBuilder)> {
         let (send, recv) = mpsc::channel();
-        let gfx_loop_builder =
-            VulkanGfxLoopBuilder::new(recv, self.instance.clone(), window.clone());
-        let gfx = VulkanGfx::new(send);
+        let (overlay_send, overlay_recv) = mpsc::channel();
+        let (debug_send, debug_recv) = mpsc::channel();
+        let gfx_loop_builder = VulkanGfxLoopBuilder::new(
+            recv,
+            overlay_recv,
+            debug_recv,
+            self.instance.clone(),
+            window.clone(),
+        );
+        let gfx = VulkanGfx::new(send, overlay_send, debug_send);
         Ok((gfx, gfx_loop_builder))
     }
 }
@@ -1,53 +1,125 @@
-use super::{UniformGlobal, UniformModel};
+use super::{Pl, PushConstants, Rp, UniformGlobal, UniformMaterial, UniformSkin};
 use super::shaders::basic::{fs, vs};
+use super::vulkan_billboards::VulkanBillboards;
+use super::vulkan_debug_lines::VulkanDebugLines;
+use super::vulkan_frame_stats::VulkanFrameStats;
 use super::vulkan_geometry::VulkanGeometry;
+use super::vulkan_instance_ring::VulkanInstanceRing;
+use super::vulkan_overlay::VulkanOverlay;
+use super::vulkan_pipeline::{build_pipeline, build_render_pass, build_wireframe_pipeline,
+                             clear_values, supported_samples};
+use super::vulkan_post_process::{VulkanPostProcess, VulkanTargets};
 use super::vulkan_primitive::VulkanPrimitive;
 use super::vulkan_primitives::VulkanPrimitives;
+use super::vulkan_skybox::VulkanSkybox;
+use super::vulkan_texture_cache::VulkanTextureCache;
+use super::vulkan_textures::VulkanSamplers;
 use cgmath::{Matrix4, Rad};
 use cgmath::prelude::*;
-use gfx::{GeometryId, Window};
-use gfx::Vertex;
+use gfx::{GeometryId, MeshId, Window};
+use gfx::{Instance, MAX_JOINTS, Vertex};
 use gfx::camera_object::CameraObject;
 use gfx::command::Command;
+use gfx::debug_draw::DebugDraw;
+use gfx::environment::Environment;
 use gfx::errors::*;
+use gfx::morph;
+use gfx::overlay::Overlay;
+use gfx::post_process::PostProcess;
 use gfx::primitive::Primitive;
+use gfx::primitives::Primitives;
+use gfx::render_mode::RenderMode;
 use image;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::f32;
 use std::mem;
 use std::sync::Arc;
 use std::sync::mpsc;
-use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use std::time::Instant;
+use texture;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, CpuBufferPool,
+                     ImmutableBuffer};
 use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
-use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
 use vulkano::device::{Device, Queue};
-use vulkano::format::{self, Format};
-use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, FramebufferBuilder, Subpass};
-use vulkano::framebuffer::RenderPassAbstract;
-use vulkano::image::AttachmentImage;
+use vulkano::format;
 use vulkano::image::Dimensions;
 use vulkano::image::SwapchainImage;
 use vulkano::image::immutable::ImmutableImage;
-use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
-use vulkano::pipeline::vertex::SingleBufferDefinition;
 use vulkano::pipeline::viewport::Viewport;
-use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::sampler::Sampler;
 use vulkano::swapchain::{self, AcquireError, Swapchain};
 use vulkano::sync::GpuFuture;
 
+/// Upper limit of bytes uploaded to the GPU in a single frame.
+///
+/// Uploads which are held back because of this are processed in the next frame, while all other
+/// commands are still processed right away.
+const UPLOAD_BUDGET: usize = 16 * 1024 * 1024;
+
+/// Near plane of the projection.
+const NEAR: f32 = 0.01;
+/// Far plane of the projection.
+const FAR: f32 = 100.0;
+
 pub struct VulkanGfxLoopTicker {
     recv: mpsc::Receiver<Command>,
+    overlay_recv: mpsc::Receiver<Overlay>,
+    debug_recv: mpsc::Receiver<DebugDraw>,
     window: Arc<Window>,
     device: Arc<Device>,
     queue: Arc<Queue>,
     swapchain: Arc<Swapchain>,
     images: Vec<Arc<SwapchainImage>>,
     /// complicated state
-    pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
-    render_pass: Arc<RenderPassAbstract + Send + Sync>,
-    depth_buffer: Arc<AttachmentImage>,
-    texture_sampler: Arc<Sampler>,
-    debug_image: Arc<ImmutableImage<format::R8G8B8A8Srgb>>,
+    vs: vs::Shader,
+    fs: fs::Shader,
+    pipeline: Arc<Pl>,
+    /// same as pipeline, but only rasterizing the edges of triangles.
+    /// Only available if the device supports non-solid fill modes.
+    wireframe_pipeline: Option<Arc<Pl>>,
+    render_pass: Arc<Rp>,
+    /// current render mode
+    render_mode: RenderMode,
+    /// number of samples used for multisample anti-aliasing
+    samples: u32,
+    samplers: VulkanSamplers,
+    debug_sampler: Arc<Sampler>,
+    debug_image: Arc<ImmutableImage<format::Format>>,
+    /// Pool for the global uniform, which is updated every frame.
+    global_pool: CpuBufferPool<UniformGlobal>,
+    /// Pool for joint palettes of skinned geometry, which are updated every frame.
+    skin_pool: CpuBufferPool<UniformSkin>,
+    /// Skin set used by geometry which is not skinned.
+    identity_skin_set: Arc<DescriptorSet + Send + Sync>,
+    /// Meshes uploaded to the GPU, shared by all geometries drawing them.
+    ///
+    /// Released once the last geometry drawing them is removed.
+    meshes: HashMap<MeshId, VulkanPrimitives>,
+    /// Persistent instance buffers for each mesh.
+    instance_rings: HashMap<MeshId, VulkanInstanceRing>,
+    /// Textures uploaded to the GPU, shared by all primitives using them.
+    textures: VulkanTextureCache,
+    /// Statistics for built frames.
+    stats: VulkanFrameStats,
+    /// Bytes uploaded to the GPU during the current frame.
+    uploaded: usize,
+    /// Uploads held back by the upload budget, in the order they were sent.
+    deferred: VecDeque<Command>,
+    /// 2D overlay drawn on top of the scene.
+    overlay: VulkanOverlay,
+    /// Debug lines drawn in the scene.
+    debug_lines: VulkanDebugLines,
+    /// Camera-facing billboards drawn in the scene, like particles.
+    billboards: VulkanBillboards,
+    /// Skybox drawn behind all geometry.
+    skybox: VulkanSkybox,
+    /// Color of the background where there is no skybox.
+    clear_color: [f32; 4],
+    /// Ambient light added to all geometry.
+    ambient: [f32; 4],
+    /// Post-processing applied to the scene before it ends up on screen.
+    post_process: VulkanPostProcess,
     /// Current registered geometry.
     visible: HashMap<GeometryId, VulkanGeometry>,
     /// Current camera.
@@ -58,8 +130,8 @@ pub struct VulkanGfxLoopTicker {
     recreate_swapchain: bool,
     /// last known window dimensions
     dimensions: [u32; 2],
-    /// loaded framebuffers
-    framebuffers: Option<Vec<Arc<FramebufferAbstract + Send + Sync>>>,
+    /// render targets for the current dimensions
+    targets: Option<VulkanTargets>,
 }
 
 impl VulkanGfxLoopTicker {
@@ -84,27 +156,20 @@ impl VulkanGfxLoopTicker {
             mem::replace(&mut self.swapchain, new_swapchain);
             mem::replace(&mut self.images, new_images);
 
-            self.framebuffers = None;
+            self.targets = None;
             self.recreate_swapchain = false;
         }
 
-        if self.framebuffers.is_none() {
-            let new_framebuffers = {
-                let mut out: Vec<Arc<FramebufferAbstract + Sync + Send>> = Vec::new();
-
-                for image in &self.images {
-                    let fb = Framebuffer::start(self.render_pass.clone())
-                        .add(image.clone())
-                        .and_then(|b| b.add(self.depth_buffer.clone()))
-                        .and_then(FramebufferBuilder::build)?;
-
-                    out.push(Arc::new(fb));
-                }
-
-                out
-            };
+        if self.targets.is_none() {
+            let new_targets = self.post_process.build_targets(
+                &self.device,
+                &self.render_pass,
+                &self.images,
+                self.dimensions,
+                self.samples,
+            )?;
 
-            mem::replace(&mut self.framebuffers, Some(new_framebuffers));
+            mem::replace(&mut self.targets, Some(new_targets));
         }
 
         let (image_num, acquire_future) =
@@ -119,34 +184,32 @@ impl VulkanGfxLoopTicker {
                 }
             };
 
+        let before = Instant::now();
+
         /// Fill up with draw-calls.
         let mut cb = AutoCommandBufferBuilder::primary_one_time_submit(
             self.device.clone(),
             self.queue.family(),
         )?;
 
-        // Get the selected framebuffer.
-        let current_framebuffer = self.framebuffers
-            .as_ref()
-            .and_then(|fb| fb.get(image_num))
-            .ok_or(ErrorKind::NoFramebuffer)?
-            .clone();
+        let targets = self.targets.clone().ok_or(ErrorKind::NoFramebuffer)?;
 
+        // the scene is drawn offscreen, and ends up in the swapchain image after post-processing.
         cb = cb.begin_render_pass(
-            current_framebuffer,
+            targets.scene.clone(),
             false,
-            vec![[0.0, 0.0, 0.0, 1.0].into(), 1f32.into()],
+            clear_values(self.clear_color, self.samples),
         )?;
 
-        let global_buffer = {
+        let (global_buffer, projection, view) = {
             let projection = ::cgmath::perspective(
                 Rad(f32::consts::FRAC_PI_2),
                 {
                     let d = self.dimensions;
                     d[0] as f32 / d[1] as f32
                 },
-                0.01,
-                100.0,
+                NEAR,
+                FAR,
             );
 
             let view = if let Some(ref mut camera) = self.camera {
@@ -156,20 +219,24 @@ impl VulkanGfxLoopTicker {
             };
 
             let scale = Matrix4::from_scale(1.0);
+            let view = view * scale;
 
             let global = UniformGlobal {
                 camera: <Matrix4<f32> as SquareMatrix>::identity().into(),
-                view: (view * scale).into(),
+                view: view.into(),
                 projection: projection.into(),
             };
 
-            CpuAccessibleBuffer::<UniformGlobal>::from_data(
-                self.device.clone(),
-                BufferUsage::all(),
-                global,
-            )?
+            (self.global_pool.next(global), projection, view)
         };
 
+        let global_set = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+            .add_buffer(global_buffer)?
+            .add_sampled_image(self.debug_image.clone(), self.debug_sampler.clone())?
+            .build()?);
+
+        self.stats.descriptor_set_allocations += 1;
+
         let state = DynamicState {
             line_width: None,
             viewports: Some(vec![
@@ -182,54 +249,196 @@ impl VulkanGfxLoopTicker {
             scissors: None,
         };
 
+        cb = self.skybox.draw(
+            cb,
+            state.clone(),
+            projection,
+            view,
+            &mut self.stats,
+        )?;
+
+        let pipeline = match (self.render_mode, self.wireframe_pipeline.as_ref()) {
+            (RenderMode::Wireframe, Some(wireframe_pipeline)) => wireframe_pipeline.clone(),
+            _ => self.pipeline.clone(),
+        };
+
+        let push_constants = PushConstants {
+            mode: match self.render_mode {
+                RenderMode::Shaded => 0,
+                RenderMode::Wireframe => 1,
+                RenderMode::Normals => 2,
+                RenderMode::UvChecker => 3,
+                RenderMode::Depth => 4,
+            },
+            ambient: self.ambient,
+            near: NEAR,
+            far: FAR,
+        };
+
+        // group all visible geometry by mesh, so that each mesh is drawn once.
+        let mut instances: HashMap<MeshId, Vec<Instance>> = HashMap::new();
+        // animated geometry has its own joints or morph weights, and is drawn one at a time.
+        let mut animated = Vec::new();
+
         for entry in self.visible.values() {
-            let VulkanGeometry {
-                ref primitives,
-                ref geometry,
-            } = *entry;
+            let g = entry.geometry.read_lock()?;
+
+            let instance = Instance { transformation: g.transformation()?.into() };
+
+            match (g.joints()?, g.morph_weights()?) {
+                (None, None) => {
+                    instances.entry(entry.mesh_id).or_insert_with(Vec::new).push(
+                        instance,
+                    )
+                }
+                (joints, weights) => {
+                    let morph = match weights {
+                        Some(weights) => Some((g.primitives()?, weights)),
+                        None => None,
+                    };
+
+                    animated.push((entry.mesh_id, instance, joints, morph));
+                }
+            }
+        }
+
+        for (mesh_id, instances) in instances {
+            let primitives = match self.meshes.get(&mesh_id) {
+                Some(primitives) => primitives,
+                None => continue,
+            };
+
+            let (instance_buffer, allocated) = self.instance_rings
+                .entry(mesh_id)
+                .or_insert_with(VulkanInstanceRing::new)
+                .upload(&self.device, &instances)?;
+
+            if allocated {
+                self.stats.buffer_allocations += 1;
+            }
 
             for p in &primitives.primitives {
-                let VulkanPrimitive {
-                    ref vertex_buffer,
-                    ref index_buffer,
-                    ref base_color_factor,
-                    ref base_color_texture,
-                    ref use_base_color_texture,
-                    ..
-                } = *p;
-
-                let geometry = geometry.read_lock()?;
-                let transformation = geometry.transformation()?;
-
-                let model = UniformModel {
-                    model: transformation.into(),
-                    base_color_factor: *base_color_factor,
-                    use_base_color_texture: *use_base_color_texture as u32,
-                };
+                cb = cb.draw_indexed(
+                        pipeline.clone(),
+                        state.clone(),
+                        vec![
+                            p.vertex_buffer.clone() as Arc<BufferAccess + Send + Sync>,
+                            instance_buffer.clone(),
+                        ],
+                        p.index_buffer.clone(),
+                        (
+                            global_set.clone(),
+                            p.material_set.clone(),
+                            self.identity_skin_set.clone(),
+                        ),
+                        push_constants
+                    )?;
+            }
+        }
 
-                let model_buffer =
-                    CpuAccessibleBuffer::from_data(self.device.clone(), BufferUsage::all(), model)?;
+        let mut blended = Vec::new();
 
-                let set = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 0)
-                    .add_buffer(global_buffer.clone())?
-                    .add_buffer(model_buffer.clone())?
-                    .build()?);
+        for (mesh_id, instance, joints, morphing) in animated {
+            let primitives = match self.meshes.get(&mesh_id) {
+                Some(primitives) => primitives,
+                None => continue,
+            };
 
-                let texture = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 1)
-                    .add_sampled_image(base_color_texture.clone(), self.texture_sampler.clone())?
-                    .build()?);
+            let skin_set = match joints {
+                Some(joints) => {
+                    let skin = self.skin_pool.next(skin_uniform(&joints));
+                    self.stats.descriptor_set_allocations += 1;
+
+                    Arc::new(PersistentDescriptorSet::start(pipeline.clone(), 2)
+                        .add_buffer(skin)?
+                        .build()?) as Arc<DescriptorSet + Send + Sync>
+                }
+                None => self.identity_skin_set.clone(),
+            };
+
+            let instance_buffer = CpuAccessibleBuffer::from_iter(
+                self.device.clone(),
+                BufferUsage::vertex_buffer(),
+                Some(instance).into_iter(),
+            )?;
+
+            self.stats.buffer_allocations += 1;
+
+            for (index, p) in primitives.primitives.iter().enumerate() {
+                // morph targets are blended on the CPU, and uploaded every frame.
+                let source = morphing.as_ref().and_then(|&(ref source, ref weights)| {
+                    source.primitives.get(index).map(|s| (s, weights))
+                });
+
+                let vertex_buffer = match source {
+                    Some((source, weights)) if !source.morph_targets.is_empty() => {
+                        morph::blend(
+                            &source.vertices,
+                            &source.morph_targets,
+                            weights,
+                            &mut blended,
+                        );
+
+                        self.stats.buffer_allocations += 1;
+
+                        CpuAccessibleBuffer::from_iter(
+                            self.device.clone(),
+                            BufferUsage::vertex_buffer(),
+                            blended.drain(..),
+                        )? as Arc<BufferAccess + Send + Sync>
+                    }
+                    _ => p.vertex_buffer.clone() as Arc<BufferAccess + Send + Sync>,
+                };
 
                 cb = cb.draw_indexed(
-                        self.pipeline.clone(),
+                        pipeline.clone(),
                         state.clone(),
-                        vec![vertex_buffer.clone()],
-                        index_buffer.clone(),
-                        (set, texture),
-                        ()
+                        vec![vertex_buffer, instance_buffer.clone()],
+                        p.index_buffer.clone(),
+                        (global_set.clone(), p.material_set.clone(), skin_set.clone()),
+                        push_constants
                     )?;
             }
         }
 
+        cb = self.billboards.draw(
+            cb,
+            &self.device,
+            state.clone(),
+            &self.overlay,
+            projection,
+            view,
+            &mut self.stats,
+        )?;
+
+        while let Ok(draw) = self.debug_recv.try_recv() {
+            self.debug_lines.add(draw);
+        }
+
+        cb = self.debug_lines.draw(
+            cb,
+            &self.device,
+            state,
+            projection * view,
+            &mut self.stats,
+        )?;
+
+        cb = cb.end_render_pass()?;
+
+        cb = self.post_process.draw(
+            cb,
+            &targets,
+            image_num,
+            self.dimensions,
+        )?;
+
+        // only the most recently submitted overlay is of interest.
+        while let Ok(overlay) = self.overlay_recv.try_recv() {
+            self.overlay.set(overlay);
+        }
+
+        cb = self.overlay.draw(cb, &self.device, self.dimensions, &mut self.stats)?;
+
         let cb = cb.end_render_pass()?;
         let cb = cb.build()?;
 
@@ -250,6 +459,7 @@ impl VulkanGfxLoopTicker {
             .then_swapchain_present(self.queue.clone(), self.swapchain.clone(), image_num)
             .then_signal_fence_and_flush()?));
 
+        self.stats.frame(before.elapsed());
         Ok(())
     }
 
@@ -279,88 +489,308 @@ impl VulkanGfxLoopTicker {
             SetCamera(camera) => {
                 self.camera = Some(camera);
             }
+            SetSamples(samples) => {
+                self.set_samples(samples)?;
+            }
+            SetRenderMode(RenderMode::Wireframe) if self.wireframe_pipeline.is_none() => {
+                warn!("wireframe mode is not supported by the device");
+            }
+            SetRenderMode(render_mode) => {
+                info!("render mode: {:?}", render_mode);
+                self.render_mode = render_mode;
+            }
+            SetPostProcess(post_process) => {
+                self.post_process.set(post_process);
+            }
+            SetEnvironment(environment) => {
+                if let Some(sky_future) = self.set_environment(environment)? {
+                    future = self.new_or_old_future(future, sky_future);
+                }
+            }
+            AddTexture(id, texture) => {
+                let tex_future = self.overlay.add_texture(
+                    &self.device,
+                    &self.queue,
+                    &mut self.samplers,
+                    id,
+                    &texture,
+                )?;
+
+                future = self.new_or_old_future(future, tex_future);
+                self.uploaded += texture.image_data.len();
+            }
+            AddBillboards(source) => {
+                self.billboards.add(source);
+            }
             AddGeometry(geometry) => {
                 let g = geometry.read_lock()?;
+                let mesh_id = g.mesh_id();
 
-                let mut primitives = Vec::new();
-
-                for p in g.primitives()?.primitives {
-                    let Primitive {
-                        vertices,
-                        indices,
-                        base_color_factor,
-                        base_color_texture,
-                        ..
-                    } = p;
-
-                    let vertex_buffer = CpuAccessibleBuffer::from_iter(
-                        self.device.clone(),
-                        BufferUsage::all(),
-                        vertices.into_iter(),
-                    )?;
+                if !self.meshes.contains_key(&mesh_id) {
+                    let (primitives, mesh_future) = self.upload_mesh(mesh_id, &g.primitives()?)?;
 
-                    let index_buffer = CpuAccessibleBuffer::from_iter(
-                        self.device.clone(),
-                        BufferUsage::all(),
-                        indices.into_iter(),
-                    )?;
+                    if let Some(mesh_future) = mesh_future {
+                        future = self.new_or_old_future(future, mesh_future);
+                    }
+
+                    self.meshes.insert(mesh_id, primitives);
+                }
+
+                self.visible.insert(
+                    g.id(),
+                    VulkanGeometry::new(geometry.clone_geometry(), mesh_id),
+                );
+            }
+            RemoveGeometry(id) => {
+                if let Some(removed) = self.visible.remove(&id) {
+                    self.release_unused_mesh(removed.mesh_id);
+                }
+            }
+            // meshes which are not uploaded are read from their geometry when it is added.
+            ReplaceMesh(mesh_id, _) if !self.meshes.contains_key(&mesh_id) => {
+                debug!("{:?}: not uploaded, ignoring replacement", mesh_id);
+            }
+            ReplaceMesh(mesh_id, primitives) => {
+                let (primitives, mesh_future) = self.upload_mesh(mesh_id, &primitives)?;
+
+                if let Some(mesh_future) = mesh_future {
+                    future = self.new_or_old_future(future, mesh_future);
+                }
+
+                info!("{:?}: replaced mesh", mesh_id);
+                self.meshes.insert(mesh_id, primitives);
+            }
+        }
+
+        Ok(future)
+    }
+
+    /// Release the buffers of a mesh, if no visible geometry draws it anymore.
+    ///
+    /// Frames in flight keep the buffers alive until the GPU is done with them.
+    fn release_unused_mesh(&mut self, mesh_id: MeshId) {
+        if self.visible.values().any(|v| v.mesh_id == mesh_id) {
+            return;
+        }
 
-                    let (base_color_texture, use_base_color_texture) =
-                        if let Some(base_color_texture) = base_color_texture {
-                            let (width, height) = base_color_texture.dimensions;
+        if self.meshes.remove(&mesh_id).is_some() {
+            info!("{:?}: released mesh", mesh_id);
+        }
+
+        self.instance_rings.remove(&mesh_id);
+        self.textures.purge();
+    }
+
+    /// Change the background and ambient lighting.
+    fn set_environment(&mut self, environment: Environment) -> Result<Option<Box<GpuFuture>>> {
+        let Environment {
+            clear_color,
+            skybox,
+            ambient,
+        } = environment;
+
+        self.clear_color = clear_color.into();
+
+        // ambient light takes after the sky, or the background color if there is no sky.
+        let sky_color = match skybox {
+            Some(ref skybox) => skybox.average_color(),
+            None => self.clear_color,
+        };
+
+        let strength = ambient.unwrap_or(0.0);
+
+        self.ambient = [
+            sky_color[0] * strength,
+            sky_color[1] * strength,
+            sky_color[2] * strength,
+            1.0,
+        ];
 
-                            info!("{:?}: loaded color texture ({}, {})", g.id(), width, height);
+        if let Some(ref skybox) = skybox {
+            self.uploaded += skybox.image_data.len();
+        }
 
-                            let (image, tex_future) = ImmutableImage::from_iter(
-                                base_color_texture.image_data.into_iter(),
-                                Dimensions::Dim2d {
-                                    width: width,
-                                    height: height,
-                                },
-                                format::R8G8B8A8Srgb,
-                                self.queue.clone(),
-                            )?;
+        self.skybox.set(
+            &self.device,
+            &self.queue,
+            &mut self.samplers,
+            skybox.as_ref(),
+        )
+    }
 
-                            future = self.new_or_old_future(future, tex_future);
-                            (image, true)
+    /// Change the number of samples used for multisample anti-aliasing.
+    ///
+    /// Rebuilds the render pass, pipeline and framebuffers.
+    fn set_samples(&mut self, samples: u32) -> Result<()> {
+        let samples = supported_samples(&self.device, samples);
+
+        if samples == self.samples {
+            return Ok(());
+        }
+
+        info!("using {}x multisampling", samples);
+
+        let render_pass = build_render_pass(&self.device, samples)?;
+        let pipeline = build_pipeline(&self.device, &render_pass, &self.vs, &self.fs, false)?;
+        let wireframe_pipeline =
+            build_wireframe_pipeline(&self.device, &render_pass, &self.vs, &self.fs)?;
+        self.debug_lines.rebuild(&self.device, &render_pass)?;
+        self.billboards.rebuild(&self.device, &render_pass)?;
+        self.skybox.rebuild(&self.device, &render_pass)?;
+
+        self.render_pass = render_pass;
+        self.pipeline = pipeline;
+        self.wireframe_pipeline = wireframe_pipeline;
+        self.samples = samples;
+        self.targets = None;
+        Ok(())
+    }
+
+    /// Upload all primitives of the given mesh to the GPU.
+    fn upload_mesh(
+        &mut self,
+        mesh_id: MeshId,
+        source: &Primitives,
+    ) -> Result<(VulkanPrimitives, Option<Box<GpuFuture>>)> {
+        let mut future = None;
+        let mut primitives = Vec::new();
+
+        for p in source.primitives.iter() {
+            let Primitive {
+                ref vertices,
+                ref indices,
+                ref base_color_factor,
+                ref base_color_texture,
+                ..
+            } = *p;
+
+            // uploaded through staging buffers into device-local memory.
+            let (vertex_buffer, vertex_future) = ImmutableBuffer::from_iter(
+                vertices.iter().cloned(),
+                BufferUsage::vertex_buffer(),
+                self.queue.clone(),
+            )?;
+
+            let (index_buffer, index_future) = ImmutableBuffer::from_iter(
+                indices.iter().cloned(),
+                BufferUsage::index_buffer(),
+                self.queue.clone(),
+            )?;
+
+            future = self.new_or_old_future(future, vertex_future);
+            future = self.new_or_old_future(future, index_future);
+
+            self.uploaded += vertices.len() * mem::size_of::<Vertex>() +
+                indices.len() * mem::size_of::<u32>();
+
+            let base_color_factor: [f32; 4] = (*base_color_factor).into();
+
+            // primitives sharing a texture and factor, like those remapped to an atlas, share
+            // their material set.
+            let shared_set = match *base_color_texture {
+                Some(ref texture) => self.textures.material_set(texture, base_color_factor),
+                None => None,
+            };
+
+            let material_set = match shared_set {
+                Some(material_set) => material_set,
+                None => {
+                    let (image, sampler, use_base_color_texture) =
+                        if let Some(ref texture) = *base_color_texture {
+                            let (image, tex_future) =
+                                self.textures.image(&self.device, &self.queue, texture)?;
+
+                            if let Some(tex_future) = tex_future {
+                                let dimensions = texture.dimensions;
+                                info!("{:?}: loaded color texture {:?}", mesh_id, dimensions);
+
+                                future = self.new_or_old_future(future, tex_future);
+                                self.uploaded += texture.image_data.len();
+                            }
+
+                            (image, self.samplers.get(&texture.sampler)?, true)
                         } else {
-                            (self.debug_image.clone(), false)
+                            (self.debug_image.clone(), self.debug_sampler.clone(), false)
                         };
 
-                    primitives.push(VulkanPrimitive::new(
-                        vertex_buffer,
-                        index_buffer,
-                        base_color_factor.into(),
-                        base_color_texture,
-                        use_base_color_texture,
-                    ));
-                }
+                    let material = UniformMaterial {
+                        base_color_factor: base_color_factor,
+                        use_base_color_texture: use_base_color_texture as u32,
+                    };
+
+                    // materials never change, so neither does their descriptor set.
+                    let (material_buffer, material_future) = ImmutableBuffer::from_data(
+                        material,
+                        BufferUsage::uniform_buffer(),
+                        self.queue.clone(),
+                    )?;
 
-                let primitives = VulkanPrimitives::new(primitives);
+                    future = self.new_or_old_future(future, material_future);
 
-                self.visible.insert(
-                    g.id(),
-                    VulkanGeometry::new(geometry.clone_geometry(), primitives),
-                );
-            }
+                    let material_set =
+                        Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 1)
+                            .add_buffer(material_buffer)?
+                            .add_sampled_image(image, sampler)?
+                            .build()?) as Arc<DescriptorSet + Send + Sync>;
+
+                    if let Some(ref texture) = *base_color_texture {
+                        self.textures.add_material_set(
+                            texture,
+                            base_color_factor,
+                            material_set.clone(),
+                        );
+                    }
+
+                    material_set
+                }
+            };
+
+            primitives.push(VulkanPrimitive::new(
+                vertex_buffer,
+                index_buffer,
+                material_set,
+            ));
         }
 
-        Ok(future)
+        Ok((VulkanPrimitives::new(primitives), future))
     }
 
     /// Check for geometry updates.
+    ///
+    /// Once the upload budget for this frame has been spent, uploads are held back until the
+    /// next frame, while all other commands are processed.
     fn check_for_updates(&mut self) -> Result<Option<Box<GpuFuture>>> {
         let mut future = None;
+        self.uploaded = 0;
+
+        while self.uploaded < UPLOAD_BUDGET {
+            let command = match self.deferred.pop_front() {
+                Some(command) => command,
+                None => break,
+            };
+
+            if let Some(added_future) = self.process_command(command)? {
+                future = self.new_or_old_future(future, added_future);
+            }
+        }
 
         loop {
-            match self.recv.try_recv() {
+            let command = match self.recv.try_recv() {
                 Err(mpsc::TryRecvError::Empty) => break,
                 Err(mpsc::TryRecvError::Disconnected) => return Err(ErrorKind::Disconnected.into()),
-                Ok(command) => {
-                    if let Some(added_future) = self.process_command(command)? {
-                        future = self.new_or_old_future(future, added_future);
-                    }
-                }
+                Ok(command) => command,
+            };
+
+            // uploads stay in order, behind those which are already held back.
+            if waits_for_uploads(&command) &&
+                (self.uploaded >= UPLOAD_BUDGET || !self.deferred.is_empty())
+            {
+                self.deferred.push_back(command);
+                continue;
+            }
+
+            if let Some(added_future) = self.process_command(command)? {
+                future = self.new_or_old_future(future, added_future);
             }
         }
 
@@ -368,31 +798,66 @@ impl VulkanGfxLoopTicker {
     }
 }
 
+/// Check if a command has to wait behind uploads held back by the upload budget.
+///
+/// Removing geometry waits as well, so that it never overtakes the upload adding it.
+fn waits_for_uploads(command: &Command) -> bool {
+    match *command {
+        Command::AddGeometry(_) |
+        Command::RemoveGeometry(_) |
+        Command::ReplaceMesh(..) |
+        Command::AddTexture(..) |
+        Command::SetEnvironment(_) => true,
+        _ => false,
+    }
+}
+
+/// Joint palette for the skin uniform, padded with identity matrices.
+fn skin_uniform(joints: &[Matrix4<f32>]) -> UniformSkin {
+    let identity: [[f32; 4]; 4] = <Matrix4<f32> as SquareMatrix>::identity().into();
+    let mut palette = [identity; MAX_JOINTS];
+
+    for (target, joint) in palette.iter_mut().zip(joints.iter()) {
+        *target = (*joint).into();
+    }
+
+    UniformSkin { palette: palette }
+}
+
 pub struct VulkanGfxLoop {
     recv: mpsc::Receiver<Command>,
+    overlay_recv: mpsc::Receiver<Overlay>,
+    debug_recv: mpsc::Receiver<DebugDraw>,
     window: Arc<Window>,
     device: Arc<Device>,
     queue: Arc<Queue>,
     swapchain: Arc<Swapchain>,
     images: Vec<Arc<SwapchainImage>>,
+    samples: u32,
 }
 
 impl VulkanGfxLoop {
     pub fn new(
         recv: mpsc::Receiver<Command>,
+        overlay_recv: mpsc::Receiver<Overlay>,
+        debug_recv: mpsc::Receiver<DebugDraw>,
         window: Arc<Window>,
         device: Arc<Device>,
         queue: Arc<Queue>,
         swapchain: Arc<Swapchain>,
         images: Vec<Arc<SwapchainImage>>,
+        samples: u32,
     ) -> VulkanGfxLoop {
         VulkanGfxLoop {
             recv: recv,
+            overlay_recv: overlay_recv,
+            debug_recv: debug_recv,
             window: window,
             device: device,
             queue: queue,
             swapchain: swapchain,
             images: images,
+            samples: samples,
         }
     }
 
@@ -402,61 +867,13 @@ impl VulkanGfxLoop {
         let vs = vs::Shader::load(self.device.clone())?;
         let fs = fs::Shader::load(self.device.clone())?;
 
-        let render_pass = Arc::new(single_pass_renderpass!(
-            self.device.clone(),
-            attachments: {
-                color: {
-                    load: Clear,
-                    store: Store,
-                    format: self.swapchain.format(),
-                    samples: 1,
-                },
-                depth: {
-                    load: Clear,
-                    store: DontCare,
-                    format: Format::D16Unorm,
-                    samples: 1,
-                }
-            },
-            pass: {
-                color: [color],
-                depth_stencil: {depth}
-            }
-        )?);
-
-        let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
-            ErrorKind::NoSubpass,
-        )?;
-
-        let pipeline: Arc<GraphicsPipelineAbstract + Send + Sync> =
-            Arc::new(GraphicsPipeline::start()
-                .vertex_input(SingleBufferDefinition::<Vertex>::new())
-                .vertex_shader(vs.main_entry_point(), ())
-                .triangle_list()
-                .viewports_dynamic_scissors_irrelevant(1)
-                .fragment_shader(fs.main_entry_point(), ())
-                .depth_stencil_simple_depth()
-                .render_pass(sub_pass)
-                .build(self.device.clone())?);
+        let samples = supported_samples(&self.device, self.samples);
+        let render_pass = build_render_pass(&self.device, samples)?;
+        let pipeline = build_pipeline(&self.device, &render_pass, &vs, &fs, false)?;
+        let wireframe_pipeline = build_wireframe_pipeline(&self.device, &render_pass, &vs, &fs)?;
 
-        let depth_buffer =
-            AttachmentImage::transient(self.device.clone(), dimensions, Format::D16Unorm)?;
-
-        let dimensions = self.window.dimensions()?;
-
-        let texture_sampler = Sampler::new(
-            self.device.clone(),
-            Filter::Linear,
-            Filter::Linear,
-            MipmapMode::Nearest,
-            SamplerAddressMode::Repeat,
-            SamplerAddressMode::Repeat,
-            SamplerAddressMode::Repeat,
-            0.0,
-            1.0,
-            0.0,
-            0.0,
-        )?;
+        let mut samplers = VulkanSamplers::new(self.device.clone());
+        let debug_sampler = samplers.get(&texture::Sampler::default())?;
 
         let (debug_image, debug_tex_future) = {
             let image = image::load_from_memory_with_format(
@@ -473,31 +890,92 @@ impl VulkanGfxLoop {
                     width: 512,
                     height: 512,
                 },
-                format::R8G8B8A8Srgb,
+                format::Format::R8G8B8A8Srgb,
                 self.queue.clone(),
             )?
         };
 
-        let previous_frame = Some(Box::new(debug_tex_future) as Box<GpuFuture>);
+        let (post_process, post_process_future) = VulkanPostProcess::new(
+            &self.device,
+            &self.queue,
+            self.swapchain.format(),
+            &mut samplers,
+        )?;
+
+        // the overlay is drawn on top of the post-processed scene.
+        let (overlay, overlay_future) = VulkanOverlay::new(
+            &self.device,
+            &self.queue,
+            post_process.final_pass(),
+            &mut samplers,
+        )?;
+
+        let debug_lines = VulkanDebugLines::new(&self.device, &render_pass)?;
+        let billboards = VulkanBillboards::new(&self.device, &render_pass)?;
+
+        let (skybox, skybox_future) = VulkanSkybox::new(&self.device, &self.queue, &render_pass)?;
+
+        let (identity_skin, identity_skin_future) = ImmutableBuffer::from_data(
+            skin_uniform(&[]),
+            BufferUsage::uniform_buffer(),
+            self.queue.clone(),
+        )?;
+
+        let identity_skin_set = Arc::new(PersistentDescriptorSet::start(pipeline.clone(), 2)
+            .add_buffer(identity_skin)?
+            .build()?);
+
+        let previous_frame = Some(Box::new(
+            debug_tex_future
+                .join(overlay_future)
+                .join(skybox_future)
+                .join(post_process_future)
+                .join(identity_skin_future),
+        ) as Box<GpuFuture>);
+
+        let global_pool = CpuBufferPool::uniform_buffer(self.device.clone());
 
         return Ok(VulkanGfxLoopTicker {
             recv: self.recv,
+            overlay_recv: self.overlay_recv,
+            debug_recv: self.debug_recv,
             window: self.window,
             device: self.device,
             queue: self.queue,
             swapchain: self.swapchain,
             images: self.images,
+            vs: vs,
+            fs: fs,
             pipeline: pipeline,
+            wireframe_pipeline: wireframe_pipeline,
             render_pass: render_pass,
-            depth_buffer: depth_buffer,
-            texture_sampler: texture_sampler,
+            render_mode: RenderMode::default(),
+            samples: samples,
+            samplers: samplers,
+            debug_sampler: debug_sampler,
             debug_image: debug_image,
+            global_pool: global_pool,
+            skin_pool: CpuBufferPool::uniform_buffer(self.device.clone()),
+            identity_skin_set: identity_skin_set,
+            meshes: HashMap::new(),
+            instance_rings: HashMap::new(),
+            textures: VulkanTextureCache::new(),
+            stats: VulkanFrameStats::new(),
+            uploaded: 0usize,
+            deferred: VecDeque::new(),
+            overlay: overlay,
+            debug_lines: debug_lines,
+            billboards: billboards,
+            skybox: skybox,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            ambient: [0.0, 0.0, 0.0, 1.0],
+            post_process: post_process,
             visible: HashMap::new(),
             camera: None,
             previous_frame: previous_frame,
             recreate_swapchain: false,
             dimensions: dimensions,
-            framebuffers: None,
+            targets: None,
         });
     }
 }
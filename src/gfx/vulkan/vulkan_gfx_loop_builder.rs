@@ -1,7 +1,9 @@
 use super::vulkan_gfx_loop::VulkanGfxLoop;
 use gfx::Window;
 use gfx::command::Command;
+use gfx::debug_draw::DebugDraw;
 use gfx::errors::*;
+use gfx::overlay::Overlay;
 use std::sync::Arc;
 use std::sync::mpsc;
 use vulkano::device::{self, Device};
@@ -10,23 +12,36 @@ use vulkano::swapchain::{PresentMode, SurfaceTransform, Swapchain};
 
 pub struct VulkanGfxLoopBuilder {
     recv: mpsc::Receiver<Command>,
+    overlay_recv: mpsc::Receiver<Overlay>,
+    debug_recv: mpsc::Receiver<DebugDraw>,
     instance: Arc<Instance>,
     window: Arc<Window>,
+    samples: u32,
 }
 
 impl VulkanGfxLoopBuilder {
     pub fn new(
         recv: mpsc::Receiver<Command>,
+        overlay_recv: mpsc::Receiver<Overlay>,
+        debug_recv: mpsc::Receiver<DebugDraw>,
         instance: Arc<Instance>,
         window: Arc<Window>,
     ) -> VulkanGfxLoopBuilder {
         VulkanGfxLoopBuilder {
             recv: recv,
+            overlay_recv: overlay_recv,
+            debug_recv: debug_recv,
             instance: instance,
             window: window,
+            samples: 1,
         }
     }
 
+    /// Set the number of samples to use for multisample anti-aliasing.
+    pub fn set_samples(&mut self, samples: u32) {
+        self.samples = samples;
+    }
+
     pub fn into_loop(self) -> Result<VulkanGfxLoop> {
         let physical = instance::PhysicalDevice::enumerate(&self.instance)
             .next()
@@ -83,11 +98,14 @@ impl VulkanGfxLoopBuilder {
 
         Ok(VulkanGfxLoop::new(
             self.recv,
+            self.overlay_recv,
+            self.debug_recv,
             self.window,
             device,
             queue,
             swapchain,
             images,
+            self.samples,
         ))
     }
 }
@@ -0,0 +1,71 @@
+use gfx::Instance;
+use gfx::errors::*;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, TypedBufferAccess};
+use vulkano::device::Device;
+
+/// Number of instance buffers kept around for each mesh.
+///
+/// Should be at least the number of frames that can be in flight at once.
+const RING_SIZE: usize = 4;
+
+/// Ring of persistent instance buffers for a single mesh.
+///
+/// Buffers are re-used across frames as long as the number of instances stays the same and the
+/// GPU is done reading from them. Otherwise a new buffer is allocated in its place.
+pub struct VulkanInstanceRing {
+    buffers: Vec<Arc<CpuAccessibleBuffer<[Instance]>>>,
+    next: usize,
+}
+
+impl VulkanInstanceRing {
+    pub fn new() -> VulkanInstanceRing {
+        VulkanInstanceRing {
+            buffers: Vec::new(),
+            next: 0usize,
+        }
+    }
+
+    /// Upload the given instances, returning the buffer they were written to and if it had to
+    /// be allocated.
+    pub fn upload(
+        &mut self,
+        device: &Arc<Device>,
+        instances: &[Instance],
+    ) -> Result<(Arc<CpuAccessibleBuffer<[Instance]>>, bool)> {
+        for offset in 0..self.buffers.len() {
+            let index = (self.next + offset) % self.buffers.len();
+            let buffer = &self.buffers[index];
+
+            if buffer.len() != instances.len() {
+                continue;
+            }
+
+            // the buffer is still in use by the GPU.
+            let mut lock = match buffer.write() {
+                Ok(lock) => lock,
+                Err(_) => continue,
+            };
+
+            lock.copy_from_slice(instances);
+            self.next = (index + 1) % self.buffers.len();
+            return Ok((buffer.clone(), false));
+        }
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::vertex_buffer(),
+            instances.iter().cloned(),
+        )?;
+
+        if self.buffers.len() < RING_SIZE {
+            self.buffers.push(buffer.clone());
+            self.next = 0;
+        } else {
+            self.buffers[self.next] = buffer.clone();
+            self.next = (self.next + 1) % self.buffers.len();
+        }
+
+        Ok((buffer, true))
+    }
+}
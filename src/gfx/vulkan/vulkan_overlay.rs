@@ -0,0 +1,253 @@
+use super::{Pl, Rp, UniformScreen};
+use super::shaders::overlay::{fs, vs};
+use super::vulkan_frame_stats::VulkanFrameStats;
+use super::vulkan_pipeline::build_overlay_pipeline;
+use super::vulkan_textures::{VulkanSamplers, upload_texture};
+use cgmath;
+use gfx::TextureId;
+use gfx::errors::*;
+use gfx::overlay::{Overlay, Quad, Rect};
+use std::collections::HashMap;
+use std::sync::Arc;
+use texture::Texture;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::device::{Device, Queue};
+use vulkano::pipeline::viewport::{Scissor, Viewport};
+use vulkano::sync::GpuFuture;
+
+#[derive(Copy, Clone, Debug)]
+pub struct OverlayVertex {
+    pub position: [f32; 2],
+    pub tex_coord: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl_vertex!(OverlayVertex, position, tex_coord, color);
+
+/// Renders the 2D overlay on top of the 3D scene.
+pub struct VulkanOverlay {
+    pipeline: Arc<Pl>,
+    screen_pool: CpuBufferPool<UniformScreen>,
+    /// Texture set used for solid colors, which samples a single white texel.
+    white: Arc<DescriptorSet + Send + Sync>,
+    /// Texture sets for all registered textures.
+    textures: HashMap<TextureId, Arc<DescriptorSet + Send + Sync>>,
+    /// The most recently submitted overlay.
+    current: Overlay,
+}
+
+impl VulkanOverlay {
+    pub fn new(
+        device: &Arc<Device>,
+        queue: &Arc<Queue>,
+        render_pass: &Arc<Rp>,
+        samplers: &mut VulkanSamplers,
+    ) -> Result<(VulkanOverlay, Box<GpuFuture>)> {
+        let vs = vs::Shader::load(device.clone())?;
+        let fs = fs::Shader::load(device.clone())?;
+        let pipeline = build_overlay_pipeline(device, render_pass, &vs, &fs)?;
+
+        let white = Texture::from_raw(vec![255u8; 4], (1, 1));
+        let (white, future) = texture_set(device, queue, &pipeline, samplers, &white)?;
+
+        let overlay = VulkanOverlay {
+            pipeline: pipeline,
+            screen_pool: CpuBufferPool::uniform_buffer(device.clone()),
+            white: white,
+            textures: HashMap::new(),
+            current: Overlay::new(),
+        };
+
+        Ok((overlay, future))
+    }
+
+    /// Register a texture which can be referenced by overlay quads.
+    pub fn add_texture(
+        &mut self,
+        device: &Arc<Device>,
+        queue: &Arc<Queue>,
+        samplers: &mut VulkanSamplers,
+        id: TextureId,
+        texture: &Texture,
+    ) -> Result<Box<GpuFuture>> {
+        let (set, future) = texture_set(device, queue, &self.pipeline, samplers, texture)?;
+        self.textures.insert(id, set);
+        Ok(future)
+    }
+
+    /// Texture set of a registered texture, or the white texture for `None`.
+    pub fn texture_set(&self, id: Option<TextureId>) -> Option<Arc<DescriptorSet + Send + Sync>> {
+        match id {
+            Some(id) => self.textures.get(&id).cloned(),
+            None => Some(self.white.clone()),
+        }
+    }
+
+    /// Replace the overlay being drawn.
+    pub fn set(&mut self, overlay: Overlay) {
+        self.current = overlay;
+    }
+
+    /// Draw the current overlay.
+    ///
+    /// Consecutive quads sharing the same texture and clipping rectangle are drawn together.
+    pub fn draw(
+        &self,
+        mut cb: AutoCommandBufferBuilder,
+        device: &Arc<Device>,
+        dimensions: [u32; 2],
+        stats: &mut VulkanFrameStats,
+    ) -> Result<AutoCommandBufferBuilder> {
+        let quads = &self.current.quads;
+
+        if quads.is_empty() {
+            return Ok(cb);
+        }
+
+        let width = dimensions[0] as f32;
+        let height = dimensions[1] as f32;
+
+        // y points down in vulkan clip space, so the top of the screen maps to -1.
+        let projection = cgmath::ortho(0.0, width, 0.0, height, -1.0, 1.0);
+
+        let screen = self.screen_pool.next(
+            UniformScreen { projection: projection.into() },
+        );
+
+        let screen_set = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+            .add_buffer(screen)?
+            .build()?);
+
+        stats.descriptor_set_allocations += 1;
+
+        let viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [width, height],
+            depth_range: 0.0..1.0,
+        };
+
+        let mut start = 0usize;
+
+        while start < quads.len() {
+            let first = quads[start];
+            let mut end = start + 1;
+
+            while end < quads.len() && quads[end].texture == first.texture &&
+                quads[end].clip == first.clip
+            {
+                end += 1;
+            }
+
+            let batch = &quads[start..end];
+            start = end;
+
+            let texture_set = match first.texture {
+                Some(id) => {
+                    match self.textures.get(&id) {
+                        Some(set) => set.clone(),
+                        None => {
+                            warn!("{:?}: texture not registered", id);
+                            continue;
+                        }
+                    }
+                }
+                None => self.white.clone(),
+            };
+
+            let scissor = match first.clip {
+                Some(clip) => {
+                    let clip = clip.intersect(&Rect::new(0.0, 0.0, width, height));
+
+                    if clip.width <= 0.0 || clip.height <= 0.0 {
+                        continue;
+                    }
+
+                    Scissor {
+                        origin: [clip.x as i32, clip.y as i32],
+                        dimensions: [clip.width as u32, clip.height as u32],
+                    }
+                }
+                None => Scissor::irrelevant(),
+            };
+
+            let mut vertices = Vec::with_capacity(batch.len() * 6);
+
+            for quad in batch {
+                vertices.extend(quad_vertices(quad).iter().cloned());
+            }
+
+            let vertex_buffer = CpuAccessibleBuffer::from_iter(
+                device.clone(),
+                BufferUsage::vertex_buffer(),
+                vertices.into_iter(),
+            )?;
+
+            stats.buffer_allocations += 1;
+
+            let state = DynamicState {
+                line_width: None,
+                viewports: Some(vec![viewport.clone()]),
+                scissors: Some(vec![scissor]),
+            };
+
+            cb = cb.draw(
+                self.pipeline.clone(),
+                state,
+                vec![vertex_buffer as Arc<BufferAccess + Send + Sync>],
+                (screen_set.clone(), texture_set),
+                (),
+            )?;
+        }
+
+        Ok(cb)
+    }
+}
+
+/// Upload a texture and build the descriptor set used to sample it.
+fn texture_set(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    pipeline: &Arc<Pl>,
+    samplers: &mut VulkanSamplers,
+    texture: &Texture,
+) -> Result<(Arc<DescriptorSet + Send + Sync>, Box<GpuFuture>)> {
+    let (image, future) = upload_texture(device, queue, texture)?;
+    let sampler = samplers.get(&texture.sampler)?;
+
+    let set = Arc::new(PersistentDescriptorSet::start(pipeline.clone(), 1)
+        .add_sampled_image(image, sampler)?
+        .build()?);
+
+    Ok((set, future))
+}
+
+/// Two triangles covering the quad.
+fn quad_vertices(quad: &Quad) -> [OverlayVertex; 6] {
+    let Quad {
+        ref rect,
+        ref uv,
+        color,
+        ..
+    } = *quad;
+
+    let color: [f32; 4] = color.into();
+
+    let vertex = |x: f32, y: f32| {
+        OverlayVertex {
+            position: [rect.x + rect.width * x, rect.y + rect.height * y],
+            tex_coord: [uv.x + uv.width * x, uv.y + uv.height * y],
+            color: color,
+        }
+    };
+
+    [
+        vertex(0.0, 0.0),
+        vertex(1.0, 0.0),
+        vertex(0.0, 1.0),
+        vertex(0.0, 1.0),
+        vertex(1.0, 0.0),
+        vertex(1.0, 1.0),
+    ]
+}
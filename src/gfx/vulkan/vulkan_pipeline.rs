@@ -0,0 +1,352 @@
+use super::{Fb, Pl, Rp};
+use super::shaders::basic::{fs, vs};
+use super::shaders::billboard;
+use super::shaders::lines;
+use super::shaders::overlay;
+use super::shaders::post;
+use super::shaders::sky;
+use super::vulkan_billboards::BillboardVertex;
+use super::vulkan_debug_lines::LineVertex;
+use super::vulkan_overlay::OverlayVertex;
+use super::vulkan_screen_triangle::ScreenVertex;
+use gfx::{Instance, Vertex};
+use gfx::errors::*;
+use std::sync::Arc;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::framebuffer::{Framebuffer, FramebufferBuilder, Subpass};
+use vulkano::image::AttachmentImage;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::pipeline::depth_stencil::DepthStencil;
+use vulkano::pipeline::vertex::{OneVertexOneInstanceDefinition, SingleBufferDefinition};
+
+pub const DEPTH_FORMAT: Format = Format::D16Unorm;
+
+/// Format the scene is drawn in, before post-processing.
+pub const HDR_FORMAT: Format = Format::R16G16B16A16Sfloat;
+
+/// Sample counts in order of preference.
+const SAMPLE_COUNTS: [u32; 7] = [64, 32, 16, 8, 4, 2, 1];
+
+/// Pick the highest sample count supported by the device which is not above the requested one.
+pub fn supported_samples(device: &Device, requested: u32) -> u32 {
+    let limits = device.physical_device().limits();
+    let supported = limits.framebuffer_color_sample_counts() &
+        limits.framebuffer_depth_sample_counts();
+
+    let samples = SAMPLE_COUNTS
+        .iter()
+        .cloned()
+        .find(|s| *s <= requested && supported & *s != 0)
+        .unwrap_or(1);
+
+    if samples != requested {
+        warn!(
+            "{}x multisampling is not supported, using {}x instead",
+            requested,
+            samples
+        );
+    }
+
+    samples
+}
+
+/// Build the render pass the scene is drawn in, with a resolve attachment if multisampling is
+/// enabled.
+pub fn build_render_pass(device: &Arc<Device>, samples: u32) -> Result<Arc<Rp>> {
+    let format = HDR_FORMAT;
+
+    if samples > 1 {
+        return Ok(Arc::new(single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                multisampled: {
+                    load: Clear,
+                    store: DontCare,
+                    format: format,
+                    samples: samples,
+                },
+                color: {
+                    load: DontCare,
+                    store: Store,
+                    format: format,
+                    samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: DEPTH_FORMAT,
+                    samples: samples,
+                }
+            },
+            pass: {
+                color: [multisampled],
+                depth_stencil: {depth},
+                resolve: [color],
+            }
+        )?));
+    }
+
+    Ok(Arc::new(single_pass_renderpass!(
+        device.clone(),
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: format,
+                samples: 1,
+            },
+            depth: {
+                load: Clear,
+                store: DontCare,
+                format: DEPTH_FORMAT,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {depth}
+        }
+    )?))
+}
+
+/// Build a render pass for full-screen passes, which overwrite every pixel of a single color
+/// attachment.
+pub fn build_post_render_pass(device: &Arc<Device>, format: Format) -> Result<Arc<Rp>> {
+    Ok(Arc::new(single_pass_renderpass!(
+        device.clone(),
+        attachments: {
+            color: {
+                load: DontCare,
+                store: Store,
+                format: format,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    )?))
+}
+
+/// Build the pipeline used to draw primitives.
+///
+/// The sample count of the pipeline is taken from the render pass. If `wireframe` is set, only
+/// the edges of triangles are rasterized.
+pub fn build_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &vs::Shader,
+    fs: &fs::Shader,
+    wireframe: bool,
+) -> Result<Arc<Pl>> {
+    let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
+        ErrorKind::NoSubpass,
+    )?;
+
+    let mut builder = GraphicsPipeline::start()
+        .vertex_input(OneVertexOneInstanceDefinition::<Vertex, Instance>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .depth_stencil_simple_depth();
+
+    if wireframe {
+        builder = builder.polygon_mode_line();
+    }
+
+    Ok(Arc::new(builder.render_pass(sub_pass).build(device.clone())?))
+}
+
+/// Build the pipeline used for the wireframe render mode.
+///
+/// Rasterizing only edges needs the `fill_mode_non_solid` feature, without it `None` is returned
+/// so that the renderer still starts.
+pub fn build_wireframe_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &vs::Shader,
+    fs: &fs::Shader,
+) -> Result<Option<Arc<Pl>>> {
+    if !device.enabled_features().fill_mode_non_solid {
+        return Ok(None);
+    }
+
+    Ok(Some(build_pipeline(device, render_pass, vs, fs, true)?))
+}
+
+/// Build the pipeline used to draw debug lines.
+///
+/// Lines are depth tested against the scene, so that they can be used to visualize volumes.
+pub fn build_lines_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &lines::vs::Shader,
+    fs: &lines::fs::Shader,
+) -> Result<Arc<Pl>> {
+    let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
+        ErrorKind::NoSubpass,
+    )?;
+
+    Ok(Arc::new(GraphicsPipeline::start()
+        .vertex_input(SingleBufferDefinition::<LineVertex>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .line_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .depth_stencil_simple_depth()
+        .render_pass(sub_pass)
+        .build(device.clone())?))
+}
+
+/// Build the pipeline used to draw billboards.
+///
+/// Billboards are tested against the depth of the scene, but do not write depth themselves so
+/// that overlapping translucent billboards blend with each other.
+pub fn build_billboard_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &billboard::vs::Shader,
+    fs: &billboard::fs::Shader,
+) -> Result<Arc<Pl>> {
+    let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
+        ErrorKind::NoSubpass,
+    )?;
+
+    Ok(Arc::new(GraphicsPipeline::start()
+        .vertex_input(SingleBufferDefinition::<BillboardVertex>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .depth_stencil(DepthStencil {
+            depth_write: false,
+            ..DepthStencil::simple_depth_test()
+        })
+        .blend_alpha_blending()
+        .render_pass(sub_pass)
+        .build(device.clone())?))
+}
+
+/// Build the pipeline used to draw the skybox.
+///
+/// Depth testing is disabled, since the skybox is drawn before anything else.
+pub fn build_sky_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &sky::vs::Shader,
+    fs: &sky::fs::Shader,
+) -> Result<Arc<Pl>> {
+    let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
+        ErrorKind::NoSubpass,
+    )?;
+
+    Ok(Arc::new(GraphicsPipeline::start()
+        .vertex_input(SingleBufferDefinition::<ScreenVertex>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .render_pass(sub_pass)
+        .build(device.clone())?))
+}
+
+/// Build the pipeline used for post-processing passes.
+pub fn build_post_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &post::vs::Shader,
+    fs: &post::fs::Shader,
+) -> Result<Arc<Pl>> {
+    let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
+        ErrorKind::NoSubpass,
+    )?;
+
+    Ok(Arc::new(GraphicsPipeline::start()
+        .vertex_input(SingleBufferDefinition::<ScreenVertex>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .render_pass(sub_pass)
+        .build(device.clone())?))
+}
+
+/// Build the pipeline used to draw the 2D overlay.
+///
+/// Depth testing is disabled, and quads are alpha blended on top of the scene in the order drawn.
+pub fn build_overlay_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    vs: &overlay::vs::Shader,
+    fs: &overlay::fs::Shader,
+) -> Result<Arc<Pl>> {
+    let sub_pass = Subpass::from(render_pass.clone(), 0).ok_or(
+        ErrorKind::NoSubpass,
+    )?;
+
+    Ok(Arc::new(GraphicsPipeline::start()
+        .vertex_input(SingleBufferDefinition::<OverlayVertex>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_list()
+        .viewports_scissors_dynamic(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .blend_alpha_blending()
+        .render_pass(sub_pass)
+        .build(device.clone())?))
+}
+
+/// Build the framebuffer the scene is drawn into.
+///
+/// With multisampling enabled, the scene is resolved into the given target.
+pub fn build_scene_framebuffer(
+    device: &Arc<Device>,
+    render_pass: &Arc<Rp>,
+    target: &Arc<AttachmentImage>,
+    dimensions: [u32; 2],
+    samples: u32,
+) -> Result<Arc<Fb>> {
+    if samples > 1 {
+        let multisampled = AttachmentImage::transient_multisampled(
+            device.clone(),
+            dimensions,
+            samples,
+            HDR_FORMAT,
+        )?;
+
+        let depth = AttachmentImage::transient_multisampled(
+            device.clone(),
+            dimensions,
+            samples,
+            DEPTH_FORMAT,
+        )?;
+
+        let fb = Framebuffer::start(render_pass.clone())
+            .add(multisampled)
+            .and_then(|b| b.add(target.clone()))
+            .and_then(|b| b.add(depth))
+            .and_then(FramebufferBuilder::build)?;
+
+        return Ok(Arc::new(fb));
+    }
+
+    let depth = AttachmentImage::transient(device.clone(), dimensions, DEPTH_FORMAT)?;
+
+    let fb = Framebuffer::start(render_pass.clone())
+        .add(target.clone())
+        .and_then(|b| b.add(depth))
+        .and_then(FramebufferBuilder::build)?;
+
+    Ok(Arc::new(fb))
+}
+
+/// Clear values matching the attachments of the render pass.
+pub fn clear_values(color: [f32; 4], samples: u32) -> Vec<ClearValue> {
+    if samples > 1 {
+        vec![color.into(), ClearValue::None, 1f32.into()]
+    } else {
+        vec![color.into(), 1f32.into()]
+    }
+}
@@ -0,0 +1,253 @@
+use super::{Fb, Pl, PushEffect, Rp};
+use super::shaders::post::{fs, vs};
+use super::vulkan_pipeline::{HDR_FORMAT, build_post_pipeline, build_post_render_pass,
+                             build_scene_framebuffer};
+use super::vulkan_screen_triangle::{ScreenVertex, screen_triangle};
+use super::vulkan_textures::VulkanSamplers;
+use gfx::errors::*;
+use gfx::post_process::{Effect, PostProcess};
+use std::sync::Arc;
+use texture;
+use texture::sampler::{Filter, Wrap};
+use vulkano::buffer::{BufferAccess, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::device::{Device, Queue};
+use vulkano::format::{ClearValue, Format};
+use vulkano::framebuffer::{Framebuffer, FramebufferBuilder};
+use vulkano::image::{AttachmentImage, SwapchainImage};
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::sampler::Sampler;
+use vulkano::sync::GpuFuture;
+
+/// Render targets for the current window size and sample count.
+#[derive(Clone)]
+pub struct VulkanTargets {
+    /// Framebuffer the scene is drawn into.
+    pub scene: Arc<Fb>,
+    /// Intermediate framebuffers, which passes take turns drawing into.
+    intermediate: Vec<Arc<Fb>>,
+    /// Final framebuffers, one for each swapchain image.
+    swapchain: Vec<Arc<Fb>>,
+    /// Descriptor sets sampling the scene, and each intermediate image.
+    sources: Vec<Arc<DescriptorSet + Send + Sync>>,
+}
+
+/// Runs the post-processing chain, from the HDR scene image to the swapchain image.
+pub struct VulkanPostProcess {
+    /// Render pass for intermediate passes.
+    intermediate_pass: Arc<Rp>,
+    /// Render pass for the final pass, which draws into the swapchain image.
+    final_pass: Arc<Rp>,
+    intermediate_pipeline: Arc<Pl>,
+    final_pipeline: Arc<Pl>,
+    vertex_buffer: Arc<ImmutableBuffer<[ScreenVertex]>>,
+    sampler: Arc<Sampler>,
+    /// The current chain of effects.
+    post_process: PostProcess,
+}
+
+impl VulkanPostProcess {
+    pub fn new(
+        device: &Arc<Device>,
+        queue: &Arc<Queue>,
+        format: Format,
+        samplers: &mut VulkanSamplers,
+    ) -> Result<(VulkanPostProcess, Box<GpuFuture>)> {
+        let vs = vs::Shader::load(device.clone())?;
+        let fs = fs::Shader::load(device.clone())?;
+
+        let intermediate_pass = build_post_render_pass(device, HDR_FORMAT)?;
+        let final_pass = build_post_render_pass(device, format)?;
+        let intermediate_pipeline = build_post_pipeline(device, &intermediate_pass, &vs, &fs)?;
+        let final_pipeline = build_post_pipeline(device, &final_pass, &vs, &fs)?;
+
+        let (vertex_buffer, future) = screen_triangle(queue)?;
+
+        let sampler = samplers.get(&texture::Sampler {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_filter: Filter::Nearest,
+            mipmaps: false,
+            wrap_s: Wrap::ClampToEdge,
+            wrap_t: Wrap::ClampToEdge,
+        })?;
+
+        let post_process = VulkanPostProcess {
+            intermediate_pass: intermediate_pass,
+            final_pass: final_pass,
+            intermediate_pipeline: intermediate_pipeline,
+            final_pipeline: final_pipeline,
+            vertex_buffer: vertex_buffer,
+            sampler: sampler,
+            post_process: PostProcess::new(),
+        };
+
+        Ok((post_process, future))
+    }
+
+    /// Render pass of the final pass, which anything drawn on top of the post-processed scene
+    /// has to use.
+    pub fn final_pass(&self) -> &Arc<Rp> {
+        &self.final_pass
+    }
+
+    /// Replace the chain of effects.
+    pub fn set(&mut self, post_process: PostProcess) {
+        self.post_process = post_process;
+    }
+
+    /// Build all render targets for the given dimensions.
+    pub fn build_targets(
+        &self,
+        device: &Arc<Device>,
+        scene_pass: &Arc<Rp>,
+        images: &[Arc<SwapchainImage>],
+        dimensions: [u32; 2],
+        samples: u32,
+    ) -> Result<VulkanTargets> {
+        let scene_image = AttachmentImage::sampled(device.clone(), dimensions, HDR_FORMAT)?;
+        let scene = build_scene_framebuffer(device, scene_pass, &scene_image, dimensions, samples)?;
+
+        let first = AttachmentImage::sampled(device.clone(), dimensions, HDR_FORMAT)?;
+        let second = AttachmentImage::sampled(device.clone(), dimensions, HDR_FORMAT)?;
+
+        let intermediate = vec![
+            self.intermediate_framebuffer(&first)?,
+            self.intermediate_framebuffer(&second)?,
+        ];
+
+        let mut swapchain: Vec<Arc<Fb>> = Vec::new();
+
+        for image in images {
+            let fb = Framebuffer::start(self.final_pass.clone())
+                .add(image.clone())
+                .and_then(FramebufferBuilder::build)?;
+
+            swapchain.push(Arc::new(fb));
+        }
+
+        let sources = vec![
+            self.source_set(scene_image)?,
+            self.source_set(first)?,
+            self.source_set(second)?,
+        ];
+
+        Ok(VulkanTargets {
+            scene: scene,
+            intermediate: intermediate,
+            swapchain: swapchain,
+            sources: sources,
+        })
+    }
+
+    /// Run all effects, ending with the final pass into the given swapchain image.
+    ///
+    /// The final render pass is left open, so that the overlay can be drawn on top of the scene.
+    pub fn draw(
+        &self,
+        mut cb: AutoCommandBufferBuilder,
+        targets: &VulkanTargets,
+        image_num: usize,
+        dimensions: [u32; 2],
+    ) -> Result<AutoCommandBufferBuilder> {
+        let final_framebuffer = targets.swapchain.get(image_num).ok_or(
+            ErrorKind::NoFramebuffer,
+        )?;
+
+        let state = DynamicState {
+            line_width: None,
+            viewports: Some(vec![
+                Viewport {
+                    origin: [0.0, 0.0],
+                    dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+                    depth_range: 0.0..1.0,
+                },
+            ]),
+            scissors: None,
+        };
+
+        let texel_size = [1.0 / dimensions[0] as f32, 1.0 / dimensions[1] as f32];
+
+        // an empty chain still needs a pass to copy the scene into the swapchain image.
+        let effects: Vec<PushEffect> = if self.post_process.effects.is_empty() {
+            vec![push_effect(None, texel_size)]
+        } else {
+            self.post_process
+                .effects
+                .iter()
+                .map(|e| push_effect(Some(e), texel_size))
+                .collect()
+        };
+
+        let last = effects.len() - 1;
+
+        for (i, effect) in effects.into_iter().enumerate() {
+            // the first pass reads the scene, after which passes alternate between the two
+            // intermediate images.
+            let source = if i == 0 { 0 } else { 1 + (i - 1) % 2 };
+
+            let (framebuffer, pipeline) = if i == last {
+                (final_framebuffer.clone(), self.final_pipeline.clone())
+            } else {
+                (
+                    targets.intermediate[i % 2].clone(),
+                    self.intermediate_pipeline.clone(),
+                )
+            };
+
+            cb = cb.begin_render_pass(framebuffer, false, vec![ClearValue::None])?
+                .draw(
+                    pipeline,
+                    state.clone(),
+                    vec![self.vertex_buffer.clone() as Arc<BufferAccess + Send + Sync>],
+                    targets.sources[source].clone(),
+                    effect,
+                )?;
+
+            if i != last {
+                cb = cb.end_render_pass()?;
+            }
+        }
+
+        Ok(cb)
+    }
+
+    fn intermediate_framebuffer(&self, image: &Arc<AttachmentImage>) -> Result<Arc<Fb>> {
+        let fb = Framebuffer::start(self.intermediate_pass.clone())
+            .add(image.clone())
+            .and_then(FramebufferBuilder::build)?;
+
+        Ok(Arc::new(fb))
+    }
+
+    fn source_set(&self, image: Arc<AttachmentImage>) -> Result<Arc<DescriptorSet + Send + Sync>> {
+        Ok(Arc::new(PersistentDescriptorSet::start(
+            self.intermediate_pipeline.clone(),
+            0,
+        ).add_sampled_image(image, self.sampler.clone())?
+            .build()?))
+    }
+}
+
+/// Push constants for the given effect, where `None` copies the source as-is.
+fn push_effect(effect: Option<&Effect>, texel_size: [f32; 2]) -> PushEffect {
+    let (effect, params) = match effect {
+        None => (0, [0.0; 4]),
+        Some(&Effect::Tonemap { exposure }) => (1, [exposure, 0.0, 0.0, 0.0]),
+        Some(&Effect::Bloom {
+                 threshold,
+                 intensity,
+                 radius,
+             }) => (2, [threshold, intensity, radius, 0.0]),
+        Some(&Effect::Gamma { gamma }) => (3, [gamma, 0.0, 0.0, 0.0]),
+        Some(&Effect::Vignette { strength, radius }) => (4, [strength, radius, 0.0, 0.0]),
+        Some(&Effect::Fxaa) => (5, [0.0; 4]),
+    };
+
+    PushEffect {
+        params: params,
+        texel_size: texel_size,
+        effect: effect,
+    }
+}
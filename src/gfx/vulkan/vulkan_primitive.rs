@@ -1,31 +1,25 @@
 use gfx::Vertex;
 use std::sync::Arc;
-use vulkano::buffer::CpuAccessibleBuffer;
-use vulkano::format;
-use vulkano::image::immutable::ImmutableImage;
+use vulkano::buffer::ImmutableBuffer;
+use vulkano::descriptor::descriptor_set::DescriptorSet;
 
 pub struct VulkanPrimitive {
-    pub vertex_buffer: Arc<CpuAccessibleBuffer<[Vertex]>>,
-    pub index_buffer: Arc<CpuAccessibleBuffer<[u32]>>,
-    pub base_color_factor: [f32; 4],
-    pub base_color_texture: Arc<ImmutableImage<format::R8G8B8A8Srgb>>,
-    pub use_base_color_texture: bool,
+    pub vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
+    pub index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    /// Descriptor set for the material and texture, built once when the primitive is uploaded.
+    pub material_set: Arc<DescriptorSet + Send + Sync>,
 }
 
 impl VulkanPrimitive {
     pub fn new(
-        vertex_buffer: Arc<CpuAccessibleBuffer<[Vertex]>>,
-        index_buffer: Arc<CpuAccessibleBuffer<[u32]>>,
-        base_color_factor: [f32; 4],
-        base_color_texture: Arc<ImmutableImage<format::R8G8B8A8Srgb>>,
-        use_base_color_texture: bool,
+        vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
+        index_buffer: Arc<ImmutableBuffer<[u32]>>,
+        material_set: Arc<DescriptorSet + Send + Sync>,
     ) -> VulkanPrimitive {
         VulkanPrimitive {
             vertex_buffer: vertex_buffer,
             index_buffer: index_buffer,
-            base_color_factor: base_color_factor,
-            base_color_texture: base_color_texture,
-            use_base_color_texture: use_base_color_texture,
+            material_set: material_set,
         }
     }
 }
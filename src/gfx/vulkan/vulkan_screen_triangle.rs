@@ -0,0 +1,31 @@
+use gfx::errors::*;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, ImmutableBuffer};
+use vulkano::device::Queue;
+use vulkano::sync::GpuFuture;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ScreenVertex {
+    pub position: [f32; 2],
+}
+
+impl_vertex!(ScreenVertex, position);
+
+/// Upload a single triangle covering the whole screen, used by full-screen passes.
+pub fn screen_triangle(
+    queue: &Arc<Queue>,
+) -> Result<(Arc<ImmutableBuffer<[ScreenVertex]>>, Box<GpuFuture>)> {
+    let vertices = vec![
+        ScreenVertex { position: [-1.0, -1.0] },
+        ScreenVertex { position: [3.0, -1.0] },
+        ScreenVertex { position: [-1.0, 3.0] },
+    ];
+
+    let (vertex_buffer, future) = ImmutableBuffer::from_iter(
+        vertices.into_iter(),
+        BufferUsage::vertex_buffer(),
+        queue.clone(),
+    )?;
+
+    Ok((vertex_buffer, Box::new(future)))
+}
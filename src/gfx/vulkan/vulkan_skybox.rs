@@ -0,0 +1,123 @@
+use super::{Pl, Rp, UniformSky};
+use super::shaders::sky::{fs, vs};
+use super::vulkan_frame_stats::VulkanFrameStats;
+use super::vulkan_pipeline::build_sky_pipeline;
+use super::vulkan_screen_triangle::{ScreenVertex, screen_triangle};
+use super::vulkan_textures::{VulkanSamplers, upload_texture};
+use cgmath::{Matrix4, SquareMatrix};
+use gfx::errors::*;
+use std::sync::Arc;
+use texture::Texture;
+use vulkano::buffer::{BufferAccess, CpuBufferPool, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::device::{Device, Queue};
+use vulkano::sync::GpuFuture;
+
+/// Renders the skybox behind all geometry.
+pub struct VulkanSkybox {
+    vs: vs::Shader,
+    fs: fs::Shader,
+    pipeline: Arc<Pl>,
+    /// A single triangle covering the whole screen.
+    vertex_buffer: Arc<ImmutableBuffer<[ScreenVertex]>>,
+    sky_pool: CpuBufferPool<UniformSky>,
+    /// Texture set of the current skybox, if any.
+    texture_set: Option<Arc<DescriptorSet + Send + Sync>>,
+}
+
+impl VulkanSkybox {
+    pub fn new(
+        device: &Arc<Device>,
+        queue: &Arc<Queue>,
+        render_pass: &Arc<Rp>,
+    ) -> Result<(VulkanSkybox, Box<GpuFuture>)> {
+        let vs = vs::Shader::load(device.clone())?;
+        let fs = fs::Shader::load(device.clone())?;
+        let pipeline = build_sky_pipeline(device, render_pass, &vs, &fs)?;
+
+        let (vertex_buffer, future) = screen_triangle(queue)?;
+
+        let skybox = VulkanSkybox {
+            vs: vs,
+            fs: fs,
+            pipeline: pipeline,
+            vertex_buffer: vertex_buffer,
+            sky_pool: CpuBufferPool::uniform_buffer(device.clone()),
+            texture_set: None,
+        };
+
+        Ok((skybox, future))
+    }
+
+    /// Rebuild the pipeline for a new render pass.
+    pub fn rebuild(&mut self, device: &Arc<Device>, render_pass: &Arc<Rp>) -> Result<()> {
+        self.pipeline = build_sky_pipeline(device, render_pass, &self.vs, &self.fs)?;
+        Ok(())
+    }
+
+    /// Replace the texture of the skybox, or remove the skybox.
+    pub fn set(
+        &mut self,
+        device: &Arc<Device>,
+        queue: &Arc<Queue>,
+        samplers: &mut VulkanSamplers,
+        texture: Option<&Texture>,
+    ) -> Result<Option<Box<GpuFuture>>> {
+        let texture = match texture {
+            Some(texture) => texture,
+            None => {
+                self.texture_set = None;
+                return Ok(None);
+            }
+        };
+
+        let (image, future) = upload_texture(device, queue, texture)?;
+        let sampler = samplers.get(&texture.sampler)?;
+
+        self.texture_set = Some(Arc::new(PersistentDescriptorSet::start(
+            self.pipeline.clone(),
+            1,
+        ).add_sampled_image(image, sampler)?
+            .build()?));
+
+        Ok(Some(future))
+    }
+
+    /// Draw the skybox, if there is one.
+    pub fn draw(
+        &self,
+        cb: AutoCommandBufferBuilder,
+        state: DynamicState,
+        projection: Matrix4<f32>,
+        view: Matrix4<f32>,
+        stats: &mut VulkanFrameStats,
+    ) -> Result<AutoCommandBufferBuilder> {
+        let texture_set = match self.texture_set {
+            Some(ref texture_set) => texture_set.clone(),
+            None => return Ok(cb),
+        };
+
+        let inverse_projection = projection.invert().unwrap_or(Matrix4::identity());
+        let inverse_view = view.invert().unwrap_or(Matrix4::identity());
+
+        let sky = self.sky_pool.next(UniformSky {
+            inverse_projection: inverse_projection.into(),
+            inverse_view: inverse_view.into(),
+        });
+
+        let sky_set = Arc::new(PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+            .add_buffer(sky)?
+            .build()?);
+
+        stats.descriptor_set_allocations += 1;
+
+        Ok(cb.draw(
+            self.pipeline.clone(),
+            state,
+            vec![self.vertex_buffer.clone() as Arc<BufferAccess + Send + Sync>],
+            (sky_set, texture_set),
+            (),
+        )?)
+    }
+}
@@ -0,0 +1,115 @@
+use super::vulkan_textures::upload_texture;
+use gfx::errors::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use texture::Texture;
+use vulkano::descriptor::descriptor_set::DescriptorSet;
+use vulkano::device::{Device, Queue};
+use vulkano::format;
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::sync::GpuFuture;
+
+/// A texture uploaded to the GPU.
+struct UploadedTexture {
+    /// The texture the image was uploaded from, which is gone once no primitive uses it.
+    texture: Weak<Texture>,
+    image: Arc<ImmutableImage<format::Format>>,
+    /// Material sets sampling the image, by base color factor.
+    material_sets: Vec<([f32; 4], Arc<DescriptorSet + Send + Sync>)>,
+}
+
+/// Textures uploaded to the GPU, so that primitives sharing a texture share its image and
+/// material set.
+///
+/// Textures are identified by their allocation, the way the asset cache shares them.
+pub struct VulkanTextureCache {
+    uploaded: HashMap<usize, UploadedTexture>,
+}
+
+impl VulkanTextureCache {
+    pub fn new() -> VulkanTextureCache {
+        VulkanTextureCache { uploaded: HashMap::new() }
+    }
+
+    /// Get the image of a texture, uploading it if it has not been uploaded yet.
+    ///
+    /// The future of the upload is only returned if the texture was uploaded.
+    pub fn image(
+        &mut self,
+        device: &Arc<Device>,
+        queue: &Arc<Queue>,
+        texture: &Arc<Texture>,
+    ) -> Result<(Arc<ImmutableImage<format::Format>>, Option<Box<GpuFuture>>)> {
+        if let Some(uploaded) = self.get(texture) {
+            return Ok((uploaded.image.clone(), None));
+        }
+
+        // released textures are only removed when a texture is uploaded, keeping lookups cheap.
+        self.purge();
+
+        let (image, future) = upload_texture(device, queue, texture)?;
+
+        self.uploaded.insert(
+            key(texture),
+            UploadedTexture {
+                texture: Arc::downgrade(texture),
+                image: image.clone(),
+                material_sets: Vec::new(),
+            },
+        );
+
+        Ok((image, Some(future)))
+    }
+
+    /// Get the material set of an uploaded texture with the given base color factor.
+    pub fn material_set(
+        &self,
+        texture: &Arc<Texture>,
+        base_color_factor: [f32; 4],
+    ) -> Option<Arc<DescriptorSet + Send + Sync>> {
+        self.get(texture).and_then(|uploaded| {
+            uploaded
+                .material_sets
+                .iter()
+                .find(|&&(factor, _)| factor == base_color_factor)
+                .map(|&(_, ref set)| set.clone())
+        })
+    }
+
+    /// Keep a material set sampling an uploaded texture, to be shared with other primitives.
+    pub fn add_material_set(
+        &mut self,
+        texture: &Arc<Texture>,
+        base_color_factor: [f32; 4],
+        set: Arc<DescriptorSet + Send + Sync>,
+    ) {
+        if self.get(texture).is_none() {
+            return;
+        }
+
+        if let Some(uploaded) = self.uploaded.get_mut(&key(texture)) {
+            uploaded.material_sets.push((base_color_factor, set));
+        }
+    }
+
+    /// Remove all textures which are no longer used by any primitive.
+    ///
+    /// Meshes which are still uploaded keep their images alive through their material sets.
+    pub fn purge(&mut self) {
+        self.uploaded.retain(|_, uploaded| uploaded.texture.upgrade().is_some());
+    }
+
+    fn get(&self, texture: &Arc<Texture>) -> Option<&UploadedTexture> {
+        self.uploaded.get(&key(texture)).and_then(|uploaded| {
+            // the allocation of a released texture may have been reused by another one.
+            match uploaded.texture.upgrade() {
+                Some(ref existing) if Arc::ptr_eq(existing, texture) => Some(uploaded),
+                _ => None,
+            }
+        })
+    }
+}
+
+fn key(texture: &Arc<Texture>) -> usize {
+    &**texture as *const Texture as usize
+}
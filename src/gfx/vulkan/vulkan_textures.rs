@@ -0,0 +1,147 @@
+use gfx::errors::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use texture::{self, ColorSpace, Texture};
+use texture::sampler::{Filter as TextureFilter, Wrap};
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::device::{Device, Queue};
+use vulkano::format;
+use vulkano::image::{Dimensions, ImageLayout, ImageUsage, MipmapsCount};
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::sync::GpuFuture;
+
+/// Format of the image a texture is uploaded to.
+///
+/// sRGB encoded colors are decoded to linear when sampled, while data like normals is sampled
+/// as stored.
+pub fn texture_format(color_space: ColorSpace) -> format::Format {
+    match color_space {
+        ColorSpace::Srgb => format::Format::R8G8B8A8Srgb,
+        ColorSpace::Linear => format::Format::R8G8B8A8Unorm,
+    }
+}
+
+/// Upload a texture, including its full mip chain.
+pub fn upload_texture(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    texture: &Texture,
+) -> Result<(Arc<ImmutableImage<format::Format>>, Box<GpuFuture>)> {
+    let (width, height) = texture.dimensions;
+    let levels = texture::mipmap::chain(texture);
+
+    let usage = ImageUsage {
+        transfer_destination: true,
+        sampled: true,
+        ..ImageUsage::none()
+    };
+
+    let (image, init) = ImmutableImage::uninitialized(
+        device.clone(),
+        Dimensions::Dim2d {
+            width: width,
+            height: height,
+        },
+        texture_format(texture.color_space),
+        MipmapsCount::Specific(levels.len() as u32),
+        usage,
+        ImageLayout::ShaderReadOnlyOptimal,
+        Some(queue.family()),
+    )?;
+
+    let init = Arc::new(init);
+
+    let mut cb = AutoCommandBufferBuilder::new(device.clone(), queue.family())?;
+
+    for (level, mip) in levels.into_iter().enumerate() {
+        let (mip_width, mip_height) = mip.dimensions;
+
+        let source = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            mip.image_data.into_iter(),
+        )?;
+
+        cb = cb.copy_buffer_to_image_dimensions(
+            source,
+            init.clone(),
+            [0, 0, 0],
+            [mip_width, mip_height, 1],
+            0,
+            1,
+            level as u32,
+        )?;
+    }
+
+    let future = cb.build()?.execute(queue.clone())?;
+    Ok((image, Box::new(future)))
+}
+
+/// Cache of samplers, since most textures share the same sampler settings.
+pub struct VulkanSamplers {
+    device: Arc<Device>,
+    samplers: HashMap<texture::Sampler, Arc<Sampler>>,
+}
+
+impl VulkanSamplers {
+    pub fn new(device: Arc<Device>) -> VulkanSamplers {
+        VulkanSamplers {
+            device: device,
+            samplers: HashMap::new(),
+        }
+    }
+
+    /// Get or build a sampler with the given settings.
+    pub fn get(&mut self, sampler: &texture::Sampler) -> Result<Arc<Sampler>> {
+        if let Some(existing) = self.samplers.get(sampler) {
+            return Ok(existing.clone());
+        }
+
+        // anisotropic filtering is only available if the device supports it.
+        let max_anisotropy = if self.device.enabled_features().sampler_anisotropy {
+            f32::min(16.0, self.device.physical_device().limits().max_sampler_anisotropy())
+        } else {
+            1.0
+        };
+
+        // without mipmaps, sampling is limited to the full size level.
+        let max_lod = if sampler.mipmaps { 1000.0 } else { 0.0 };
+
+        let new = Sampler::new(
+            self.device.clone(),
+            filter(sampler.mag_filter),
+            filter(sampler.min_filter),
+            match sampler.mipmap_filter {
+                TextureFilter::Nearest => MipmapMode::Nearest,
+                TextureFilter::Linear => MipmapMode::Linear,
+            },
+            address_mode(sampler.wrap_s),
+            address_mode(sampler.wrap_t),
+            SamplerAddressMode::Repeat,
+            0.0,
+            max_anisotropy,
+            0.0,
+            max_lod,
+        )?;
+
+        self.samplers.insert(*sampler, new.clone());
+        Ok(new)
+    }
+}
+
+fn filter(filter: TextureFilter) -> Filter {
+    match filter {
+        TextureFilter::Nearest => Filter::Nearest,
+        TextureFilter::Linear => Filter::Linear,
+    }
+}
+
+fn address_mode(wrap: Wrap) -> SamplerAddressMode {
+    match wrap {
+        Wrap::Repeat => SamplerAddressMode::Repeat,
+        Wrap::MirroredRepeat => SamplerAddressMode::MirroredRepeat,
+        Wrap::ClampToEdge => SamplerAddressMode::ClampToEdge,
+    }
+}
@@ -2,7 +2,7 @@ use super::errors::*;
 use super::fps_counter::FpsCounter;
 use gfx::GfxLoopBuilder;
 use std::sync::{Arc, Condvar, Mutex};
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::atomic::Ordering;
 use std::thread;
 
@@ -10,6 +10,8 @@ pub struct GfxThread {
     stopped: Arc<AtomicBool>,
     errored: Arc<AtomicBool>,
     enabled: Arc<(Mutex<bool>, Condvar)>,
+    /// Frames rendered during the last second.
+    fps: Arc<AtomicUsize>,
     handle: Option<thread::JoinHandle<Result<()>>>,
 }
 
@@ -19,6 +21,7 @@ impl GfxThread {
             stopped: Arc::new(AtomicBool::new(false)),
             errored: Arc::new(AtomicBool::new(false)),
             enabled: Arc::new((Mutex::new(false), Condvar::new())),
+            fps: Arc::new(AtomicUsize::new(0usize)),
             handle: None,
         }
     }
@@ -33,6 +36,11 @@ impl GfxThread {
         Ok(())
     }
 
+    /// Frames rendered during the last second.
+    pub fn fps(&self) -> u64 {
+        self.fps.load(Ordering::Relaxed) as u64
+    }
+
     pub fn errored(&self) -> bool {
         self.errored.load(Ordering::Relaxed)
     }
@@ -57,14 +65,16 @@ impl GfxThread {
         let errored = self.errored.clone();
         let stopped = self.stopped.clone();
         let enabled = self.enabled.clone();
+        let fps = self.fps.clone();
 
         let builder = thread::Builder::new().name(String::from("gfx"));
 
         self.handle = Some(builder.spawn(move || {
             let gfx_loop = gfx_loop_builder.into_loop()?;
 
-            let mut fps_counter = FpsCounter::new(|fps| {
-                info!("fps = {}", fps);
+            let mut fps_counter = FpsCounter::new(|frames| {
+                debug!("fps = {}", frames);
+                fps.store(frames as usize, Ordering::Relaxed);
                 Ok(())
             });
 
@@ -0,0 +1,148 @@
+//! Binary glTF containers, which hold the JSON document and its binary buffer in a single file.
+
+use super::bytes::read_u32;
+use super::errors::*;
+
+/// The magic every binary glTF container starts with.
+const MAGIC: &'static [u8] = b"glTF";
+const HEADER_LENGTH: usize = 12;
+const CHUNK_HEADER_LENGTH: usize = 8;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// The chunks of a binary glTF container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glb<'a> {
+    /// The JSON document.
+    pub json: &'a [u8],
+    /// The binary buffer, used by the first buffer of the document if it has no URI.
+    pub bin: Option<&'a [u8]>,
+}
+
+/// Test if the given bytes are a binary glTF container, rather than a JSON document.
+pub fn is_glb(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Split a binary glTF container into its chunks.
+pub fn parse(bytes: &[u8]) -> Result<Glb> {
+    if bytes.len() < HEADER_LENGTH || !is_glb(bytes) {
+        return Err(ErrorKind::InvalidGlb("missing header").into());
+    }
+
+    if read_u32(bytes, 4) != 2 {
+        return Err(ErrorKind::InvalidGlb("unsupported version").into());
+    }
+
+    let length = read_u32(bytes, 8) as usize;
+
+    if length > bytes.len() {
+        return Err(ErrorKind::InvalidGlb("truncated").into());
+    }
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = HEADER_LENGTH;
+
+    while offset + CHUNK_HEADER_LENGTH <= length {
+        let chunk_length = read_u32(bytes, offset) as usize;
+        let chunk_type = read_u32(bytes, offset + 4);
+        let start = offset + CHUNK_HEADER_LENGTH;
+        let end = start + chunk_length;
+
+        if end > length {
+            return Err(ErrorKind::InvalidGlb("truncated chunk").into());
+        }
+
+        let data = &bytes[start..end];
+
+        match chunk_type {
+            CHUNK_JSON if json.is_none() => json = Some(data),
+            CHUNK_BIN if json.is_some() && bin.is_none() => bin = Some(data),
+            CHUNK_JSON | CHUNK_BIN => {
+                return Err(ErrorKind::InvalidGlb("unexpected chunk").into());
+            }
+            // unknown chunks are reserved for extensions, and skipped.
+            _ => {}
+        }
+
+        // chunks are padded to four bytes.
+        offset = (end + 3) & !3;
+    }
+
+    Ok(Glb {
+        json: json.ok_or(ErrorKind::InvalidGlb("missing JSON chunk"))?,
+        bin: bin,
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn push_u32(out: &mut Vec<u8>, value: u32) {
+        for i in 0..4 {
+            out.push((value >> (i * 8)) as u8);
+        }
+    }
+
+    /// Build a container out of the given chunks, padding them as needed.
+    pub fn build(chunks: &[(u32, &[u8])]) -> Vec<u8> {
+        let mut body = Vec::new();
+
+        for &(chunk_type, data) in chunks {
+            let padded = (data.len() + 3) & !3;
+            push_u32(&mut body, padded as u32);
+            push_u32(&mut body, chunk_type);
+            body.extend_from_slice(data);
+
+            let padding = if chunk_type == CHUNK_JSON { b' ' } else { 0 };
+            body.extend(::std::iter::repeat(padding).take(padded - data.len()));
+        }
+
+        let mut out = MAGIC.to_vec();
+        push_u32(&mut out, 2);
+        push_u32(&mut out, (HEADER_LENGTH + body.len()) as u32);
+        out.extend(body);
+        out
+    }
+
+    /// Build a container with a JSON chunk, and an optional binary chunk.
+    pub fn build_glb(json: &[u8], bin: Option<&[u8]>) -> Vec<u8> {
+        match bin {
+            Some(bin) => build(&[(CHUNK_JSON, json), (CHUNK_BIN, bin)]),
+            None => build(&[(CHUNK_JSON, json)]),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let bytes = build_glb(b"{}", Some(&[1, 2, 3, 4, 5]));
+        assert!(is_glb(&bytes));
+
+        let glb = parse(&bytes).unwrap();
+        assert_eq!(b"{}  ", glb.json);
+        assert_eq!(Some(&[1u8, 2, 3, 4, 5, 0, 0, 0][..]), glb.bin);
+    }
+
+    #[test]
+    fn test_skip_unknown_chunks() {
+        let bytes = build(&[(CHUNK_JSON, b"{}"), (0x1234, b"ext"), (CHUNK_BIN, b"bin!")]);
+        let glb = parse(&bytes).unwrap();
+        assert_eq!(Some(&b"bin!"[..]), glb.bin);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse(b"{}").is_err());
+        assert!(parse(&build(&[(CHUNK_BIN, b"bin!")])).is_err());
+
+        let mut truncated = build_glb(b"{}", None);
+        truncated.pop();
+        assert!(parse(&truncated).is_err());
+
+        let mut version = build_glb(b"{}", None);
+        version[4] = 1;
+        assert!(parse(&version).is_err());
+    }
+}
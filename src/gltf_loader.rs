@@ -1,31 +1,160 @@
+use super::animation::{AnimationClip, Skeleton};
+use super::assets::AssetCache;
+use super::data_uri;
 use super::errors::*;
+use super::glb;
 use super::model::Model;
-use gltf::{Gltf, Mesh};
-use gltf_importer::{self, Buffers, Config};
-use gltf_importer::config::ValidationStrategy;
+use gltf::{self, Gltf, Mesh, Skin};
+use gltf::image::Data;
+use gltf_utils::Source;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use texture::Texture;
+
+/// Contents of all buffers of a glTF document.
+#[derive(Debug, Clone, Default)]
+pub struct Buffers {
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Buffers {
+    /// Load all buffers of the document, from files relative to the given directory, data URIs
+    /// or the binary chunk of a binary glTF container.
+    fn load(gltf: &Gltf, base: &Path, bin: Option<&[u8]>) -> Result<Buffers> {
+        let mut buffers = Vec::new();
+
+        for buffer in gltf.buffers() {
+            let uri = buffer.uri();
+
+            let data = if uri.is_empty() {
+                // only the first buffer may refer to the binary chunk.
+                match bin {
+                    Some(bin) if buffer.index() == 0 => bin.to_vec(),
+                    _ => return Err(ErrorKind::InvalidBuffer(buffer.index(), "missing uri").into()),
+                }
+            } else if data_uri::is_data_uri(uri) {
+                data_uri::parse(uri)?.data
+            } else {
+                let mut data = Vec::new();
+                File::open(base.join(uri))?.read_to_end(&mut data)?;
+                data
+            };
+
+            if data.len() < buffer.length() {
+                return Err(ErrorKind::InvalidBuffer(buffer.index(), "too short").into());
+            }
+
+            buffers.push(data);
+        }
+
+        Ok(Buffers { buffers: buffers })
+    }
+
+    pub fn buffer(&self, buffer: &gltf::Buffer) -> Option<&[u8]> {
+        self.buffers.get(buffer.index()).map(|b| &b[..])
+    }
+
+    pub fn view(&self, view: &gltf::buffer::View) -> Option<&[u8]> {
+        let start = view.offset();
+        let end = start + view.length();
+
+        self.buffer(&view.buffer()).and_then(|b| b.get(start..end))
+    }
+}
+
+impl Source for Buffers {
+    fn source_buffer(&self, buffer: &gltf::Buffer) -> &[u8] {
+        self.buffer(buffer).unwrap_or(&[])
+    }
+}
 
 pub struct GltfLoader {
     gltf: Gltf,
     path: PathBuf,
     buffers: Buffers,
+    /// Textures decoded for models of the document, shared between primitives.
+    textures: AssetCache<Texture>,
 }
 
 impl GltfLoader {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<GltfLoader> {
+        Self::with_textures(path, &AssetCache::new())
+    }
+
+    /// Load the document at the given path, sharing textures through the given cache.
+    ///
+    /// Both JSON documents and binary glTF containers are supported.
+    pub fn with_textures<P: AsRef<Path>>(
+        path: P,
+        textures: &AssetCache<Texture>,
+    ) -> Result<GltfLoader> {
+        let path = path.as_ref();
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::from_slice_with_textures(&bytes, path, textures)
+    }
+
+    /// Load a document from memory, like one embedded in the binary.
+    ///
+    /// The document is treated as if it was loaded from the given path, which relative URIs are
+    /// resolved against.
+    pub fn from_slice<P: AsRef<Path>>(bytes: &[u8], path: P) -> Result<GltfLoader> {
+        Self::from_slice_with_textures(bytes, path, &AssetCache::new())
+    }
+
+    /// Load a document from memory, sharing textures through the given cache.
+    pub fn from_slice_with_textures<P: AsRef<Path>>(
+        bytes: &[u8],
+        path: P,
+        textures: &AssetCache<Texture>,
+    ) -> Result<GltfLoader> {
         let path = path.as_ref().to_owned();
-        let config = Config { validation_strategy: ValidationStrategy::Complete };
-        let (gltf, buffers) = gltf_importer::import_with_config(&path, config)?;
+
+        let (json, bin) = if glb::is_glb(bytes) {
+            let glb = glb::parse(bytes)?;
+            (glb.json, glb.bin)
+        } else {
+            (bytes, None)
+        };
+
+        let gltf = Gltf::from_slice(json)?.validate_completely()?;
+        let base = path.parent().unwrap_or_else(|| Path::new("")).to_owned();
+        let buffers = Buffers::load(&gltf, &base, bin)?;
 
         Ok(GltfLoader {
             gltf: gltf,
             path: path,
             buffers: buffers,
+            textures: textures.clone(),
         })
     }
 
+    /// Path the document was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Paths of all images the document refers to, which are not embedded in it.
+    pub fn image_paths(&self) -> Vec<PathBuf> {
+        let parent = self.path.parent().unwrap_or_else(|| Path::new(""));
+        let mut paths = Vec::new();
+
+        for image in self.gltf.images() {
+            if let Data::Uri { uri, .. } = image.data() {
+                if !data_uri::is_data_uri(uri) {
+                    paths.push(parent.join(uri));
+                }
+            }
+        }
+
+        paths
+    }
+
     fn model_from_mesh(&self, mesh: &Mesh) -> Result<Model> {
-        Model::from_gltf(&self.path, &self.buffers, mesh)
+        Model::from_gltf(&self.path, &self.buffers, mesh, &self.textures)
     }
 
     pub fn model_from_first(&self) -> Result<Option<Model>> {
@@ -41,7 +170,14 @@ impl GltfLoader {
             if let Some(node_name) = node.name() {
                 if node_name == name {
                     if let Some(m) = node.mesh() {
-                        return Ok(Some(self.model_from_mesh(&m)?));
+                        let mut model = self.model_from_mesh(&m)?;
+                        model.node = Some(node.index());
+
+                        if let Some(skin) = node.skin() {
+                            model.skeleton = Some(Arc::new(self.skeleton_from_skin(&skin)?));
+                        }
+
+                        return Ok(Some(model));
                     }
                 }
             }
@@ -49,4 +185,33 @@ impl GltfLoader {
 
         Ok(None)
     }
+
+    fn skeleton_from_skin(&self, skin: &Skin) -> Result<Skeleton> {
+        Skeleton::from_gltf(&self.gltf, &self.buffers, skin)
+    }
+
+    /// Load all animations in the document.
+    pub fn animations(&self) -> Result<Vec<AnimationClip>> {
+        self.gltf
+            .animations()
+            .map(|a| AnimationClip::from_gltf(&self.buffers, &a))
+            .collect()
+    }
+
+    /// Load the animation with the given name.
+    pub fn animation(&self, name: &str) -> Result<Option<AnimationClip>> {
+        for animation in self.gltf.animations() {
+            if animation.name() == Some(name) {
+                return Ok(Some(AnimationClip::from_gltf(&self.buffers, &animation)?));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl fmt::Debug for GltfLoader {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "GltfLoader {{ path: {:?} }}", self.path)
+    }
 }
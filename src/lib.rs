@@ -22,9 +22,14 @@ extern crate bit_vec;
 extern crate image;
 extern crate gltf;
 extern crate gltf_utils;
-extern crate gltf_importer;
 extern crate shuteye;
+extern crate rusttype;
 
+pub mod animation;
+pub mod assets;
+pub mod bytes;
+pub mod data_uri;
+pub mod glb;
 pub mod gltf_loader;
 pub mod events;
 pub mod errors;
@@ -34,12 +39,18 @@ pub mod pressed_keys;
 pub mod fps_counter;
 pub mod player;
 pub mod static_entity;
+pub mod transform;
 pub mod camera;
 pub mod texture;
+pub mod text;
+pub mod ui;
 pub mod gfx_thread;
 pub mod model;
+pub mod particles;
 pub mod scheduler;
+pub mod tween;
 pub mod scene;
+pub mod loading_scene;
 pub mod scene_object;
 pub mod boxed_scene;
 pub mod into_boxed_scene;
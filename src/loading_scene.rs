@@ -0,0 +1,102 @@
+//! A scene which shows a loading screen until its assets have been loaded.
+
+use super::assets::{Dependency, LoadProgress, Progress};
+use super::boxed_scene::BoxedScene;
+use super::errors::*;
+use super::into_boxed_scene::IntoBoxedScene;
+use gfx::Gfx;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Core state which is able to draw a loading screen.
+pub trait LoadingScreen {
+    /// Draw the loading screen for the current tick.
+    fn draw_loading_screen(&mut self, progress: &LoadProgress) -> Result<()>;
+}
+
+/// Shows a loading screen until all dependencies are loaded, then builds and switches over to
+/// the actual scene.
+pub struct LoadingScene<F> {
+    progress: Progress,
+    dependencies: Vec<Box<Dependency>>,
+    build: F,
+}
+
+impl<F> LoadingScene<F> {
+    /// Show the given progress until loading is done, after which the scene is built using the
+    /// given function.
+    pub fn new(progress: Progress, build: F) -> LoadingScene<F> {
+        LoadingScene {
+            progress: progress,
+            dependencies: Vec::new(),
+            build: build,
+        }
+    }
+
+    /// Wait for the given dependency before building the scene.
+    pub fn depend_on<D>(mut self, dependency: &D) -> LoadingScene<F>
+    where
+        D: 'static + Dependency + Clone,
+    {
+        self.dependencies.push(Box::new(dependency.clone()));
+        self
+    }
+}
+
+impl<C, S, F> IntoBoxedScene<C> for LoadingScene<F>
+where
+    C: 'static + LoadingScreen,
+    S: 'static + IntoBoxedScene<C>,
+    F: 'static + FnOnce() -> Result<S>,
+{
+    fn into_boxed_scene(self, gfx: &Gfx) -> Result<Box<BoxedScene<C>>> {
+        Ok(Box::new(Loading {
+            gfx: gfx.clone(),
+            progress: self.progress,
+            dependencies: self.dependencies,
+            build: Some(self.build),
+            scene: None,
+        }))
+    }
+}
+
+/// A loading scene which has been handed over to the core loop.
+struct Loading<C, F> {
+    gfx: Gfx,
+    progress: Progress,
+    dependencies: Vec<Box<Dependency>>,
+    build: Option<F>,
+    /// The actual scene, once it has been built.
+    scene: Option<Box<BoxedScene<C>>>,
+}
+
+impl<C, S, F> BoxedScene<C> for Loading<C, F>
+where
+    C: LoadingScreen,
+    S: IntoBoxedScene<C>,
+    F: FnOnce() -> Result<S>,
+{
+    fn tick(&mut self, core: Rc<RefCell<C>>) -> Result<()> {
+        if let Some(ref mut scene) = self.scene {
+            return scene.tick(core);
+        }
+
+        let mut done = true;
+
+        // fails if any dependency failed to load.
+        for dependency in &self.dependencies {
+            done = dependency.is_done()? && done;
+        }
+
+        if done {
+            if let Some(build) = self.build.take() {
+                let mut scene = build()?.into_boxed_scene(&self.gfx)?;
+                scene.tick(core)?;
+                self.scene = Some(scene);
+                return Ok(());
+            }
+        }
+
+        core.try_borrow_mut()?.draw_loading_screen(&self.progress.get())
+    }
+}
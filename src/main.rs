@@ -3,34 +3,85 @@ extern crate threedge;
 extern crate cgmath;
 
 use std::sync::{Arc, RwLock};
+use threedge::assets::{AssetLoader, AssetManager, Handle, HotReload, Pending};
 use threedge::camera::Camera;
 use threedge::core_loop::CoreLoop;
 use threedge::core_state::CoreState;
 use threedge::errors::*;
+use threedge::gfx::color::Color;
+use threedge::gfx::environment::Environment;
 use threedge::gltf_loader::GltfLoader;
+use threedge::loading_scene::LoadingScene;
+use threedge::model::Model;
+use threedge::particles::{EmitterSettings, ParticleEmitter};
 use threedge::player::Player;
 use threedge::scene::Scene;
 use threedge::static_entity::StaticEntity;
+use threedge::text::Font;
+use threedge::tween::{Easing, Property, Tween};
 
 struct SceneState {}
 
-fn setup_scene() -> Result<Scene<CoreState, SceneState>> {
-    let player = GltfLoader::from_file("assets/player.gltf")?;
+/// Assets of the scene, loaded in the background.
+#[derive(Clone)]
+struct SceneAssets {
+    loader: AssetLoader,
+    player_gltf: Pending<Handle<GltfLoader>>,
+    player: Pending<Handle<Model>>,
+    floor: Pending<Handle<Model>>,
+}
+
+impl SceneAssets {
+    fn load(loader: &AssetLoader) -> SceneAssets {
+        SceneAssets {
+            loader: loader.clone(),
+            player_gltf: loader.gltf("assets/player.gltf"),
+            player: loader.model("assets/player.gltf", "Player"),
+            floor: loader.model("assets/assets.gltf", "Floor"),
+        }
+    }
+}
 
-    let player = Player::new(player.model_from_node("Player")?.ok_or(
-        ErrorKind::NoNode("Player"),
-    )?);
+fn setup_scene(assets: &SceneAssets) -> Result<Scene<CoreState, SceneState>> {
+    let player_gltf = assets.player_gltf.wait()?;
+    let player = Player::new((*assets.player.wait()?).clone());
 
-    let assets = GltfLoader::from_file("assets/assets.gltf")?;
+    // the walk cycle plays while the player is moving.
+    if let Some(walk) = player_gltf.animation("Walk")? {
+        player.play(walk)?;
+    }
 
     let mut scene = Scene::new(SceneState {});
+    scene.set_environment(Environment::new(Color::from_rgb(0.1, 0.1, 0.15)));
 
-    scene.register(Arc::new(RwLock::new(Camera::new(&player))));
-    scene.register(player);
+    let camera = Arc::new(RwLock::new(Camera::new(&player)));
+
+    // zoom in on the player when the scene starts.
+    scene.tween(
+        Tween::new(Property::zoom(&camera), 0.5, 200)
+            .from(0.0)
+            .easing(Easing::CubicInOut),
+    );
+
+    scene.register(camera);
 
-    let floor = StaticEntity::new(assets.model_from_node("Floor")?.ok_or(
-        ErrorKind::NoNode("Floor"),
-    )?);
+    let mut trail = ParticleEmitter::new(EmitterSettings {
+        start_color: Color::from_rgba(1.0, 0.8, 0.3, 1.0),
+        end_color: Color::from_rgba(1.0, 0.2, 0.0, 0.0),
+        ..EmitterSettings::default()
+    });
+    trail.follow(&player);
+    scene.register(trail);
+
+    let floor = StaticEntity::new((*assets.floor.wait()?).clone());
+
+    // models are replaced as soon as their files are saved.
+    let hot_reload = HotReload::new(&assets.loader);
+    hot_reload.track("assets/player.gltf", "Player", &player)?;
+    hot_reload.track("assets/assets.gltf", "Floor", &floor)?;
+    scene.register(hot_reload);
+
+    scene.register(player);
     scene.register(floor);
 
     Ok(scene)
@@ -39,7 +90,21 @@ fn setup_scene() -> Result<Scene<CoreState, SceneState>> {
 fn entry() -> Result<()> {
     let mut core_loop = CoreLoop::new()?;
 
-    core_loop.set_scene(setup_scene()?)?;
+    core_loop.set_debug_font(
+        Font::from_path("assets/fonts/DejaVuSansMono.ttf", 16.0)?,
+    )?;
+
+    let loader = AssetLoader::new(AssetManager::new(), 2)?;
+    let assets = SceneAssets::load(&loader);
+
+    let loaded = assets.clone();
+
+    let scene = LoadingScene::new(loader.progress(), move || setup_scene(&loaded))
+        .depend_on(&assets.player_gltf)
+        .depend_on(&assets.player)
+        .depend_on(&assets.floor);
+
+    core_loop.set_scene(scene)?;
     core_loop.run()?;
 
     Ok(())
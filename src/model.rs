@@ -1,26 +1,50 @@
+use super::animation::Skeleton;
+use super::assets::AssetCache;
+use super::assets::manager::path_key;
+use super::data_uri;
 use super::errors::*;
+use super::gltf_loader::Buffers;
 use cgmath::Point3;
-use gfx::{GeometryId, Vertex};
+use gfx::{MeshId, Vertex};
+use gfx::morph::MorphTarget;
 use gfx::primitive::Primitive;
 use gfx::primitives::Primitives;
-use gltf::Mesh;
+use gltf::{self, Mesh};
 use gltf::image::Data;
-use gltf_importer::Buffers;
-use gltf_utils::PrimitiveIterators;
+use gltf::texture::{self as gltf_texture, MagFilter, MinFilter, WrappingMode};
+use gltf_utils::{AccessorIter, PrimitiveIterators};
 use std::iter;
 use std::path::Path;
-use texture;
+use std::sync::Arc;
+use texture::{self, Sampler, Texture};
+use texture::sampler::{Filter, Wrap};
 
-#[derive(Debug)]
+/// A model loaded from glTF.
+///
+/// Cloning a model is cheap, and all clones share the same mesh on the GPU.
+#[derive(Debug, Clone)]
 pub struct Model {
-    id: GeometryId,
+    mesh_id: MeshId,
     pub location: Point3<f32>,
     pub primitives: Primitives,
+    /// Skeleton the mesh is skinned to, if any.
+    pub skeleton: Option<Arc<Skeleton>>,
+    /// Default weights of the morph targets of all primitives.
+    pub weights: Vec<f32>,
+    /// Index of the glTF node the model was loaded from, which animation channels refer to.
+    pub node: Option<usize>,
 }
 
 impl Model {
-    pub fn from_gltf(path: &Path, buffers: &Buffers, mesh: &Mesh) -> Result<Model> {
+    /// Load the given mesh, sharing decoded textures through the given cache.
+    pub fn from_gltf(
+        path: &Path,
+        buffers: &Buffers,
+        mesh: &Mesh,
+        textures: &AssetCache<Texture>,
+    ) -> Result<Model> {
         let mut primitives = Vec::new();
+        let mut target_count = 0;
 
         for p in mesh.primitives() {
             let mut positions = p.positions(buffers).ok_or(ErrorKind::NoPositions)?;
@@ -30,15 +54,34 @@ impl Model {
                 .map(|t| Box::new(t) as Box<Iterator<Item = [f32; 2]>>)
                 .unwrap_or_else(|| Box::new(iter::repeat([0.0, 0.0])));
 
+            let mut joints = p.joints_u16(0, buffers)
+                .map(|j| {
+                    Box::new(j.map(|j| [j[0] as f32, j[1] as f32, j[2] as f32, j[3] as f32])) as
+                        Box<Iterator<Item = [f32; 4]>>
+                })
+                .unwrap_or_else(|| Box::new(iter::repeat([0.0; 4])));
+
+            let mut weights = p.weights_f32(0, buffers)
+                .map(|w| Box::new(w) as Box<Iterator<Item = [f32; 4]>>)
+                .unwrap_or_else(|| Box::new(iter::repeat([0.0; 4])));
+
             let mut vertices = Vec::new();
 
             loop {
-                match (positions.next(), normals.next(), tex_coords.next()) {
-                    (Some(p), Some(normal), Some(tex_coord)) => {
+                match (
+                    positions.next(),
+                    normals.next(),
+                    tex_coords.next(),
+                    joints.next(),
+                    weights.next(),
+                ) {
+                    (Some(p), Some(normal), Some(tex_coord), Some(joints), Some(weights)) => {
                         vertices.push(Vertex {
                             position: [p[2], -p[1], -p[0]],
                             normal: normal,
                             tex_coord: tex_coord,
+                            joints: joints,
+                            weights: weights,
                         });
                     }
                     _ => {
@@ -51,50 +94,210 @@ impl Model {
                 .ok_or(ErrorKind::NoIndices)?
                 .collect();
 
+            let mut morph_targets = Vec::new();
+
+            for target in p.morph_targets() {
+                let positions = match target.positions() {
+                    Some(accessor) => {
+                        AccessorIter::<[f32; 3]>::new(accessor, buffers)
+                            .map(|p| [p[2], -p[1], -p[0]])
+                            .collect()
+                    }
+                    None => Vec::new(),
+                };
+
+                let normals = match target.normals() {
+                    Some(accessor) => AccessorIter::<[f32; 3]>::new(accessor, buffers).collect(),
+                    None => Vec::new(),
+                };
+
+                morph_targets.push(MorphTarget {
+                    positions: positions,
+                    normals: normals,
+                });
+            }
+
+            target_count = usize::max(target_count, morph_targets.len());
+
             let material = p.material();
 
             let pbr_metallic_roughness = material.pbr_metallic_roughness();
 
             let base_color_factor = pbr_metallic_roughness.base_color_factor().into();
-            let mut base_color_texture = None;
-
-            if let Some(base_color_texture_in) = pbr_metallic_roughness.base_color_texture() {
-                let texture = base_color_texture_in.texture();
-                let source = texture.source();
-                let data = source.data();
-
-                match data {
-                    Data::Uri { uri, mime_type } => {
-                        if let Some(parent) = path.parent() {
-                            let path = parent.join(uri);
-                            base_color_texture = Some(texture::load_from_path(mime_type, &path)?);
-                        }
-                    }
-                    Data::View { view, mime_type } => {
-                        if let Some(buffer) = buffers.view(&view) {
-                            base_color_texture =
-                                Some(texture::load_from_memory(mime_type, buffer)?);
-                        }
-                    }
-                }
-            }
+
+            let base_color_texture = match pbr_metallic_roughness.base_color_texture() {
+                Some(info) => load_texture(path, buffers, &info.texture(), textures)?,
+                None => None,
+            };
 
             primitives.push(Primitive {
                 vertices: vertices,
                 indices: indices,
                 base_color_factor: base_color_factor,
                 base_color_texture: base_color_texture,
+                morph_targets: morph_targets,
             });
         }
 
+        let weights = match mesh.weights() {
+            Some(weights) => weights.to_vec(),
+            None => vec![0.0; target_count],
+        };
+
         Ok(Model {
-            id: GeometryId::allocate(),
+            mesh_id: MeshId::allocate(),
             location: Point3::new(0.0, 0.0, 0.0),
             primitives: Primitives::new(primitives),
+            skeleton: None,
+            weights: weights,
+            node: None,
         })
     }
 
+    pub fn mesh_id(&self) -> MeshId {
+        self.mesh_id
+    }
+
+    /// Take over the mesh of another model, used when a model is reloaded.
+    pub fn with_mesh_id(mut self, mesh_id: MeshId) -> Model {
+        self.mesh_id = mesh_id;
+        self
+    }
+
     pub fn primitives(&self) -> Primitives {
         self.primitives.clone()
     }
 }
+
+/// Load the image of a base color texture through the cache.
+///
+/// Only base color textures are loaded, since the shaders do not sample the other maps. Their
+/// color space is the one of the image, which is sRGB unless a KTX2 or DDS format says otherwise.
+fn load_texture(
+    path: &Path,
+    buffers: &Buffers,
+    texture: &gltf::Texture,
+    textures: &AssetCache<Texture>,
+) -> Result<Option<Arc<Texture>>> {
+    let sampler = sampler_from_gltf(&texture.sampler());
+    let source = texture.source();
+
+    // embedded images are identified by their index in the document.
+    let embedded_key = || {
+        let key = format!("{}#image{}", path_key(path), source.index());
+        texture_key(&key, &sampler)
+    };
+
+    let tag = |mut texture: Texture| {
+        texture.sampler = sampler;
+        texture
+    };
+
+    let loaded = match source.data() {
+        Data::Uri { uri, mime_type } if data_uri::is_data_uri(uri) => {
+            Some(textures.get_or_load(&embedded_key(), || {
+                let decoded = data_uri::parse(uri)?;
+
+                let mime_type = match (mime_type, decoded.mime_type.as_ref()) {
+                    (Some(mime_type), _) => mime_type,
+                    (None, Some(mime_type)) => mime_type.as_str(),
+                    (None, None) => "",
+                };
+
+                Ok(tag(texture::load_from_memory(mime_type, &decoded.data)?))
+            })?)
+        }
+        Data::Uri { uri, mime_type } => {
+            match path.parent() {
+                Some(parent) => {
+                    let path = parent.join(uri);
+                    let key = texture_key(&path_key(&path), &sampler);
+
+                    Some(textures.get_or_load(&key, || {
+                        Ok(tag(texture::load_from_path(mime_type, &path)?))
+                    })?)
+                }
+                None => None,
+            }
+        }
+        Data::View { view, mime_type } => {
+            match buffers.view(&view) {
+                Some(buffer) => {
+                    Some(textures.get_or_load(&embedded_key(), || {
+                        Ok(tag(texture::load_from_memory(mime_type, buffer)?))
+                    })?)
+                }
+                None => None,
+            }
+        }
+    };
+
+    Ok(loaded.map(|t| t.shared()))
+}
+
+/// Key of a texture, the same image sampled differently is cached separately.
+fn texture_key(image: &str, sampler: &Sampler) -> String {
+    let mut key = image.to_string();
+
+    if *sampler != Sampler::default() {
+        key.push_str(&format!("?{:?}", sampler));
+    }
+
+    key
+}
+
+fn wrap_from_gltf(mode: WrappingMode) -> Wrap {
+    match mode {
+        WrappingMode::ClampToEdge => Wrap::ClampToEdge,
+        WrappingMode::MirroredRepeat => Wrap::MirroredRepeat,
+        WrappingMode::Repeat => Wrap::Repeat,
+    }
+}
+
+/// Convert glTF sampler settings, falling back to linear filtering where unspecified.
+fn sampler_from_gltf(sampler: &gltf_texture::Sampler) -> Sampler {
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => Filter::Nearest,
+        _ => Filter::Linear,
+    };
+
+    let (min_filter, mipmap_filter, mipmaps) = min_filter_from_gltf(sampler.min_filter());
+
+    Sampler {
+        mag_filter: mag_filter,
+        min_filter: min_filter,
+        mipmap_filter: mipmap_filter,
+        mipmaps: mipmaps,
+        wrap_s: wrap_from_gltf(sampler.wrap_s()),
+        wrap_t: wrap_from_gltf(sampler.wrap_t()),
+    }
+}
+
+/// Filters for minification and between mip levels, and whether mipmaps are used at all.
+///
+/// `Nearest` and `Linear` sample the full size texture only.
+fn min_filter_from_gltf(min_filter: Option<MinFilter>) -> (Filter, Filter, bool) {
+    match min_filter {
+        Some(MinFilter::Nearest) => (Filter::Nearest, Filter::Nearest, false),
+        Some(MinFilter::Linear) => (Filter::Linear, Filter::Linear, false),
+        Some(MinFilter::NearestMipmapNearest) => (Filter::Nearest, Filter::Nearest, true),
+        Some(MinFilter::NearestMipmapLinear) => (Filter::Nearest, Filter::Linear, true),
+        Some(MinFilter::LinearMipmapNearest) => (Filter::Linear, Filter::Nearest, true),
+        _ => (Filter::Linear, Filter::Linear, true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_filter_without_mipmaps() {
+        assert!(!min_filter_from_gltf(Some(MinFilter::Nearest)).2);
+        assert!(!min_filter_from_gltf(Some(MinFilter::Linear)).2);
+        assert!(min_filter_from_gltf(Some(MinFilter::LinearMipmapLinear)).2);
+
+        // unspecified filters are up to the renderer, which uses trilinear filtering.
+        assert_eq!((Filter::Linear, Filter::Linear, true), min_filter_from_gltf(None));
+    }
+}
@@ -0,0 +1,286 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use gfx::billboard::Billboard;
+use gfx::color::Color;
+use std::f32;
+
+/// Settings of a particle emitter.
+#[derive(Debug, Clone, Copy)]
+pub struct EmitterSettings {
+    /// Particles spawned per second.
+    pub spawn_rate: f32,
+    /// Maximum number of live particles, no more are spawned while at the limit.
+    pub max_particles: usize,
+    /// Seconds each particle lives.
+    pub lifetime: f32,
+    /// Direction particles are emitted in, the center of the cone.
+    pub direction: Vector3<f32>,
+    /// Half-angle of the cone particles are emitted in, in radians.
+    pub spread: f32,
+    /// Minimum initial speed, in units per second.
+    pub min_speed: f32,
+    /// Maximum initial speed, in units per second.
+    pub max_speed: f32,
+    /// Acceleration applied to all particles.
+    pub gravity: Vector3<f32>,
+    /// Color when spawned.
+    pub start_color: Color,
+    /// Color when dying, colors are interpolated in between.
+    pub end_color: Color,
+    /// Size when spawned.
+    pub start_size: f32,
+    /// Size when dying, sizes are interpolated in between.
+    pub end_size: f32,
+}
+
+impl Default for EmitterSettings {
+    fn default() -> EmitterSettings {
+        EmitterSettings {
+            spawn_rate: 20.0,
+            max_particles: 1000,
+            lifetime: 1.0,
+            direction: Vector3::new(0.0, 1.0, 0.0),
+            spread: 0.3,
+            min_speed: 1.0,
+            max_speed: 2.0,
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            start_color: Color::from_rgba(1.0, 1.0, 1.0, 1.0),
+            end_color: Color::from_rgba(1.0, 1.0, 1.0, 0.0),
+            start_size: 0.1,
+            end_size: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Particle {
+    pub position: Point3<f32>,
+    pub velocity: Vector3<f32>,
+    /// Seconds since the particle was spawned.
+    pub age: f32,
+}
+
+/// Simulates particles spawned from a single point.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    pub settings: EmitterSettings,
+    /// Where new particles are spawned.
+    pub position: Point3<f32>,
+    /// If new particles are spawned, existing particles are simulated either way.
+    pub enabled: bool,
+    particles: Vec<Particle>,
+    /// Fractional particles left over from previous steps.
+    spawn_accumulator: f32,
+    /// State of the random number generator.
+    seed: u32,
+}
+
+impl Emitter {
+    pub fn new(settings: EmitterSettings) -> Emitter {
+        Emitter {
+            settings: settings,
+            position: Point3::new(0.0, 0.0, 0.0),
+            enabled: true,
+            particles: Vec::new(),
+            spawn_accumulator: 0.0,
+            seed: 0x9e37_79b9,
+        }
+    }
+
+    /// All live particles.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Advance the simulation by the given number of seconds.
+    pub fn step(&mut self, dt: f32) {
+        let lifetime = self.settings.lifetime;
+        let gravity = self.settings.gravity;
+
+        self.particles.retain(|p| p.age + dt < lifetime);
+
+        for p in &mut self.particles {
+            p.age += dt;
+            p.velocity += gravity * dt;
+            p.position += p.velocity * dt;
+        }
+
+        if !self.enabled {
+            self.spawn_accumulator = 0.0;
+            return;
+        }
+
+        self.spawn_accumulator += self.settings.spawn_rate * dt;
+        let count = self.spawn_accumulator.floor();
+        self.spawn_accumulator -= count;
+        self.burst(count as usize);
+    }
+
+    /// Spawn the given number of particles immediately, respecting the particle limit.
+    pub fn burst(&mut self, count: usize) {
+        let available = self.settings
+            .max_particles
+            .saturating_sub(self.particles.len());
+
+        for _ in 0..usize::min(count, available) {
+            let particle = self.spawn();
+            self.particles.push(particle);
+        }
+    }
+
+    /// Build billboards for all live particles.
+    pub fn billboards(&self, out: &mut Vec<Billboard>) {
+        let s = &self.settings;
+
+        for p in &self.particles {
+            let t = if s.lifetime > 0.0 {
+                f32::min(1.0, p.age / s.lifetime)
+            } else {
+                1.0
+            };
+
+            out.push(Billboard {
+                position: p.position.into(),
+                size: s.start_size + (s.end_size - s.start_size) * t,
+                color: s.start_color.lerp(s.end_color, t),
+            });
+        }
+    }
+
+    fn spawn(&mut self) -> Particle {
+        let s = self.settings;
+
+        let speed = s.min_speed + (s.max_speed - s.min_speed) * self.random();
+        let direction = self.random_direction(s.direction, s.spread);
+
+        Particle {
+            position: self.position,
+            velocity: direction * speed,
+            age: 0.0,
+        }
+    }
+
+    /// Pick a random direction inside of a cone.
+    fn random_direction(&mut self, axis: Vector3<f32>, spread: f32) -> Vector3<f32> {
+        let axis = if axis.magnitude2() > 0.0 {
+            axis.normalize()
+        } else {
+            Vector3::unit_y()
+        };
+
+        // any vector which is not parallel to the axis works for building a basis.
+        let other = if axis.x.abs() < 0.9 {
+            Vector3::unit_x()
+        } else {
+            Vector3::unit_y()
+        };
+
+        let u = axis.cross(other).normalize();
+        let v = axis.cross(u);
+
+        // uniformly distributed over the spherical cap.
+        let cos_theta = 1.0 - self.random() * (1.0 - spread.cos());
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = self.random() * f32::consts::PI * 2.0;
+
+        axis * cos_theta + (u * phi.cos() + v * phi.sin()) * sin_theta
+    }
+
+    /// Random number in [0, 1), using xorshift.
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_rate() {
+        let mut emitter = Emitter::new(EmitterSettings {
+            spawn_rate: 10.0,
+            lifetime: 100.0,
+            ..EmitterSettings::default()
+        });
+
+        for _ in 0..4 {
+            emitter.step(0.25);
+        }
+
+        assert_eq!(10, emitter.particles().len());
+    }
+
+    #[test]
+    fn test_lifetime_and_limit() {
+        let mut emitter = Emitter::new(EmitterSettings {
+            max_particles: 5,
+            lifetime: 0.5,
+            ..EmitterSettings::default()
+        });
+
+        emitter.burst(10);
+        assert_eq!(5, emitter.particles().len());
+
+        emitter.enabled = false;
+        emitter.step(0.4);
+        assert_eq!(5, emitter.particles().len());
+        emitter.step(0.2);
+        assert_eq!(0, emitter.particles().len());
+    }
+
+    #[test]
+    fn test_velocity_cone() {
+        let mut emitter = Emitter::new(EmitterSettings {
+            direction: Vector3::new(0.0, 0.0, 2.0),
+            spread: 0.5,
+            ..EmitterSettings::default()
+        });
+
+        for _ in 0..1000 {
+            let d = emitter.random_direction(Vector3::new(0.0, 0.0, 2.0), 0.5);
+            assert!((d.magnitude() - 1.0).abs() < 0.001);
+            assert!(d.z >= 0.5f32.cos() - 0.001);
+        }
+    }
+
+    #[test]
+    fn test_gravity() {
+        let mut emitter = Emitter::new(EmitterSettings {
+            min_speed: 0.0,
+            max_speed: 0.0,
+            gravity: Vector3::new(0.0, -10.0, 0.0),
+            ..EmitterSettings::default()
+        });
+
+        emitter.burst(1);
+        emitter.enabled = false;
+        emitter.step(0.5);
+
+        let p = emitter.particles()[0];
+        assert_eq!(-5.0, p.velocity.y);
+        assert!(p.position.y < 0.0);
+    }
+
+    #[test]
+    fn test_billboards_over_life() {
+        let mut emitter = Emitter::new(EmitterSettings {
+            lifetime: 1.0,
+            start_size: 1.0,
+            end_size: 3.0,
+            ..EmitterSettings::default()
+        });
+
+        emitter.burst(1);
+        emitter.enabled = false;
+        emitter.step(0.5);
+
+        let mut billboards = Vec::new();
+        emitter.billboards(&mut billboards);
+
+        assert_eq!(1, billboards.len());
+        assert_eq!(2.0, billboards[0].size);
+    }
+}
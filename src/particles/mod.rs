@@ -0,0 +1,9 @@
+//! # Particle systems
+//!
+//! Particles are simulated on the tick scheduler, and drawn as camera-facing billboards.
+
+pub mod emitter;
+pub mod particle_emitter;
+
+pub use self::emitter::{Emitter, EmitterSettings, Particle};
+pub use self::particle_emitter::ParticleEmitter;
@@ -0,0 +1,99 @@
+use super::emitter::{Emitter, EmitterSettings};
+use core_loop::TICK_SECONDS;
+use errors::*;
+use gfx::TextureId;
+use gfx::billboard::{Billboard, BillboardSource};
+use gfx::errors as gfx;
+use gfx::geometry::Geometry;
+use gfx::geometry_object::GeometryObject;
+use scheduler::{Scheduler, SchedulerSetup};
+use std::sync::{Arc, RwLock};
+
+/// The part of a particle emitter read by the render thread.
+#[derive(Debug, Clone)]
+struct ParticleSource {
+    emitter: Arc<RwLock<Emitter>>,
+    texture: Option<TextureId>,
+}
+
+impl BillboardSource for ParticleSource {
+    fn texture(&self) -> gfx::Result<Option<TextureId>> {
+        Ok(self.texture)
+    }
+
+    fn billboards(&self, out: &mut Vec<Billboard>) -> gfx::Result<()> {
+        self.emitter
+            .read()
+            .map_err(|_| gfx::ErrorKind::PoisonError)?
+            .billboards(out);
+
+        Ok(())
+    }
+}
+
+/// A particle emitter which can be registered in a scene.
+///
+/// Particles are simulated on every tick, and drawn as camera-facing billboards.
+pub struct ParticleEmitter {
+    source: ParticleSource,
+    /// Geometry the emitter follows, if any.
+    follow: Option<Box<Geometry>>,
+}
+
+impl ParticleEmitter {
+    pub fn new(settings: EmitterSettings) -> ParticleEmitter {
+        ParticleEmitter {
+            source: ParticleSource {
+                emitter: Arc::new(RwLock::new(Emitter::new(settings))),
+                texture: None,
+            },
+            follow: None,
+        }
+    }
+
+    /// Draw particles using a texture registered with `Gfx::register_texture`.
+    ///
+    /// Has to be set before the scene is set up.
+    pub fn set_texture(&mut self, texture: TextureId) {
+        self.source.texture = Some(texture);
+    }
+
+    /// Spawn particles from the position of the given object, following it as it moves.
+    pub fn follow(&mut self, object: &GeometryObject) {
+        self.follow = Some(object.geometry());
+    }
+
+    /// Shared handle to the emitter, used to move it, toggle it or burst particles from game
+    /// logic.
+    pub fn emitter(&self) -> Arc<RwLock<Emitter>> {
+        self.source.emitter.clone()
+    }
+
+    /// The source of billboards to register with the render thread.
+    pub fn billboard_source(&self) -> &BillboardSource {
+        &self.source
+    }
+}
+
+impl<S> SchedulerSetup<S> for ParticleEmitter {
+    fn setup_scheduler(&mut self, scheduler: &mut Scheduler<S>) {
+        let emitter = self.source.emitter.clone();
+        let follow = self.follow.as_ref().map(|g| g.clone_geometry());
+
+        scheduler.on_every_tick(Box::new(move |_, _| {
+            let position = match follow {
+                Some(ref geometry) => Some(geometry.read_lock()?.position()?),
+                None => None,
+            };
+
+            let mut e = emitter.write().map_err(|_| ErrorKind::PoisonError)?;
+
+            if let Some(position) = position {
+                e.position = position;
+            }
+
+            e.step(TICK_SECONDS);
+            Ok(())
+        }));
+    }
+}
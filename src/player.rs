@@ -1,29 +1,43 @@
+use super::animation::{AnimationClip, AnimationController, Animator};
+use super::assets::ModelObject;
+use super::core_loop::TICK_SECONDS;
 use super::errors::*;
 use super::model::Model;
 use super::scheduler::{Scheduler, SchedulerSetup};
-use cgmath::{Matrix4, Point3};
+use super::transform::{move_relative, trs};
+use cgmath::{Matrix4, One, Point3, Quaternion, Vector3};
 use cgmath::prelude::*;
-use gfx::GeometryId;
+use gfx::{GeometryId, MeshId};
 use gfx::errors as gfx;
 use gfx::geometry::Geometry;
 use gfx::geometry_accessor::GeometryAccessor;
 use gfx::geometry_object::GeometryObject;
 use gfx::primitives::Primitives;
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 #[derive(Debug)]
 pub struct PlayerGeometry {
     id: GeometryId,
     location: Point3<f32>,
+    /// Which way the player is facing.
+    rotation: Quaternion<f32>,
+    scale: Vector3<f32>,
     model: Model,
+    /// Plays back animations, if the model is skinned.
+    animator: Option<Animator>,
 }
 
 impl PlayerGeometry {
     pub fn new(model: Model) -> PlayerGeometry {
+        let animator = Animator::for_model(&model);
+
         PlayerGeometry {
             id: GeometryId::allocate(),
             location: Point3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
             model: model,
+            animator: animator,
         }
     }
 }
@@ -41,6 +55,50 @@ impl Player {
         Player { geometry: Arc::new(RwLock::new(PlayerGeometry::new(model))) }
     }
 
+    /// Play the given animation while the player is moving.
+    ///
+    /// Does nothing if the model of the player is neither skinned nor has morph targets.
+    pub fn play(&self, clip: AnimationClip) -> gfx::Result<()> {
+        let mut g = self.geometry.write().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?;
+
+        if let Some(ref mut animator) = g.animator {
+            animator.play(Arc::new(clip));
+        }
+
+        Ok(())
+    }
+
+    /// Let a controller drive the animation of the player.
+    ///
+    /// The controller is updated every tick, with the `speed` parameter set to how fast the
+    /// player is moving in units per second.
+    pub fn set_controller(&self, controller: AnimationController) -> gfx::Result<()> {
+        let mut g = self.geometry.write().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?;
+
+        if let Some(ref mut animator) = g.animator {
+            animator.set_controller(controller);
+        }
+
+        Ok(())
+    }
+
+    /// Set the weight of a morph target of the player, like a facial expression.
+    pub fn set_morph_weight(&self, index: usize, weight: f32) -> gfx::Result<()> {
+        let mut g = self.geometry.write().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?;
+
+        if let Some(ref mut animator) = g.animator {
+            animator.set_weight(index, weight);
+        }
+
+        Ok(())
+    }
+
     /// Get the position of the player.
     pub fn position(&self) -> gfx::Result<Point3<f32>> {
         self.geometry
@@ -48,6 +106,38 @@ impl Player {
             .map_err(|_| gfx::ErrorKind::PoisonError)?
             .position()
     }
+
+    /// Get the orientation of the player.
+    pub fn rotation(&self) -> gfx::Result<Quaternion<f32>> {
+        Ok(self.read()?.rotation)
+    }
+
+    pub fn set_rotation(&self, rotation: Quaternion<f32>) -> gfx::Result<()> {
+        self.write()?.rotation = rotation;
+        Ok(())
+    }
+
+    /// Get the scale of the player along each axis.
+    pub fn scale(&self) -> gfx::Result<Vector3<f32>> {
+        Ok(self.read()?.scale)
+    }
+
+    pub fn set_scale(&self, scale: Vector3<f32>) -> gfx::Result<()> {
+        self.write()?.scale = scale;
+        Ok(())
+    }
+
+    fn read(&self) -> gfx::Result<RwLockReadGuard<PlayerGeometry>> {
+        Ok(self.geometry.read().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?)
+    }
+
+    fn write(&self) -> gfx::Result<RwLockWriteGuard<PlayerGeometry>> {
+        Ok(self.geometry.write().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?)
+    }
 }
 
 impl GeometryObject for Player {
@@ -56,6 +146,33 @@ impl GeometryObject for Player {
     }
 }
 
+impl ModelObject for Player {
+    /// Replace the model, while keeping animations playing if the skeleton keeps its shape.
+    fn replace_model(&self, model: Model) -> Result<()> {
+        let mut g = self.geometry.write().map_err(|_| ErrorKind::PoisonError)?;
+
+        let compatible = match g.animator {
+            Some(ref animator) => {
+                let joints = animator.skeleton().map(|s| s.joints().len());
+                joints == model.skeleton.as_ref().map(|s| s.joints().len()) &&
+                    animator.weights().len() == model.weights.len()
+            }
+            None => false,
+        };
+
+        if !compatible {
+            g.animator = Animator::for_model(&model);
+        }
+
+        g.model = model;
+        Ok(())
+    }
+
+    fn clone_model_object(&self) -> Box<ModelObject> {
+        Box::new(Player { geometry: self.geometry.clone() })
+    }
+}
+
 impl Geometry for Arc<RwLock<PlayerGeometry>> {
     fn read_lock<'a>(&'a self) -> gfx::Result<Box<'a + GeometryAccessor>> {
         Ok(Box::new(
@@ -70,19 +187,38 @@ impl<'a> GeometryAccessor for RwLockReadGuard<'a, PlayerGeometry> {
     }
 
     fn transformation(&self) -> gfx::Result<Matrix4<f32>> {
-        Ok(Matrix4::from_translation(self.location.to_vec()))
+        Ok(trs(self.location, self.rotation, self.scale))
     }
 
     fn position(&self) -> gfx::Result<Point3<f32>> {
         Ok(self.location)
     }
 
+    fn mesh_id(&self) -> MeshId {
+        self.model.mesh_id()
+    }
+
     fn primitives(&self) -> gfx::Result<Primitives> {
         Ok(self.model.primitives())
     }
+
+    fn joints(&self) -> gfx::Result<Option<Vec<Matrix4<f32>>>> {
+        Ok(self.animator.as_ref().and_then(|a| {
+            a.skeleton().map(|_| a.palette().to_vec())
+        }))
+    }
+
+    fn morph_weights(&self) -> gfx::Result<Option<Vec<f32>>> {
+        Ok(self.animator.as_ref().and_then(|a| if a.weights().is_empty() {
+            None
+        } else {
+            Some(a.weights().to_vec())
+        }))
+    }
 }
 
 pub trait PlayerTransform {
+    /// Movement of the player for the current tick, relative to the direction it is facing.
     fn player_transform(&mut self) -> Result<Option<Matrix4<f32>>>;
 }
 
@@ -91,14 +227,36 @@ impl<S: PlayerTransform> SchedulerSetup<S> for Player {
         let geometry = self.geometry.clone();
 
         scheduler.on_every_tick(Box::new(move |_, gs| {
+            let transform = gs.player_transform()?;
+            let mut g = geometry.write().map_err(|_| ErrorKind::PoisonError)?;
+
             // perform player transform based on pressed keys
-            if let Some(transform) = gs.player_transform()? {
-                let mut g = geometry.write().map_err(|_| ErrorKind::PoisonError)?;
-                g.location = transform.transform_point(g.location);
+            if let Some(transform) = transform {
+                let (location, rotation) = move_relative(g.location, g.rotation, &transform);
+                g.location = location;
+                g.rotation = rotation;
                 g.location.x = clamp(-4.0, 4.0, g.location.x);
                 g.location.z = clamp(-4.0, 4.0, g.location.z);
             }
 
+            if let Some(ref mut animator) = g.animator {
+                let speed = transform
+                    .map(|t| t.w.truncate().magnitude() / TICK_SECONDS)
+                    .unwrap_or(0.0);
+
+                match animator.controller_mut() {
+                    Some(controller) => controller.set_parameter("speed", speed),
+                    None => {
+                        // without a controller, the clip only plays while moving.
+                        if speed == 0.0 {
+                            return Ok(());
+                        }
+                    }
+                }
+
+                animator.advance(TICK_SECONDS);
+            }
+
             Ok(())
         }));
     }
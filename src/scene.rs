@@ -5,8 +5,11 @@ use super::into_boxed_scene::IntoBoxedScene;
 use super::player::PlayerTransform;
 use super::scene_object::SceneObject;
 use super::scheduler::{Scheduler, SchedulerSetup};
+use super::tween::{self, Step};
 use cgmath::Matrix4;
 use gfx::Gfx;
+use gfx::environment::Environment;
+use gfx::post_process::PostProcess;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -18,6 +21,12 @@ pub struct SceneState<C, S> {
 pub struct Scene<C, S> {
     state: Rc<RefCell<S>>,
     objects: Vec<SceneObject>,
+    /// Background and ambient lighting, applied when the scene is set up.
+    environment: Environment,
+    /// Post-processing effects, applied when the scene is set up.
+    post_process: PostProcess,
+    /// Where the objects of the scene are drawn, once it has been set up.
+    gfx: Option<Gfx>,
     pub scheduler: Scheduler<SceneState<C, S>>,
 }
 
@@ -51,6 +60,9 @@ impl<C: 'static + CameraScroll + PlayerTransform, S: 'static> Scene<C, S> {
         Scene {
             state: Rc::new(RefCell::new(state)),
             objects: Vec::new(),
+            environment: Environment::default(),
+            post_process: PostProcess::default(),
+            gfx: None,
             scheduler: Scheduler::new(),
         }
     }
@@ -60,9 +72,28 @@ impl<C: 'static + CameraScroll + PlayerTransform, S: 'static> Scene<C, S> {
         self.objects.push(object.into());
     }
 
+    /// Set the background and ambient lighting of the scene.
+    pub fn set_environment(&mut self, environment: Environment) {
+        self.environment = environment;
+    }
+
+    /// Set the post-processing effects of the scene.
+    pub fn set_post_process(&mut self, post_process: PostProcess) {
+        self.post_process = post_process;
+    }
+
+    /// Start running a tween, or any other step, on the first tick of the scene.
+    pub fn tween<T: 'static + Step>(&mut self, step: T) {
+        tween::schedule(&mut self.scheduler, step);
+    }
+
     pub fn setup(&mut self, gfx: &Gfx) -> Result<()> {
         use self::SceneObject::*;
 
+        gfx.set_environment(self.environment.clone())?;
+        gfx.set_post_process(self.post_process.clone())?;
+        self.gfx = Some(gfx.clone());
+
         for object in &mut self.objects {
             match *object {
                 Player(ref mut player) => {
@@ -76,6 +107,14 @@ impl<C: 'static + CameraScroll + PlayerTransform, S: 'static> Scene<C, S> {
                 StaticEntity(ref mut static_entity) => {
                     gfx.register_geometry(static_entity)?;
                 }
+                ParticleEmitter(ref mut particle_emitter) => {
+                    gfx.register_billboards(particle_emitter.billboard_source())?;
+                    particle_emitter.setup_scheduler(&mut self.scheduler);
+                }
+                HotReload(ref mut hot_reload) => {
+                    hot_reload.set_gfx(gfx)?;
+                    hot_reload.setup_scheduler(&mut self.scheduler);
+                }
             }
         }
 
@@ -83,6 +122,31 @@ impl<C: 'static + CameraScroll + PlayerTransform, S: 'static> Scene<C, S> {
     }
 }
 
+impl<C, S> Drop for Scene<C, S> {
+    /// Stop drawing the geometry of the scene, so that its meshes are released when the scene
+    /// is replaced.
+    fn drop(&mut self) {
+        use self::SceneObject::*;
+
+        let gfx = match self.gfx.take() {
+            Some(gfx) => gfx,
+            None => return,
+        };
+
+        for object in &self.objects {
+            let result = match *object {
+                Player(ref player) => gfx.unregister_geometry(player),
+                StaticEntity(ref static_entity) => gfx.unregister_geometry(static_entity),
+                _ => Ok(()),
+            };
+
+            if let Err(e) = result {
+                warn!("failed to unregister geometry: {}", e);
+            }
+        }
+    }
+}
+
 impl<C, S> PlayerTransform for SceneState<C, S>
 where
     C: PlayerTransform,
@@ -1,4 +1,6 @@
+use super::assets::HotReload;
 use super::camera::Camera;
+use super::particles::ParticleEmitter;
 use super::player::Player;
 use super::static_entity::StaticEntity;
 use std::sync::{Arc, RwLock};
@@ -7,6 +9,8 @@ pub enum SceneObject {
     Player(Player),
     Camera(Arc<RwLock<Camera>>),
     StaticEntity(StaticEntity),
+    ParticleEmitter(ParticleEmitter),
+    HotReload(HotReload),
 }
 
 impl From<Arc<RwLock<Camera>>> for SceneObject {
@@ -26,3 +30,15 @@ impl From<StaticEntity> for SceneObject {
         SceneObject::StaticEntity(value)
     }
 }
+
+impl From<ParticleEmitter> for SceneObject {
+    fn from(value: ParticleEmitter) -> SceneObject {
+        SceneObject::ParticleEmitter(value)
+    }
+}
+
+impl From<HotReload> for SceneObject {
+    fn from(value: HotReload) -> SceneObject {
+        SceneObject::HotReload(value)
+    }
+}
@@ -1,18 +1,23 @@
+use super::assets::ModelObject;
+use super::errors::{ErrorKind, Result};
 use super::model::Model;
-use cgmath::{Matrix4, Point3};
+use super::transform::trs;
+use cgmath::{Matrix4, One, Point3, Quaternion, Vector3};
 use cgmath::prelude::*;
-use gfx::GeometryId;
+use gfx::{GeometryId, MeshId};
 use gfx::errors as gfx;
 use gfx::geometry::Geometry;
 use gfx::geometry_accessor::GeometryAccessor;
 use gfx::geometry_object::GeometryObject;
 use gfx::primitives::Primitives;
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 #[derive(Debug)]
 pub struct StaticEntityGeometry {
     id: GeometryId,
     location: Point3<f32>,
+    rotation: Quaternion<f32>,
+    scale: Vector3<f32>,
     model: Model,
 }
 
@@ -21,11 +26,17 @@ impl StaticEntityGeometry {
         StaticEntityGeometry {
             id: GeometryId::allocate(),
             location: Point3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
             model: model,
         }
     }
 }
 
+/// A handle to an entity which is not controlled by the player.
+///
+/// Clones of the handle refer to the same entity.
+#[derive(Clone)]
 pub struct StaticEntity {
     geometry: Arc<RwLock<StaticEntityGeometry>>,
 }
@@ -44,13 +55,65 @@ impl StaticEntity {
         Ok(())
     }
 
-    /// Get the position of the player.
+    /// Get the position of the entity.
     pub fn position(&self) -> gfx::Result<Point3<f32>> {
         self.geometry
             .read()
             .map_err(|_| gfx::ErrorKind::PoisonError)?
             .position()
     }
+
+    pub fn set_position(&self, position: Point3<f32>) -> gfx::Result<()> {
+        self.write()?.location = position;
+        Ok(())
+    }
+
+    /// Get the orientation of the entity.
+    pub fn rotation(&self) -> gfx::Result<Quaternion<f32>> {
+        Ok(self.read()?.rotation)
+    }
+
+    pub fn set_rotation(&self, rotation: Quaternion<f32>) -> gfx::Result<()> {
+        self.write()?.rotation = rotation;
+        Ok(())
+    }
+
+    /// Get the scale of the entity along each axis.
+    pub fn scale(&self) -> gfx::Result<Vector3<f32>> {
+        Ok(self.read()?.scale)
+    }
+
+    pub fn set_scale(&self, scale: Vector3<f32>) -> gfx::Result<()> {
+        self.write()?.scale = scale;
+        Ok(())
+    }
+
+    fn read(&self) -> gfx::Result<RwLockReadGuard<StaticEntityGeometry>> {
+        Ok(self.geometry.read().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?)
+    }
+
+    fn write(&self) -> gfx::Result<RwLockWriteGuard<StaticEntityGeometry>> {
+        Ok(self.geometry.write().map_err(
+            |_| gfx::ErrorKind::PoisonError,
+        )?)
+    }
+}
+
+impl ModelObject for StaticEntity {
+    fn replace_model(&self, model: Model) -> Result<()> {
+        self.geometry
+            .write()
+            .map_err(|_| ErrorKind::PoisonError)?
+            .model = model;
+
+        Ok(())
+    }
+
+    fn clone_model_object(&self) -> Box<ModelObject> {
+        Box::new(self.clone())
+    }
 }
 
 impl GeometryObject for StaticEntity {
@@ -73,13 +136,17 @@ impl<'a> GeometryAccessor for RwLockReadGuard<'a, StaticEntityGeometry> {
     }
 
     fn transformation(&self) -> gfx::Result<Matrix4<f32>> {
-        Ok(Matrix4::from_translation(self.location.to_vec()))
+        Ok(trs(self.location, self.rotation, self.scale))
     }
 
     fn position(&self) -> gfx::Result<Point3<f32>> {
         Ok(self.location)
     }
 
+    fn mesh_id(&self) -> MeshId {
+        self.model.mesh_id()
+    }
+
     fn primitives(&self) -> gfx::Result<Primitives> {
         Ok(self.model.primitives())
     }
@@ -0,0 +1,28 @@
+use gfx::errors as gfx;
+
+error_chain! {
+    foreign_links {
+        Io(::std::io::Error);
+    }
+
+    links {
+        Gfx(gfx::Error, gfx::ErrorKind);
+    }
+
+    errors {
+        InvalidFont {
+            description("invalid font")
+            display("invalid font")
+        }
+
+        GlyphsTooLarge(size: f32) {
+            description("glyphs too large")
+            display("glyphs at size {} do not fit into a font atlas", size)
+        }
+
+        FontNotRegistered {
+            description("font not registered")
+            display("font has not been registered with the graphics subsystem")
+        }
+    }
+}
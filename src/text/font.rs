@@ -0,0 +1,217 @@
+use super::errors::*;
+use super::layout::{self, TextOptions};
+use gfx::{Gfx, TextureId};
+use gfx::overlay::{Overlay, Rect};
+use rusttype::{FontCollection, Scale, point};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use texture::Texture;
+use texture::atlas;
+
+/// Limit for the width and height of the glyph atlas in pixels.
+const MAX_ATLAS_SIZE: u32 = 4096;
+/// Empty space around each glyph in the atlas, to avoid bleeding between glyphs.
+const PADDING: u32 = 1;
+
+/// A single glyph in a font.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    /// Texture coordinates of the glyph in the atlas.
+    pub uv: Rect,
+    /// Offset from the pen position on the baseline to the top-left corner of the glyph.
+    pub offset: [f32; 2],
+    /// Size of the glyph in pixels.
+    pub size: [f32; 2],
+    /// How far to move the pen after this glyph.
+    pub advance: f32,
+}
+
+/// A font rasterized at a fixed size.
+pub struct Font {
+    /// Atlas containing all glyphs, as white texels with coverage in the alpha channel.
+    pub atlas: Texture,
+    pub glyphs: HashMap<char, Glyph>,
+    /// Adjustment to the advance between pairs of characters.
+    pub kerning: HashMap<(char, char), f32>,
+    /// Distance from the top of a line to its baseline.
+    pub ascent: f32,
+    /// Distance between two consecutive baselines.
+    pub line_height: f32,
+    /// Texture of the atlas, once registered.
+    texture: Option<TextureId>,
+}
+
+impl Font {
+    /// Build a font from already rasterized glyphs.
+    pub fn new(
+        atlas: Texture,
+        glyphs: HashMap<char, Glyph>,
+        kerning: HashMap<(char, char), f32>,
+        ascent: f32,
+        line_height: f32,
+    ) -> Font {
+        Font {
+            atlas: atlas,
+            glyphs: glyphs,
+            kerning: kerning,
+            ascent: ascent,
+            line_height: line_height,
+            texture: None,
+        }
+    }
+
+    /// Load a TrueType font from the given path, rasterized at the given pixel size.
+    pub fn from_path<P: AsRef<Path>>(path: P, size: f32) -> Result<Font> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Font::from_bytes(bytes, size)
+    }
+
+    /// Load a TrueType font from memory, rasterizing all printable ASCII characters.
+    pub fn from_bytes(bytes: Vec<u8>, size: f32) -> Result<Font> {
+        let font = FontCollection::from_bytes(bytes).into_font().ok_or(
+            ErrorKind::InvalidFont,
+        )?;
+
+        let scale = Scale::uniform(size);
+        let v_metrics = font.v_metrics(scale);
+
+        let chars: Vec<char> = (32u8..127u8).map(|c| c as char).collect();
+
+        let mut rasterized = Vec::new();
+
+        for c in chars.iter().cloned() {
+            let glyph = match font.glyph(c) {
+                Some(glyph) => glyph.scaled(scale),
+                None => continue,
+            };
+
+            let advance = glyph.h_metrics().advance_width;
+            let glyph = glyph.positioned(point(0.0, 0.0));
+
+            let bitmap = glyph.pixel_bounding_box().map(|bb| {
+                let width = bb.width() as u32;
+                let height = bb.height() as u32;
+                let mut coverage = vec![0u8; (width * height) as usize];
+
+                glyph.draw(|x, y, v| {
+                    coverage[(y * width + x) as usize] = (v * 255.0) as u8;
+                });
+
+                ([bb.min.x as f32, bb.min.y as f32], width, height, coverage)
+            });
+
+            rasterized.push((c, advance, bitmap));
+        }
+
+        let sizes: Vec<(u32, u32)> = rasterized
+            .iter()
+            .map(|&(_, _, ref bitmap)| match *bitmap {
+                Some((_, width, height, _)) => (width + PADDING * 2, height + PADDING * 2),
+                None => (0, 0),
+            })
+            .collect();
+
+        let ((atlas_width, atlas_height), placements) = atlas::pack(&sizes, MAX_ATLAS_SIZE)
+            .ok_or(ErrorKind::GlyphsTooLarge(size))?;
+
+        let mut image_data = vec![0u8; (atlas_width * atlas_height * 4) as usize];
+
+        for pixel in image_data.chunks_mut(4) {
+            pixel[0] = 255;
+            pixel[1] = 255;
+            pixel[2] = 255;
+        }
+
+        let mut glyphs = HashMap::new();
+
+        for (&(c, advance, ref bitmap), &(x, y)) in rasterized.iter().zip(placements.iter()) {
+            let (gx, gy) = (x + PADDING, y + PADDING);
+
+            let glyph = match *bitmap {
+                Some((offset, width, height, ref coverage)) => {
+                    for row in 0..height {
+                        for col in 0..width {
+                            let index = ((gy + row) * atlas_width + gx + col) * 4 + 3;
+                            image_data[index as usize] = coverage[(row * width + col) as usize];
+                        }
+                    }
+
+                    Glyph {
+                        uv: Rect::new(
+                            gx as f32 / atlas_width as f32,
+                            gy as f32 / atlas_height as f32,
+                            width as f32 / atlas_width as f32,
+                            height as f32 / atlas_height as f32,
+                        ),
+                        offset: offset,
+                        size: [width as f32, height as f32],
+                        advance: advance,
+                    }
+                }
+                None => {
+                    Glyph {
+                        uv: Rect::new(0.0, 0.0, 0.0, 0.0),
+                        offset: [0.0, 0.0],
+                        size: [0.0, 0.0],
+                        advance: advance,
+                    }
+                }
+            };
+
+            glyphs.insert(c, glyph);
+        }
+
+        let mut kerning = HashMap::new();
+
+        for a in chars.iter().cloned() {
+            for b in chars.iter().cloned() {
+                let amount = font.pair_kerning(scale, a, b);
+
+                if amount != 0.0 {
+                    kerning.insert((a, b), amount);
+                }
+            }
+        }
+
+        let atlas = Texture::from_raw(image_data, (atlas_width, atlas_height));
+
+        Ok(Font::new(
+            atlas,
+            glyphs,
+            kerning,
+            v_metrics.ascent,
+            v_metrics.ascent - v_metrics.descent + v_metrics.line_gap,
+        ))
+    }
+
+    /// Register the glyph atlas with the graphics subsystem, so that text can be drawn.
+    pub fn register(&mut self, gfx: &Gfx) -> Result<()> {
+        self.texture = Some(gfx.register_texture(self.atlas.clone())?);
+        Ok(())
+    }
+
+    /// Draw text with its top-left corner at the given position.
+    pub fn draw(
+        &self,
+        overlay: &mut Overlay,
+        x: f32,
+        y: f32,
+        text: &str,
+        options: &TextOptions,
+    ) -> Result<()> {
+        let texture = self.texture.ok_or(ErrorKind::FontNotRegistered)?;
+        let layout = layout::layout(self, text, options);
+
+        for glyph in layout.glyphs {
+            let mut rect = glyph.rect;
+            rect.x += x;
+            rect.y += y;
+            overlay.quad(rect, texture, glyph.uv, options.color);
+        }
+
+        Ok(())
+    }
+}
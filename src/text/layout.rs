@@ -0,0 +1,253 @@
+use super::font::{Font, Glyph};
+use gfx::color::Color;
+use gfx::overlay::Rect;
+use std::mem;
+
+/// Horizontal alignment of lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Options for how text is laid out and drawn.
+#[derive(Debug, Clone, Copy)]
+pub struct TextOptions {
+    pub color: Color,
+    /// Wrap lines at word boundaries to fit inside of this width.
+    pub max_width: Option<f32>,
+    /// Lines are aligned within `max_width`, or within the widest line if not set.
+    pub align: Align,
+}
+
+impl Default for TextOptions {
+    fn default() -> TextOptions {
+        TextOptions {
+            color: Color::from_rgb(1.0, 1.0, 1.0),
+            max_width: None,
+            align: Align::Left,
+        }
+    }
+}
+
+/// A glyph positioned relative to the top-left corner of the text.
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedGlyph {
+    pub rect: Rect,
+    pub uv: Rect,
+}
+
+/// The result of laying out a piece of text.
+#[derive(Debug)]
+pub struct TextLayout {
+    pub glyphs: Vec<PlacedGlyph>,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Glyph drawn for a character, falling back to `'?'` for characters missing from the font.
+fn glyph_for(font: &Font, c: char) -> Option<&Glyph> {
+    font.glyphs.get(&c).or_else(|| font.glyphs.get(&'?'))
+}
+
+/// Measure the width of a single line.
+pub fn line_width(font: &Font, line: &[char]) -> f32 {
+    let mut width = 0f32;
+    let mut previous = None;
+
+    for c in line.iter().cloned() {
+        let glyph = match glyph_for(font, c) {
+            Some(glyph) => glyph,
+            None => continue,
+        };
+
+        if let Some(p) = previous {
+            width += font.kerning.get(&(p, c)).cloned().unwrap_or(0.0);
+        }
+
+        width += glyph.advance;
+        previous = Some(c);
+    }
+
+    width
+}
+
+/// Split text into lines, wrapping at spaces to fit inside of `max_width`.
+///
+/// Words which are wider than `max_width` on their own are kept on a single line.
+pub fn wrap(font: &Font, text: &str, max_width: Option<f32>) -> Vec<Vec<char>> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let mut current: Vec<char> = Vec::new();
+
+        for word in paragraph.split(' ') {
+            let mut candidate = current.clone();
+
+            if !current.is_empty() {
+                candidate.push(' ');
+            }
+
+            candidate.extend(word.chars());
+
+            let fits = max_width.map(|m| line_width(font, &candidate) <= m).unwrap_or(
+                true,
+            );
+
+            if current.is_empty() || fits {
+                current = candidate;
+            } else {
+                lines.push(mem::replace(&mut current, word.chars().collect()));
+            }
+        }
+
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Lay out text, with the top-left corner of the first line at the origin.
+pub fn layout(font: &Font, text: &str, options: &TextOptions) -> TextLayout {
+    let lines = wrap(font, text, options.max_width);
+    let widths: Vec<f32> = lines.iter().map(|l| line_width(font, l)).collect();
+
+    let width = options.max_width.unwrap_or_else(|| {
+        widths.iter().cloned().fold(0f32, f32::max)
+    });
+
+    let mut glyphs = Vec::new();
+
+    for (index, (line, line_width)) in lines.iter().zip(widths.iter()).enumerate() {
+        let mut pen = match options.align {
+            Align::Left => 0.0,
+            Align::Center => (width - line_width) / 2.0,
+            Align::Right => width - line_width,
+        };
+
+        let baseline = index as f32 * font.line_height + font.ascent;
+        let mut previous = None;
+
+        for c in line.iter().cloned() {
+            let glyph = match glyph_for(font, c) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            if let Some(p) = previous {
+                pen += font.kerning.get(&(p, c)).cloned().unwrap_or(0.0);
+            }
+
+            if glyph.size[0] > 0.0 && glyph.size[1] > 0.0 {
+                glyphs.push(PlacedGlyph {
+                    rect: Rect::new(
+                        pen + glyph.offset[0],
+                        baseline + glyph.offset[1],
+                        glyph.size[0],
+                        glyph.size[1],
+                    ),
+                    uv: glyph.uv,
+                });
+            }
+
+            pen += glyph.advance;
+            previous = Some(c);
+        }
+    }
+
+    TextLayout {
+        glyphs: glyphs,
+        width: width,
+        height: lines.len() as f32 * font.line_height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use texture::Texture;
+
+    /// Monospaced font where every glyph is 10x10 pixels.
+    fn test_font() -> Font {
+        let mut glyphs = HashMap::new();
+
+        for c in (32u8..127u8).map(|c| c as char) {
+            glyphs.insert(
+                c,
+                Glyph {
+                    uv: Rect::new(0.0, 0.0, 1.0, 1.0),
+                    offset: [0.0, -10.0],
+                    size: [10.0, 10.0],
+                    advance: 10.0,
+                },
+            );
+        }
+
+        let mut kerning = HashMap::new();
+        kerning.insert(('A', 'V'), -2.0);
+
+        Font::new(
+            Texture::from_raw(vec![255; 4], (1, 1)),
+            glyphs,
+            kerning,
+            10.0,
+            12.0,
+        )
+    }
+
+    fn lines(lines: Vec<Vec<char>>) -> Vec<String> {
+        lines.into_iter().map(|l| l.into_iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_line_width_with_kerning() {
+        let font = test_font();
+        assert_eq!(18.0, line_width(&font, &['A', 'V']));
+        assert_eq!(20.0, line_width(&font, &['V', 'A']));
+    }
+
+    #[test]
+    fn test_line_width_with_fallback() {
+        let font = test_font();
+        assert_eq!(20.0, line_width(&font, &['a', '\u{e9}']));
+
+        let layout = layout(&font, "\u{e9}\u{e9}", &TextOptions::default());
+        assert_eq!(20.0, layout.width);
+        assert_eq!(2, layout.glyphs.len());
+    }
+
+    #[test]
+    fn test_wrap() {
+        let font = test_font();
+        assert_eq!(
+            vec!["aa bb", "cc"],
+            lines(wrap(&font, "aa bb cc", Some(50.0)))
+        );
+        assert_eq!(
+            vec!["aaaaaaaa", "b"],
+            lines(wrap(&font, "aaaaaaaa b", Some(50.0)))
+        );
+        assert_eq!(vec!["aa", "bb cc"], lines(wrap(&font, "aa\nbb cc", None)));
+    }
+
+    #[test]
+    fn test_layout_alignment() {
+        let font = test_font();
+
+        let options = TextOptions {
+            max_width: Some(40.0),
+            align: Align::Center,
+            ..TextOptions::default()
+        };
+
+        let layout = layout(&font, "ab\nabcd", &options);
+        let xs: Vec<f32> = layout.glyphs.iter().map(|g| g.rect.x).collect();
+        let ys: Vec<f32> = layout.glyphs.iter().map(|g| g.rect.y).collect();
+
+        assert_eq!(vec![10.0, 20.0, 0.0, 10.0, 20.0, 30.0], xs);
+        assert_eq!(vec![0.0, 0.0, 12.0, 12.0, 12.0, 12.0], ys);
+        assert_eq!(24.0, layout.height);
+    }
+}
@@ -0,0 +1,10 @@
+//! # Text rendering on top of the 2D overlay
+//!
+//! TrueType fonts are rasterized into a glyph atlas at a fixed pixel size when loaded.
+
+pub mod errors;
+pub mod font;
+pub mod layout;
+
+pub use self::font::{Font, Glyph};
+pub use self::layout::{Align, TextOptions};
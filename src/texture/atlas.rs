@@ -0,0 +1,345 @@
+//! # Texture atlases
+//!
+//! Many small textures, like sprites or UI icons, are packed into one larger texture so they can
+//! share a single image and descriptor set. Textures are packed onto shelves, tallest first, and
+//! the atlas grows in powers of two until everything fits.
+
+use super::errors::*;
+use super::sampler::{Sampler, Wrap};
+use super::texture::{ColorSpace, Texture};
+use gfx::overlay::Rect;
+use gfx::primitive::Primitive;
+use std::sync::Arc;
+
+/// Default limit for the width and height of an atlas, which all devices support.
+const DEFAULT_MAX_SIZE: u32 = 4096;
+
+/// Collects textures and packs them into an atlas.
+#[derive(Debug, Clone)]
+pub struct AtlasBuilder {
+    textures: Vec<Texture>,
+    padding: u32,
+    max_size: u32,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> AtlasBuilder {
+        AtlasBuilder::new()
+    }
+}
+
+impl AtlasBuilder {
+    pub fn new() -> AtlasBuilder {
+        AtlasBuilder {
+            textures: Vec::new(),
+            padding: 1,
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Texels around each texture, filled with its edge texels so filtering does not bleed
+    /// between neighbours.
+    pub fn padding(mut self, padding: u32) -> AtlasBuilder {
+        self.padding = padding;
+        self
+    }
+
+    /// Limit for the width and height of the atlas.
+    pub fn max_size(mut self, max_size: u32) -> AtlasBuilder {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Add a texture, returning its index in the atlas.
+    pub fn add(&mut self, texture: Texture) -> usize {
+        self.textures.push(texture);
+        self.textures.len() - 1
+    }
+
+    /// Pack all added textures into an atlas.
+    pub fn build(&self) -> Result<Atlas> {
+        let color_space = self.textures
+            .first()
+            .map(|t| t.color_space)
+            .unwrap_or(ColorSpace::Srgb);
+
+        if self.textures.iter().any(|t| t.color_space != color_space) {
+            return Err(ErrorKind::InvalidAtlas("mixed color spaces".to_string()).into());
+        }
+
+        let sizes: Vec<(u32, u32)> = self.textures
+            .iter()
+            .map(|t| {
+                (t.dimensions.0 + self.padding * 2, t.dimensions.1 + self.padding * 2)
+            })
+            .collect();
+
+        let (dimensions, positions) = match pack(&sizes, self.max_size) {
+            Some(packed) => packed,
+            None => {
+                let reason = format!("textures do not fit into {0}x{0} texels", self.max_size);
+                return Err(ErrorKind::InvalidAtlas(reason).into());
+            }
+        };
+
+        let (width, height) = dimensions;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+        let mut rects = Vec::with_capacity(self.textures.len());
+
+        for (texture, &(x, y)) in self.textures.iter().zip(positions.iter()) {
+            let (x, y) = (x + self.padding, y + self.padding);
+            blit(texture, &mut image_data, width, (x, y), self.padding);
+
+            rects.push(Rect::new(
+                x as f32 / width as f32,
+                y as f32 / height as f32,
+                texture.dimensions.0 as f32 / width as f32,
+                texture.dimensions.1 as f32 / height as f32,
+            ));
+        }
+
+        let mut texture = Texture::from_raw(image_data, dimensions);
+        texture.color_space = color_space;
+
+        // neighbouring textures would show up when wrapping, and in smaller mip levels, where
+        // the padding shrinks below a texel.
+        texture.sampler = Sampler {
+            mipmaps: false,
+            wrap_s: Wrap::ClampToEdge,
+            wrap_t: Wrap::ClampToEdge,
+            ..Sampler::default()
+        };
+
+        Ok(Atlas {
+            texture: Arc::new(texture),
+            rects: rects,
+        })
+    }
+}
+
+/// Textures packed into a single texture.
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    pub texture: Arc<Texture>,
+    /// Texture coordinates of each packed texture, in the order they were added.
+    pub rects: Vec<Rect>,
+}
+
+impl Atlas {
+    /// Map texture coordinates of a packed texture to the atlas.
+    ///
+    /// Coordinates are clamped to [0, 1], since repeating textures can not be packed.
+    pub fn map(&self, index: usize, tex_coord: [f32; 2]) -> [f32; 2] {
+        let rect = &self.rects[index];
+
+        [
+            rect.x + clamp(tex_coord[0]) * rect.width,
+            rect.y + clamp(tex_coord[1]) * rect.height,
+        ]
+    }
+
+    /// Make a primitive textured with a packed texture use the atlas instead.
+    pub fn remap(&self, index: usize, primitive: &mut Primitive) {
+        for vertex in &mut primitive.vertices {
+            vertex.tex_coord = self.map(index, vertex.tex_coord);
+        }
+
+        primitive.base_color_texture = Some(self.texture.clone());
+    }
+}
+
+fn clamp(value: f32) -> f32 {
+    f32::min(f32::max(value, 0.0), 1.0)
+}
+
+/// Copy a texture into the atlas, extending its edge texels into the padding around it.
+fn blit(texture: &Texture, image_data: &mut [u8], width: u32, position: (u32, u32), padding: u32) {
+    let (w, h) = texture.dimensions;
+
+    if w == 0 || h == 0 {
+        return;
+    }
+
+    let padding = padding as i64;
+
+    for y in -padding..h as i64 + padding {
+        for x in -padding..w as i64 + padding {
+            let sx = i64::min(i64::max(x, 0), w as i64 - 1) as u32;
+            let sy = i64::min(i64::max(y, 0), h as i64 - 1) as u32;
+            let source = ((sy * w + sx) * 4) as usize;
+
+            let tx = (position.0 as i64 + x) as u32;
+            let ty = (position.1 as i64 + y) as u32;
+            let target = ((ty * width + tx) * 4) as usize;
+
+            image_data[target..target + 4].copy_from_slice(&texture.image_data[source..source + 4]);
+        }
+    }
+}
+
+/// Pack rectangles of the given sizes, returning the size of the atlas and the position of each
+/// rectangle, or `None` if they do not fit into `max_size` texels in either direction.
+pub fn pack(sizes: &[(u32, u32)], max_size: u32) -> Option<((u32, u32), Vec<(u32, u32)>)> {
+    let area: u64 = sizes.iter().map(|&(w, h)| w as u64 * h as u64).sum();
+    let max_width = sizes.iter().map(|s| s.0).max().unwrap_or(1);
+    let max_height = sizes.iter().map(|s| s.1).max().unwrap_or(1);
+
+    // start with the smallest square that could hold all rectangles.
+    let side = ((area as f64).sqrt().ceil() as u32).next_power_of_two();
+    let mut width = u32::max(side, max_width.next_power_of_two());
+    let mut height = u32::max(side, max_height.next_power_of_two());
+
+    // tallest rectangles first, so shelves waste little space.
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| (!sizes[i].1, !sizes[i].0));
+
+    while width <= max_size && height <= max_size {
+        if let Some(positions) = pack_shelves(sizes, &order, (width, height)) {
+            return Some(((width, height), positions));
+        }
+
+        if width <= height {
+            width *= 2;
+        } else {
+            height *= 2;
+        }
+    }
+
+    None
+}
+
+fn pack_shelves(
+    sizes: &[(u32, u32)],
+    order: &[usize],
+    dimensions: (u32, u32),
+) -> Option<Vec<(u32, u32)>> {
+    let (width, height) = dimensions;
+    let mut positions = vec![(0, 0); sizes.len()];
+
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+
+    for &i in order {
+        let (w, h) = sizes[i];
+
+        if x + w > width {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+
+        if w > width || y + h > height {
+            return None;
+        }
+
+        positions[i] = (x, y);
+        x += w;
+        shelf_height = u32::max(shelf_height, h);
+    }
+
+    Some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gfx::Vertex;
+
+    fn overlaps(a: ((u32, u32), (u32, u32)), b: ((u32, u32), (u32, u32))) -> bool {
+        let (((ax, ay), (aw, ah)), ((bx, by), (bw, bh))) = (a, b);
+        ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah
+    }
+
+    #[test]
+    fn test_pack() {
+        let sizes = vec![(16, 16), (8, 32), (32, 8), (8, 8), (8, 8), (20, 4)];
+        let ((width, height), positions) = pack(&sizes, 256).unwrap();
+
+        for (i, (&(x, y), &(w, h))) in positions.iter().zip(sizes.iter()).enumerate() {
+            assert!(x + w <= width && y + h <= height);
+
+            for (&other, &size) in positions.iter().zip(sizes.iter()).take(i) {
+                assert!(!overlaps(((x, y), (w, h)), (other, size)));
+            }
+        }
+
+        assert_eq!((64, 64), (width, height));
+    }
+
+    #[test]
+    fn test_pack_too_large() {
+        assert_eq!(None, pack(&[(300, 10)], 256));
+        assert_eq!(None, pack(&[(200, 200), (200, 200)], 256));
+    }
+
+    #[test]
+    fn test_pack_empty() {
+        assert_eq!(Some(((1, 1), vec![])), pack(&[], 256));
+    }
+
+    #[test]
+    fn test_build() {
+        let mut builder = AtlasBuilder::new().padding(1);
+        let red = builder.add(Texture::from_raw(vec![255, 0, 0, 255], (1, 1)));
+        let green_texels = [0, 255, 0, 255].iter().cycle().take(2 * 2 * 4).cloned().collect();
+        let green = builder.add(Texture::from_raw(green_texels, (2, 2)));
+
+        let atlas = builder.build().unwrap();
+        assert_eq!((8, 8), atlas.texture.dimensions);
+
+        // the larger texture is packed first, and padding repeats the edges.
+        assert_eq!(Rect::new(0.125, 0.125, 0.25, 0.25), atlas.rects[green]);
+        assert_eq!(Rect::new(0.625, 0.125, 0.125, 0.125), atlas.rects[red]);
+
+        let texel = |x: usize, y: usize| &atlas.texture.image_data[(y * 8 + x) * 4..][..4];
+        assert_eq!(&[0, 255, 0, 255], texel(0, 0));
+        assert_eq!(&[255, 0, 0, 255], texel(4, 0));
+        assert_eq!(&[255, 0, 0, 255], texel(5, 1));
+        assert_eq!(&[0, 0, 0, 0], texel(7, 0));
+
+        assert!(!atlas.texture.sampler.mipmaps);
+        assert_eq!(Wrap::ClampToEdge, atlas.texture.sampler.wrap_s);
+    }
+
+    #[test]
+    fn test_mixed_color_spaces() {
+        let mut linear = Texture::from_raw(vec![0; 4], (1, 1));
+        linear.color_space = ColorSpace::Linear;
+
+        let mut builder = AtlasBuilder::new();
+        builder.add(Texture::from_raw(vec![0; 4], (1, 1)));
+        builder.add(linear);
+
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_remap() {
+        let mut builder = AtlasBuilder::new().padding(0);
+        builder.add(Texture::from_raw(vec![0; 2 * 2 * 4], (2, 2)));
+        let index = builder.add(Texture::from_raw(vec![0; 2 * 2 * 4], (2, 2)));
+        let atlas = builder.build().unwrap();
+
+        let vertex = |tex_coord: [f32; 2]| {
+            Vertex {
+                position: [0.0; 3],
+                normal: [0.0; 3],
+                tex_coord: tex_coord,
+                joints: [0.0; 4],
+                weights: [0.0; 4],
+            }
+        };
+
+        let mut primitive = Primitive::new(
+            vec![vertex([0.0, 0.0]), vertex([1.0, 0.5]), vertex([2.0, -1.0])],
+            vec![0, 1, 2],
+            [1.0; 4].into(),
+            None,
+        );
+
+        atlas.remap(index, &mut primitive);
+
+        let tex_coords: Vec<_> = primitive.vertices.iter().map(|v| v.tex_coord).collect();
+        assert_eq!(vec![[0.5, 0.0], [1.0, 0.25], [1.0, 0.0]], tex_coords);
+        assert!(Arc::ptr_eq(&atlas.texture, primitive.base_color_texture.as_ref().unwrap()));
+    }
+}
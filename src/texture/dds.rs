@@ -0,0 +1,201 @@
+//! # DirectDraw Surface images
+//!
+//! Only the first mip level of a 2D image is read, mipmaps are generated when uploading.
+
+use super::errors::*;
+use super::format::DDS_MAGIC;
+use super::pixels::{self, PixelFormat};
+use super::texture::{ColorSpace, Texture};
+use bytes::read_u32;
+
+/// Size of the magic number and the header.
+const HEADER_LENGTH: usize = 128;
+/// Size of the extended header, present for the `DX10` four character code.
+const DX10_HEADER_LENGTH: usize = 20;
+
+/// Block compressed formats by their four character code.
+const FOUR_CCS: [(&'static [u8], PixelFormat); 5] = [
+    (b"DXT1", PixelFormat::Bc1),
+    (b"DXT2", PixelFormat::Bc2),
+    (b"DXT3", PixelFormat::Bc2),
+    (b"DXT4", PixelFormat::Bc3),
+    (b"DXT5", PixelFormat::Bc3),
+];
+
+/// Pixel format flag for four character codes.
+const DDPF_FOURCC: u32 = 0x4;
+/// Pixel format flag for uncompressed RGB data.
+const DDPF_RGB: u32 = 0x40;
+
+/// Decode a DDS image.
+///
+/// Only the DX10 header tells sRGB and linear data apart, other images are taken to be sRGB.
+pub fn load(bytes: &[u8]) -> Result<Texture> {
+    if bytes.len() < HEADER_LENGTH || !bytes.starts_with(DDS_MAGIC) {
+        return Err(ErrorKind::InvalidImage("not a DDS file".to_string()).into());
+    }
+
+    let height = read_u32(bytes, 12);
+    let width = read_u32(bytes, 16);
+
+    if width == 0 || height == 0 {
+        return Err(ErrorKind::InvalidImage("image has no pixels".to_string()).into());
+    }
+
+    let flags = read_u32(bytes, 80);
+    let four_cc = &bytes[84..88];
+
+    let mut color_space = ColorSpace::Srgb;
+
+    let (format, offset) = if flags & DDPF_FOURCC != 0 {
+        if four_cc == b"DX10" {
+            if bytes.len() < HEADER_LENGTH + DX10_HEADER_LENGTH {
+                return Err(ErrorKind::InvalidImage("truncated DX10 header".to_string()).into());
+            }
+
+            let (format, dxgi_color_space) = dxgi_format(read_u32(bytes, HEADER_LENGTH))?;
+            color_space = dxgi_color_space;
+            (format, HEADER_LENGTH + DX10_HEADER_LENGTH)
+        } else {
+            match FOUR_CCS.iter().find(|&&(code, _)| code == four_cc) {
+                Some(&(_, format)) => (format, HEADER_LENGTH),
+                None => {
+                    let four_cc = String::from_utf8_lossy(four_cc).into_owned();
+                    return Err(ErrorKind::UnsupportedPixelFormat(four_cc).into());
+                }
+            }
+        }
+    } else if flags & DDPF_RGB != 0 && read_u32(bytes, 88) == 32 {
+        // masks of the red and blue channels tell RGBA and BGRA apart.
+        match (read_u32(bytes, 92), read_u32(bytes, 100)) {
+            (0xff, 0xff_0000) => (PixelFormat::Rgba8, HEADER_LENGTH),
+            (0xff_0000, 0xff) => (PixelFormat::Bgra8, HEADER_LENGTH),
+            masks => {
+                let masks = format!("RGB masks {:?}", masks);
+                return Err(ErrorKind::UnsupportedPixelFormat(masks).into());
+            }
+        }
+    } else {
+        let format = format!("flags {:#x}", flags);
+        return Err(ErrorKind::UnsupportedPixelFormat(format).into());
+    };
+
+    let image_data = pixels::decode(format, &bytes[offset..], (width, height))?;
+    let mut texture = Texture::from_raw(image_data, (width, height));
+    texture.color_space = color_space;
+    Ok(texture)
+}
+
+/// Pixel format and color space of a `DXGI_FORMAT`, where each format has a UNORM and an SRGB
+/// variant.
+fn dxgi_format(format: u32) -> Result<(PixelFormat, ColorSpace)> {
+    let pixel_format = match format {
+        28 | 29 => PixelFormat::Rgba8,
+        87 | 91 => PixelFormat::Bgra8,
+        71 | 72 => PixelFormat::Bc1,
+        74 | 75 => PixelFormat::Bc2,
+        77 | 78 => PixelFormat::Bc3,
+        _ => {
+            let format = format!("DXGI format {}", format);
+            return Err(ErrorKind::UnsupportedPixelFormat(format).into());
+        }
+    };
+
+    let color_space = match format {
+        29 | 91 | 72 | 75 | 78 => ColorSpace::Srgb,
+        _ => ColorSpace::Linear,
+    };
+
+    Ok((pixel_format, color_space))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::write_u32;
+
+    fn header(dimensions: (u32, u32), flags: u32, four_cc: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_LENGTH];
+        bytes[..4].copy_from_slice(DDS_MAGIC);
+
+        write_u32(&mut bytes, 4, 124);
+        write_u32(&mut bytes, 12, dimensions.1);
+        write_u32(&mut bytes, 16, dimensions.0);
+        write_u32(&mut bytes, 76, 32);
+        write_u32(&mut bytes, 80, flags);
+
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes
+    }
+
+    #[test]
+    fn test_dxt1() {
+        let mut bytes = header((4, 4), DDPF_FOURCC, b"DXT1");
+        bytes.extend(&[0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0]);
+
+        let texture = load(&bytes).unwrap();
+        assert_eq!((4, 4), texture.dimensions);
+        assert_eq!(&[255, 0, 0, 255], &texture.image_data[60..]);
+    }
+
+    #[test]
+    fn test_dx10() {
+        let mut bytes = header((1, 1), DDPF_FOURCC, b"DX10");
+        bytes.extend(&[87, 0, 0, 0]);
+        bytes.extend(&[0; 16]);
+        bytes.extend(&[1, 2, 3, 4]);
+
+        assert_eq!(vec![3, 2, 1, 4], load(&bytes).unwrap().image_data);
+    }
+
+    #[test]
+    fn test_dx10_color_space() {
+        for &(dxgi, color_space) in &[(28, ColorSpace::Linear), (29, ColorSpace::Srgb)] {
+            let mut bytes = header((1, 1), DDPF_FOURCC, b"DX10");
+            bytes.extend(&[dxgi, 0, 0, 0]);
+            bytes.extend(&[0; 16]);
+            bytes.extend(&[1, 2, 3, 4]);
+
+            assert_eq!(color_space, load(&bytes).unwrap().color_space);
+        }
+    }
+
+    #[test]
+    fn test_dxt1_is_srgb() {
+        let mut bytes = header((4, 4), DDPF_FOURCC, b"DXT1");
+        bytes.extend(&[0; 8]);
+
+        assert_eq!(ColorSpace::Srgb, load(&bytes).unwrap().color_space);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let mut bytes = header((4, 4), DDPF_FOURCC, b"ATI2");
+        bytes.extend(&[0; 16]);
+
+        match *load(&bytes).unwrap_err().kind() {
+            ErrorKind::UnsupportedPixelFormat(ref format) => assert_eq!("ATI2", format),
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_zero_dimensions() {
+        for &dimensions in &[(0, 4), (4, 0)] {
+            let mut bytes = header(dimensions, DDPF_FOURCC, b"DXT1");
+            bytes.extend(&[0; 8]);
+
+            match *load(&bytes).unwrap_err().kind() {
+                ErrorKind::InvalidImage(_) => {}
+                ref kind => panic!("unexpected error: {:?}", kind),
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = header((4, 4), DDPF_FOURCC, b"DXT5");
+        assert!(load(&bytes).is_err());
+        assert!(load(&bytes[..64]).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use super::format::{supported_extensions, supported_mime_types};
+
 error_chain!{
     foreign_links {
         ImageError(::image::ImageError);
@@ -7,12 +9,37 @@ error_chain!{
     errors {
         UnsupportedMimeType(mime_type: String) {
             description("unsupported mime type")
-            display("Unsupported mime type: {}", mime_type)
+            display("unsupported mime type: {} (supported: {})", mime_type, supported_mime_types())
         }
 
         UnsupportedExtension(ext: Option<String>) {
             description("unsupported extension")
-            display("unsupported extension: {:?}", ext)
+            display("unsupported extension: {:?} (supported: {})", ext, supported_extensions())
+        }
+
+        UnknownFormat {
+            description("unknown image format")
+            display("unknown image format (supported: {})", supported_mime_types())
+        }
+
+        InvalidImage(reason: String) {
+            description("invalid image")
+            display("invalid image: {}", reason)
+        }
+
+        UnsupportedPixelFormat(format: String) {
+            description("unsupported pixel format")
+            display("unsupported pixel format: {} (supported: RGBA8, BGRA8, BC1-3)", format)
+        }
+
+        InvalidAtlas(reason: String) {
+            description("invalid atlas")
+            display("invalid atlas: {}", reason)
+        }
+
+        InvalidCubemap(reason: String) {
+            description("invalid cubemap")
+            display("invalid cubemap: {}", reason)
         }
     }
 }
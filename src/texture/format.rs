@@ -0,0 +1,200 @@
+//! # Image file formats
+//!
+//! Formats are detected from the leading bytes of a file where possible, falling back to the
+//! mime type or file extension for formats without a signature, like TGA.
+
+use super::errors::*;
+use image;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Signature of a KTX 2.0 container.
+pub const KTX2_MAGIC: &'static [u8] = b"\xABKTX 20\xBB\r\n\x1A\n";
+
+/// Signature of a DirectDraw Surface.
+pub const DDS_MAGIC: &'static [u8] = b"DDS ";
+
+const PNG_MAGIC: &'static [u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_MAGIC: &'static [u8] = b"\xFF\xD8\xFF";
+const BMP_MAGIC: &'static [u8] = b"BM";
+
+/// All supported formats, in the order they are listed in errors.
+pub const ALL: [ImageFormat; 6] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::Tga,
+    ImageFormat::Bmp,
+    ImageFormat::Ktx2,
+    ImageFormat::Dds,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Tga,
+    Bmp,
+    /// KTX 2.0 container, with uncompressed or BC1-3 compressed texels.
+    Ktx2,
+    /// DirectDraw Surface, with uncompressed or BC1-3 compressed texels.
+    Dds,
+}
+
+impl ImageFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match *self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Tga => "image/x-tga",
+            ImageFormat::Bmp => "image/bmp",
+            ImageFormat::Ktx2 => "image/ktx2",
+            ImageFormat::Dds => "image/vnd-ms.dds",
+        }
+    }
+
+    /// File extensions of the format, the first one being the canonical one.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match *self {
+            ImageFormat::Png => &["png"],
+            ImageFormat::Jpeg => &["jpg", "jpeg"],
+            ImageFormat::Tga => &["tga"],
+            ImageFormat::Bmp => &["bmp"],
+            ImageFormat::Ktx2 => &["ktx2"],
+            ImageFormat::Dds => &["dds"],
+        }
+    }
+
+    pub fn from_mime_type(mime_type: &str) -> Result<ImageFormat> {
+        let mime_type = mime_type.trim().to_lowercase();
+
+        // common aliases, which some exporters write instead of the registered names.
+        let format = match mime_type.as_ref() {
+            "image/jpg" | "image/pjpeg" => Some(ImageFormat::Jpeg),
+            "image/tga" | "image/x-targa" | "image/targa" => Some(ImageFormat::Tga),
+            "image/x-bmp" | "image/x-ms-bmp" => Some(ImageFormat::Bmp),
+            "image/x-dds" | "image/dds" => Some(ImageFormat::Dds),
+            _ => ALL.iter().find(|f| f.mime_type() == mime_type).cloned(),
+        };
+
+        match format {
+            Some(format) => Ok(format),
+            None if mime_type.is_empty() => Err(ErrorKind::UnknownFormat.into()),
+            None => Err(ErrorKind::UnsupportedMimeType(mime_type).into()),
+        }
+    }
+
+    pub fn from_extension(path: &Path) -> Result<ImageFormat> {
+        let ext = path.extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_lowercase);
+
+        let format = ext.as_ref().and_then(|ext| {
+            ALL.iter()
+                .find(|f| f.extensions().contains(&ext.as_str()))
+                .cloned()
+        });
+
+        match format {
+            Some(format) => Ok(format),
+            None => Err(ErrorKind::UnsupportedExtension(ext).into()),
+        }
+    }
+
+    /// Detect the format from the leading bytes of an image.
+    ///
+    /// TGA files have no signature and are never detected.
+    pub fn from_magic(bytes: &[u8]) -> Option<ImageFormat> {
+        if bytes.starts_with(PNG_MAGIC) {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(JPEG_MAGIC) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(KTX2_MAGIC) {
+            Some(ImageFormat::Ktx2)
+        } else if bytes.starts_with(DDS_MAGIC) {
+            Some(ImageFormat::Dds)
+        } else if bytes.starts_with(BMP_MAGIC) {
+            Some(ImageFormat::Bmp)
+        } else {
+            None
+        }
+    }
+
+    /// Format to decode with the image crate, for formats it supports.
+    pub(crate) fn image_format(&self) -> Option<image::ImageFormat> {
+        match *self {
+            ImageFormat::Png => Some(image::ImageFormat::PNG),
+            ImageFormat::Jpeg => Some(image::ImageFormat::JPEG),
+            ImageFormat::Tga => Some(image::ImageFormat::TGA),
+            ImageFormat::Bmp => Some(image::ImageFormat::BMP),
+            ImageFormat::Ktx2 | ImageFormat::Dds => None,
+        }
+    }
+}
+
+/// Comma separated list of supported mime types, for error messages.
+pub fn supported_mime_types() -> String {
+    ALL.iter()
+        .map(|f| f.mime_type())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Comma separated list of supported file extensions, for error messages.
+pub fn supported_extensions() -> String {
+    ALL.iter()
+        .flat_map(|f| f.extensions().iter().cloned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_mime_type() {
+        assert_eq!(ImageFormat::Png, ImageFormat::from_mime_type("image/png").unwrap());
+        assert_eq!(ImageFormat::Jpeg, ImageFormat::from_mime_type("image/jpeg").unwrap());
+        assert_eq!(ImageFormat::Jpeg, ImageFormat::from_mime_type("Image/JPG").unwrap());
+        assert_eq!(ImageFormat::Ktx2, ImageFormat::from_mime_type("image/ktx2").unwrap());
+
+        match *ImageFormat::from_mime_type("image/webp").unwrap_err().kind() {
+            ErrorKind::UnsupportedMimeType(ref mime_type) => assert_eq!("image/webp", mime_type),
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        match *ImageFormat::from_mime_type("").unwrap_err().kind() {
+            ErrorKind::UnknownFormat => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_from_extension() {
+        let format = |path: &str| ImageFormat::from_extension(Path::new(path)).ok();
+
+        assert_eq!(Some(ImageFormat::Png), format("a/b.png"));
+        assert_eq!(Some(ImageFormat::Jpeg), format("b.JPEG"));
+        assert_eq!(Some(ImageFormat::Tga), format("c.tga"));
+        assert_eq!(Some(ImageFormat::Dds), format("d.dds"));
+        assert_eq!(None, format("e.gif"));
+        assert_eq!(None, format("f"));
+    }
+
+    #[test]
+    fn test_from_magic() {
+        assert_eq!(Some(ImageFormat::Png), ImageFormat::from_magic(b"\x89PNG\r\n\x1a\n\0\0"));
+        assert_eq!(Some(ImageFormat::Jpeg), ImageFormat::from_magic(b"\xFF\xD8\xFF\xE0"));
+        assert_eq!(Some(ImageFormat::Bmp), ImageFormat::from_magic(b"BM\0\0"));
+        assert_eq!(Some(ImageFormat::Dds), ImageFormat::from_magic(b"DDS \x7C\0\0\0"));
+        assert_eq!(Some(ImageFormat::Ktx2), ImageFormat::from_magic(KTX2_MAGIC));
+        assert_eq!(None, ImageFormat::from_magic(b"\0\0\x02\0"));
+        assert_eq!(None, ImageFormat::from_magic(b""));
+    }
+
+    #[test]
+    fn test_supported() {
+        assert!(supported_mime_types().starts_with("image/png, image/jpeg"));
+        assert_eq!("png, jpg, jpeg, tga, bmp, ktx2, dds", supported_extensions());
+    }
+}
@@ -0,0 +1,158 @@
+//! # KTX 2.0 images
+//!
+//! Only the first layer and face of the first mip level is read. Supercompressed files, like
+//! Basis Universal textures, are not supported.
+
+use super::errors::*;
+use super::format::KTX2_MAGIC;
+use super::pixels::{self, PixelFormat};
+use super::texture::{ColorSpace, Texture};
+use bytes::{read_u32, read_u64};
+
+/// Size of the identifier, the header and the index, up to the level index.
+const HEADER_LENGTH: usize = 80;
+/// Size of an entry in the level index.
+const LEVEL_LENGTH: usize = 24;
+
+/// Decode a KTX 2.0 image.
+pub fn load(bytes: &[u8]) -> Result<Texture> {
+    if bytes.len() < HEADER_LENGTH + LEVEL_LENGTH || !bytes.starts_with(KTX2_MAGIC) {
+        return Err(ErrorKind::InvalidImage("not a KTX2 file".to_string()).into());
+    }
+
+    let vk_format = read_u32(bytes, 12);
+    let width = read_u32(bytes, 20);
+    let height = read_u32(bytes, 24);
+    let depth = read_u32(bytes, 28);
+    let supercompression = read_u32(bytes, 44);
+
+    // 1D textures have a height of 0.
+    if width == 0 || height == 0 {
+        return Err(ErrorKind::InvalidImage("image has no pixels".to_string()).into());
+    }
+
+    if depth > 1 {
+        return Err(ErrorKind::InvalidImage("3D textures are not supported".to_string()).into());
+    }
+
+    if supercompression != 0 {
+        let scheme = format!("supercompression scheme {}", supercompression);
+        return Err(ErrorKind::UnsupportedPixelFormat(scheme).into());
+    }
+
+    let (format, color_space) = vk_format_to_pixel_format(vk_format)?;
+
+    // the level index starts with the largest level.
+    let offset = read_u64(bytes, HEADER_LENGTH) as usize;
+    let length = read_u64(bytes, HEADER_LENGTH + 8) as usize;
+
+    let level = match bytes.get(offset..offset.saturating_add(length)) {
+        Some(level) => level,
+        None => return Err(ErrorKind::InvalidImage("level out of bounds".to_string()).into()),
+    };
+
+    let image_data = pixels::decode(format, level, (width, height))?;
+    let mut texture = Texture::from_raw(image_data, (width, height));
+    texture.color_space = color_space;
+    Ok(texture)
+}
+
+/// Pixel format and color space of a `VkFormat`, where each format has a UNORM and an SRGB
+/// variant.
+fn vk_format_to_pixel_format(vk_format: u32) -> Result<(PixelFormat, ColorSpace)> {
+    let format = match vk_format {
+        37 | 43 => PixelFormat::Rgba8,
+        44 | 50 => PixelFormat::Bgra8,
+        131...134 => PixelFormat::Bc1,
+        135 | 136 => PixelFormat::Bc2,
+        137 | 138 => PixelFormat::Bc3,
+        _ => {
+            let format = format!("VkFormat {}", vk_format);
+            return Err(ErrorKind::UnsupportedPixelFormat(format).into());
+        }
+    };
+
+    let color_space = match vk_format {
+        43 | 50 | 132 | 134 | 136 | 138 => ColorSpace::Srgb,
+        _ => ColorSpace::Linear,
+    };
+
+    Ok((format, color_space))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::write_u32;
+
+    fn build(vk_format: u32, dimensions: (u32, u32), level: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_LENGTH + LEVEL_LENGTH];
+        bytes[..12].copy_from_slice(KTX2_MAGIC);
+
+        let offset = bytes.len() as u32;
+        write_u32(&mut bytes, 12, vk_format);
+        write_u32(&mut bytes, 20, dimensions.0);
+        write_u32(&mut bytes, 24, dimensions.1);
+        write_u32(&mut bytes, 40, 1);
+        write_u32(&mut bytes, HEADER_LENGTH, offset);
+        write_u32(&mut bytes, HEADER_LENGTH + 8, level.len() as u32);
+
+        bytes.extend(level);
+        bytes
+    }
+
+    #[test]
+    fn test_rgba() {
+        let texture = load(&build(37, (2, 1), &[1, 2, 3, 4, 5, 6, 7, 8])).unwrap();
+
+        assert_eq!((2, 1), texture.dimensions);
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], texture.image_data);
+    }
+
+    #[test]
+    fn test_color_space() {
+        assert_eq!(ColorSpace::Linear, load(&build(37, (1, 1), &[0; 4])).unwrap().color_space);
+        assert_eq!(ColorSpace::Srgb, load(&build(43, (1, 1), &[0; 4])).unwrap().color_space);
+
+        let block = [0; 8];
+        assert_eq!(ColorSpace::Linear, load(&build(133, (4, 4), &block)).unwrap().color_space);
+        assert_eq!(ColorSpace::Srgb, load(&build(134, (4, 4), &block)).unwrap().color_space);
+    }
+
+    #[test]
+    fn test_bc1() {
+        let texture = load(&build(131, (4, 4), &[0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0])).unwrap();
+        assert_eq!(&[255, 0, 0, 255], &texture.image_data[..4]);
+    }
+
+    #[test]
+    fn test_supercompressed() {
+        let mut bytes = build(37, (1, 1), &[0; 4]);
+        write_u32(&mut bytes, 44, 1);
+
+        match *load(&bytes).unwrap_err().kind() {
+            ErrorKind::UnsupportedPixelFormat(ref format) => {
+                assert_eq!("supercompression scheme 1", format)
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_zero_dimensions() {
+        for &dimensions in &[(0, 1), (1, 0)] {
+            match *load(&build(37, dimensions, &[0; 4])).unwrap_err().kind() {
+                ErrorKind::InvalidImage(_) => {}
+                ref kind => panic!("unexpected error: {:?}", kind),
+            }
+        }
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut bytes = build(37, (1, 1), &[0; 4]);
+        write_u32(&mut bytes, HEADER_LENGTH + 8, 5);
+
+        assert!(load(&bytes).is_err());
+    }
+}
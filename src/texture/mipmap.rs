@@ -0,0 +1,107 @@
+//! # Mipmap generation
+
+use super::texture::{ColorSpace, Texture};
+
+/// Number of mip levels in a full chain for the given dimensions.
+pub fn levels(dimensions: (u32, u32)) -> u32 {
+    let (width, height) = dimensions;
+    32 - u32::max(u32::max(width, height), 1).leading_zeros()
+}
+
+/// Downsample the texture to half its size, using a box filter.
+///
+/// sRGB colors are averaged as linear values and encoded again, so mip levels keep the
+/// brightness of the texture.
+pub fn downsample(texture: &Texture) -> Texture {
+    let (width, height) = texture.dimensions;
+    let new_width = u32::max(width / 2, 1);
+    let new_height = u32::max(height / 2, 1);
+
+    let mut image_data = Vec::with_capacity((new_width * new_height * 4) as usize);
+
+    // alpha is always stored linearly.
+    let color_space = |c: u32| if c < 3 {
+        texture.color_space
+    } else {
+        ColorSpace::Linear
+    };
+
+    let texel = |x: u32, y: u32, c: u32| {
+        let x = u32::min(x, width - 1);
+        let y = u32::min(y, height - 1);
+        color_space(c).decode(texture.image_data[((y * width + x) * 4 + c) as usize])
+    };
+
+    for y in 0..new_height {
+        for x in 0..new_width {
+            for c in 0..4 {
+                let sum = texel(x * 2, y * 2, c) + texel(x * 2 + 1, y * 2, c) +
+                    texel(x * 2, y * 2 + 1, c) + texel(x * 2 + 1, y * 2 + 1, c);
+
+                image_data.push(color_space(c).encode(sum / 4.0));
+            }
+        }
+    }
+
+    let mut out = Texture::from_raw(image_data, (new_width, new_height));
+    out.sampler = texture.sampler;
+    out.color_space = texture.color_space;
+    out
+}
+
+/// Generate the full mip chain for a texture, starting with the texture itself.
+pub fn chain(texture: &Texture) -> Vec<Texture> {
+    let mut out = vec![texture.clone()];
+
+    for _ in 1..levels(texture.dimensions) {
+        let next = downsample(&out[out.len() - 1]);
+        out.push(next);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(1, levels((1, 1)));
+        assert_eq!(10, levels((512, 512)));
+        assert_eq!(11, levels((1024, 3)));
+    }
+
+    #[test]
+    fn test_downsample() {
+        let mut texture = Texture::from_raw(
+            vec![0, 0, 0, 255, 255, 255, 255, 255, 100, 0, 0, 255, 0, 100, 0, 255],
+            (2, 2),
+        );
+        texture.color_space = ColorSpace::Linear;
+
+        let out = downsample(&texture);
+        assert_eq!((1, 1), out.dimensions);
+        assert_eq!(vec![89, 89, 64, 255], out.image_data);
+    }
+
+    #[test]
+    fn test_downsample_srgb() {
+        let texture = Texture::from_raw(
+            vec![0, 0, 0, 255, 255, 255, 255, 255, 100, 0, 0, 0, 0, 100, 0, 0],
+            (2, 2),
+        );
+
+        // averaging the encoded values would darken the colors to 89, 89 and 64.
+        let out = downsample(&texture);
+        assert_eq!(vec![145, 145, 137, 128], out.image_data);
+        assert_eq!(ColorSpace::Srgb, out.color_space);
+    }
+
+    #[test]
+    fn test_chain() {
+        let texture = Texture::from_raw(vec![0; 8 * 2 * 4], (8, 2));
+        let dimensions: Vec<_> = chain(&texture).iter().map(|t| t.dimensions).collect();
+        assert_eq!(vec![(8, 2), (4, 1), (2, 1), (1, 1)], dimensions);
+    }
+}
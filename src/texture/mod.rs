@@ -1,58 +1,109 @@
 //! # Texture-handling utilities
 
+pub mod atlas;
 pub mod builtin;
+pub mod dds;
 pub mod texture;
 pub mod errors;
+pub mod format;
+pub mod ktx2;
+pub mod mipmap;
+pub mod pixels;
+pub mod sampler;
+pub mod skybox;
 
 use self::errors::*;
 
-pub use self::texture::Texture;
+pub use self::format::ImageFormat;
+pub use self::sampler::Sampler;
+pub use self::skybox::Skybox;
+pub use self::texture::{ColorSpace, Texture};
 use image;
-use std::ffi::OsStr;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::Read;
 use std::path::Path;
 
-const IMAGE_PNG: &'static str = "image/png";
-
-pub fn mime_type_from_file_ext(path: &Path) -> Result<&str> {
-    match path.extension().and_then(OsStr::to_str) {
-        Some("png") => Ok(IMAGE_PNG),
-        ext => Err(
-            ErrorKind::UnsupportedExtension(ext.map(String::from)).into(),
-        ),
-    }
+/// Mime type of the format the file extension of the path refers to.
+pub fn mime_type_from_file_ext(path: &Path) -> Result<&'static str> {
+    Ok(ImageFormat::from_extension(path)?.mime_type())
 }
 
+/// Load a texture from a file.
+///
+/// The format is detected from the contents of the file, falling back to the mime type or the
+/// file extension for formats without a signature.
 pub fn load_from_path<P: AsRef<Path>>(mime_type: Option<&str>, path: P) -> Result<Texture> {
     let path = path.as_ref();
 
-    let mime_type = if let Some(mime_type) = mime_type {
-        mime_type
-    } else {
-        mime_type_from_file_ext(path)?
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let format = match ImageFormat::from_magic(&bytes) {
+        Some(format) => format,
+        None => {
+            match mime_type {
+                Some(mime_type) => ImageFormat::from_mime_type(mime_type)?,
+                None => ImageFormat::from_extension(path)?,
+            }
+        }
+    };
+
+    decode(format, &bytes)
+}
+
+/// Load a texture from memory.
+///
+/// The format is detected from the contents, falling back to the mime type for formats without
+/// a signature. An empty mime type is accepted when the format can be detected.
+pub fn load_from_memory(mime_type: &str, bytes: &[u8]) -> Result<Texture> {
+    let format = match ImageFormat::from_magic(bytes) {
+        Some(format) => format,
+        None => ImageFormat::from_mime_type(mime_type)?,
     };
 
-    match mime_type.as_ref() {
-        IMAGE_PNG => {
-            let file = File::open(path)?;
-            let reader = BufReader::new(file);
-            let image = image::load(reader, image::ImageFormat::PNG)?.to_rgba();
+    decode(format, bytes)
+}
+
+/// Decode an image in the given format.
+pub fn decode(format: ImageFormat, bytes: &[u8]) -> Result<Texture> {
+    match format {
+        ImageFormat::Ktx2 => ktx2::load(bytes),
+        ImageFormat::Dds => dds::load(bytes),
+        _ => {
+            let image_format = format.image_format().expect("format decoded by the image crate");
+            let image = image::load_from_memory_with_format(bytes, image_format)?.to_rgba();
             let dimensions = image.dimensions();
             Ok(Texture::from_raw(image.into_raw(), dimensions))
         }
-        _ => Err(ErrorKind::UnsupportedMimeType(mime_type.to_string()).into()),
     }
 }
 
-pub fn load_from_memory(mime_type: &str, bytes: &[u8]) -> Result<Texture> {
-    match mime_type {
-        IMAGE_PNG => {
-            let image = image::load_from_memory_with_format(bytes, image::ImageFormat::PNG)?
-                .to_rgba();
-            let dimensions = image.dimensions();
-            Ok(Texture::from_raw(image.into_raw(), dimensions))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_from_memory() {
+        let texture = load_from_memory("", include_bytes!("debug_512x512.png")).unwrap();
+        assert_eq!((512, 512), texture.dimensions);
+
+        // the signature wins over a wrong mime type.
+        let texture = load_from_memory("image/jpeg", include_bytes!("debug_512x512.png")).unwrap();
+        assert_eq!((512, 512), texture.dimensions);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let error = load_from_memory("image/webp", b"RIFF").unwrap_err();
+        assert_eq!(
+            "unsupported mime type: image/webp (supported: image/png, image/jpeg, image/x-tga, \
+             image/bmp, image/ktx2, image/vnd-ms.dds)",
+            error.to_string()
+        );
+
+        match *load_from_memory("", b"RIFF").unwrap_err().kind() {
+            ErrorKind::UnknownFormat => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
         }
-        _ => Err(ErrorKind::UnsupportedMimeType(mime_type.to_string()).into()),
     }
 }
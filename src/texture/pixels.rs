@@ -0,0 +1,237 @@
+//! # Texel decoding for container formats
+//!
+//! KTX2 and DDS files store texels in a GPU format, which are decoded to RGBA here. Block
+//! compressed formats are decompressed on the CPU, since textures are always uploaded as RGBA.
+
+use super::errors::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba8,
+    Bgra8,
+    /// DXT1, 4x4 blocks of 8 bytes with optional 1-bit alpha.
+    Bc1,
+    /// DXT3, 4x4 blocks of 16 bytes with explicit 4-bit alpha.
+    Bc2,
+    /// DXT5, 4x4 blocks of 16 bytes with interpolated alpha.
+    Bc3,
+}
+
+impl PixelFormat {
+    /// Number of bytes needed for an image of the given dimensions.
+    pub fn byte_length(&self, dimensions: (u32, u32)) -> usize {
+        let (width, height) = (dimensions.0 as usize, dimensions.1 as usize);
+        let blocks = ((width + 3) / 4) * ((height + 3) / 4);
+
+        match *self {
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => width * height * 4,
+            PixelFormat::Bc1 => blocks * 8,
+            PixelFormat::Bc2 | PixelFormat::Bc3 => blocks * 16,
+        }
+    }
+}
+
+/// Decode the texels of an image to RGBA.
+pub fn decode(format: PixelFormat, data: &[u8], dimensions: (u32, u32)) -> Result<Vec<u8>> {
+    if data.len() < format.byte_length(dimensions) {
+        return Err(ErrorKind::InvalidImage("texel data too short".to_string()).into());
+    }
+
+    let out = match format {
+        PixelFormat::Rgba8 => data[..format.byte_length(dimensions)].to_vec(),
+        PixelFormat::Bgra8 => {
+            let mut out = data[..format.byte_length(dimensions)].to_vec();
+
+            for texel in out.chunks_mut(4) {
+                texel.swap(0, 2);
+            }
+
+            out
+        }
+        PixelFormat::Bc1 | PixelFormat::Bc2 | PixelFormat::Bc3 => {
+            decode_blocks(format, data, dimensions)
+        }
+    };
+
+    Ok(out)
+}
+
+fn decode_blocks(format: PixelFormat, data: &[u8], dimensions: (u32, u32)) -> Vec<u8> {
+    let (width, height) = (dimensions.0 as usize, dimensions.1 as usize);
+    let block_size = if format == PixelFormat::Bc1 { 8 } else { 16 };
+    let blocks_x = (width + 3) / 4;
+
+    let mut out = vec![0u8; width * height * 4];
+
+    for (i, block) in data.chunks(block_size)
+        .take(blocks_x * ((height + 3) / 4))
+        .enumerate()
+    {
+        let texels = match format {
+            PixelFormat::Bc1 => decode_color(block, true),
+            PixelFormat::Bc2 => {
+                let mut texels = decode_color(&block[8..], false);
+                decode_explicit_alpha(&block[..8], &mut texels);
+                texels
+            }
+            _ => {
+                let mut texels = decode_color(&block[8..], false);
+                decode_interpolated_alpha(&block[..8], &mut texels);
+                texels
+            }
+        };
+
+        let (block_x, block_y) = ((i % blocks_x) * 4, (i / blocks_x) * 4);
+
+        for (j, texel) in texels.iter().enumerate() {
+            let (x, y) = (block_x + j % 4, block_y + j / 4);
+
+            // blocks on the right and bottom edges may extend past the image.
+            if x < width && y < height {
+                let offset = (y * width + x) * 4;
+                out[offset..offset + 4].copy_from_slice(texel);
+            }
+        }
+    }
+
+    out
+}
+
+fn expand_565(color: u16) -> [u8; 4] {
+    let r = ((color >> 11) & 0x1f) as u8;
+    let g = ((color >> 5) & 0x3f) as u8;
+    let b = (color & 0x1f) as u8;
+
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2), 255]
+}
+
+fn mix(a: [u8; 4], b: [u8; 4], wa: u32, wb: u32) -> [u8; 4] {
+    let mut out = [255u8; 4];
+
+    for (c, (a, b)) in out.iter_mut().zip(a.iter().zip(b.iter())).take(3) {
+        *c = ((u32::from(*a) * wa + u32::from(*b) * wb) / (wa + wb)) as u8;
+    }
+
+    out
+}
+
+/// Decode the color part of a block, which with `alpha` may contain transparent texels.
+fn decode_color(block: &[u8], alpha: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from(block[0]) | (u16::from(block[1]) << 8);
+    let c1 = u16::from(block[2]) | (u16::from(block[3]) << 8);
+    let (e0, e1) = (expand_565(c0), expand_565(c1));
+
+    let palette = if c0 > c1 || !alpha {
+        [e0, e1, mix(e0, e1, 2, 1), mix(e0, e1, 1, 2)]
+    } else {
+        [e0, e1, mix(e0, e1, 1, 1), [0, 0, 0, 0]]
+    };
+
+    let indices = u32::from(block[4]) | (u32::from(block[5]) << 8) | (u32::from(block[6]) << 16) |
+        (u32::from(block[7]) << 24);
+
+    let mut texels = [[0u8; 4]; 16];
+
+    for (i, texel) in texels.iter_mut().enumerate() {
+        *texel = palette[((indices >> (i * 2)) & 0x3) as usize];
+    }
+
+    texels
+}
+
+fn decode_explicit_alpha(block: &[u8], texels: &mut [[u8; 4]; 16]) {
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let nibble = (block[i / 2] >> ((i % 2) * 4)) & 0xf;
+        texel[3] = nibble * 17;
+    }
+}
+
+fn decode_interpolated_alpha(block: &[u8], texels: &mut [[u8; 4]; 16]) {
+    let (a0, a1) = (u32::from(block[0]), u32::from(block[1]));
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 255];
+
+    // six interpolated values, or four plus fully transparent and opaque.
+    let steps = if a0 > a1 { 7 } else { 5 };
+
+    for (i, alpha) in palette[2..(steps + 1) as usize].iter_mut().enumerate() {
+        let i = i as u32 + 1;
+        *alpha = ((steps - i) * a0 + i * a1) / steps;
+    }
+
+    let mut indices = 0u64;
+
+    for (i, byte) in block[2..8].iter().enumerate() {
+        indices |= u64::from(*byte) << (i * 8);
+    }
+
+    for (i, texel) in texels.iter_mut().enumerate() {
+        texel[3] = palette[((indices >> (i * 3)) & 0x7) as usize] as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bgra() {
+        let out = decode(PixelFormat::Bgra8, &[1, 2, 3, 4, 5, 6, 7, 8], (2, 1)).unwrap();
+        assert_eq!(vec![3, 2, 1, 4, 7, 6, 5, 8], out);
+    }
+
+    #[test]
+    fn test_too_short() {
+        assert!(decode(PixelFormat::Rgba8, &[0; 15], (2, 2)).is_err());
+        assert!(decode(PixelFormat::Bc1, &[0; 8], (5, 4)).is_err());
+    }
+
+    #[test]
+    fn test_bc1() {
+        // red and blue endpoints, the first row using all four palette entries.
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0b11_10_01_00, 0, 0, 0];
+        let out = decode(PixelFormat::Bc1, &block, (4, 1)).unwrap();
+
+        assert_eq!(
+            vec![255, 0, 0, 255, 0, 0, 255, 255, 170, 0, 85, 255, 85, 0, 170, 255],
+            out
+        );
+    }
+
+    #[test]
+    fn test_bc1_transparent() {
+        // blue before red selects the three color mode, with index 3 being transparent.
+        let block = [0x1f, 0x00, 0x00, 0xf8, 0b11_10_00, 0, 0, 0];
+        let out = decode(PixelFormat::Bc1, &block, (3, 1)).unwrap();
+
+        assert_eq!(vec![0, 0, 255, 255, 127, 0, 127, 255, 0, 0, 0, 0], out);
+    }
+
+    #[test]
+    fn test_bc2_alpha() {
+        let mut block = [0u8; 16];
+        block[0] = 0xf0;
+        let out = decode(PixelFormat::Bc2, &block, (2, 1)).unwrap();
+
+        assert_eq!(0, out[3]);
+        assert_eq!(255, out[7]);
+    }
+
+    #[test]
+    fn test_bc3_alpha() {
+        let mut block = [0u8; 16];
+        block[0] = 255;
+        block[1] = 0;
+        // indices 0, 1 and 2 for the first three texels.
+        block[2] = 0b10_001_000;
+        let out = decode(PixelFormat::Bc3, &block, (3, 1)).unwrap();
+
+        assert_eq!(vec![255, 0, 218], vec![out[3], out[7], out[11]]);
+    }
+
+    #[test]
+    fn test_partial_blocks() {
+        let block = [0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0];
+        let out = decode(PixelFormat::Bc1, &block, (1, 1)).unwrap();
+        assert_eq!(vec![255, 0, 0, 255], out);
+    }
+}
@@ -0,0 +1,42 @@
+//! # Sampler settings for textures
+
+/// How texels are filtered when sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// How texture coordinates outside of [0, 1] are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+/// Describes how a texture should be sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sampler {
+    pub mag_filter: Filter,
+    pub min_filter: Filter,
+    /// Filter used between mip levels.
+    pub mipmap_filter: Filter,
+    /// Whether mip levels below the full size texture are sampled.
+    pub mipmaps: bool,
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+}
+
+impl Default for Sampler {
+    fn default() -> Sampler {
+        Sampler {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_filter: Filter::Linear,
+            mipmaps: true,
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+        }
+    }
+}
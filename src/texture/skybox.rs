@@ -0,0 +1,193 @@
+//! # Skyboxes
+//!
+//! Skyboxes are always drawn from an equirectangular texture, cubemaps are converted when loaded.
+
+use super::errors::*;
+use super::load_from_path;
+use super::sampler::Wrap;
+use super::texture::Texture;
+use std::f32;
+use std::path::Path;
+
+/// Number of faces in a cubemap.
+const CUBEMAP_FACES: usize = 6;
+
+#[derive(Debug, Clone)]
+pub enum Skybox {
+    /// A single texture, where the horizontal axis maps to longitude and the vertical to latitude.
+    Equirectangular(Texture),
+    /// Six square faces, in the order +X, -X, +Y, -Y, +Z, -Z.
+    Cubemap(Vec<Texture>),
+}
+
+impl Skybox {
+    /// Build a skybox from six square faces of the same size, in the order +X, -X, +Y, -Y, +Z,
+    /// -Z.
+    pub fn from_cubemap(faces: Vec<Texture>) -> Result<Skybox> {
+        if faces.len() != CUBEMAP_FACES {
+            return Err(
+                ErrorKind::InvalidCubemap(format!("expected 6 faces, got {}", faces.len())).into(),
+            );
+        }
+
+        let dimensions = faces[0].dimensions;
+
+        if dimensions.0 != dimensions.1 || dimensions.0 == 0 {
+            return Err(
+                ErrorKind::InvalidCubemap(format!("faces must be square, got {:?}", dimensions))
+                    .into(),
+            );
+        }
+
+        if let Some(face) = faces.iter().find(|f| f.dimensions != dimensions) {
+            return Err(
+                ErrorKind::InvalidCubemap(format!(
+                    "faces must have the same size, got {:?} and {:?}",
+                    dimensions,
+                    face.dimensions
+                )).into(),
+            );
+        }
+
+        Ok(Skybox::Cubemap(faces))
+    }
+
+    /// Load an equirectangular skybox.
+    pub fn load_equirectangular<P: AsRef<Path>>(path: P) -> Result<Skybox> {
+        Ok(Skybox::Equirectangular(load_from_path(None, path)?))
+    }
+
+    /// Load a cubemap skybox from six faces, in the order +X, -X, +Y, -Y, +Z, -Z.
+    pub fn load_cubemap<P: AsRef<Path>>(paths: &[P]) -> Result<Skybox> {
+        let mut faces = Vec::new();
+
+        for path in paths {
+            faces.push(load_from_path(None, path)?);
+        }
+
+        Skybox::from_cubemap(faces)
+    }
+
+    /// Convert into an equirectangular texture, ready to be drawn.
+    pub fn into_texture(self) -> Texture {
+        let mut texture = match self {
+            Skybox::Equirectangular(texture) => texture,
+            Skybox::Cubemap(faces) => cubemap_to_equirectangular(&faces),
+        };
+
+        // longitude wraps around, while latitude stops at the poles.
+        texture.sampler.wrap_s = Wrap::Repeat;
+        texture.sampler.wrap_t = Wrap::ClampToEdge;
+        texture
+    }
+}
+
+/// Direction of the sky for the given equirectangular texture coordinates.
+///
+/// The inverse of this is used when sampling the sky in shaders.
+fn direction(u: f32, v: f32) -> [f32; 3] {
+    let phi = (u - 0.5) * f32::consts::PI * 2.0;
+    let theta = v * f32::consts::PI;
+
+    [theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos()]
+}
+
+/// Find the cubemap face and texture coordinates for a direction.
+fn cubemap_lookup(d: [f32; 3]) -> (usize, f32, f32) {
+    let (x, y, z) = (d[0], d[1], d[2]);
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x > 0.0 {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y > 0.0 {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z > 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+
+    (face, (sc / ma + 1.0) / 2.0, (tc / ma + 1.0) / 2.0)
+}
+
+fn cubemap_to_equirectangular(faces: &[Texture]) -> Texture {
+    let size = faces[0].dimensions.0;
+    let width = size * 4;
+    let height = size * 2;
+
+    let mut image_data = Vec::with_capacity((width * height * 4) as usize);
+
+    for y in 0..height {
+        for x in 0..width {
+            let u = (x as f32 + 0.5) / width as f32;
+            let v = (y as f32 + 0.5) / height as f32;
+
+            let (face, s, t) = cubemap_lookup(direction(u, v));
+            let face = &faces[face];
+
+            let fx = u32::min((s * size as f32) as u32, size - 1);
+            let fy = u32::min((t * size as f32) as u32, size - 1);
+            let offset = ((fy * size + fx) * 4) as usize;
+
+            image_data.extend_from_slice(&face.image_data[offset..offset + 4]);
+        }
+    }
+
+    Texture::from_raw(image_data, (width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(size: u32, value: u8) -> Texture {
+        Texture::from_raw(vec![value; (size * size * 4) as usize], (size, size))
+    }
+
+    #[test]
+    fn test_cubemap_lookup() {
+        assert_eq!(0, cubemap_lookup([1.0, 0.0, 0.0]).0);
+        assert_eq!(1, cubemap_lookup([-1.0, 0.0, 0.0]).0);
+        assert_eq!(2, cubemap_lookup([0.0, 1.0, 0.0]).0);
+        assert_eq!(3, cubemap_lookup([0.0, -1.0, 0.0]).0);
+        assert_eq!(4, cubemap_lookup([0.0, 0.0, 1.0]).0);
+        assert_eq!(5, cubemap_lookup([0.0, 0.0, -1.0]).0);
+
+        let (_, s, t) = cubemap_lookup([1.0, 0.0, 0.0]);
+        assert_eq!((0.5, 0.5), (s, t));
+    }
+
+    #[test]
+    fn test_cubemap_to_equirectangular() {
+        let faces = (0..6).map(|i| solid(4, i as u8)).collect();
+        let texture = Skybox::from_cubemap(faces).unwrap().into_texture();
+
+        assert_eq!((16, 8), texture.dimensions);
+
+        let texel = |x: u32, y: u32| texture.image_data[((y * 16 + x) * 4) as usize];
+
+        // top and bottom rows are the poles.
+        assert_eq!(2, texel(0, 0));
+        assert_eq!(3, texel(0, 7));
+        // the center of the texture looks towards -Z, and a quarter turn further towards +X.
+        assert_eq!(5, texel(8, 4));
+        assert_eq!(0, texel(12, 4));
+    }
+
+    #[test]
+    fn test_invalid_cubemap() {
+        assert!(Skybox::from_cubemap(vec![solid(4, 0); 5]).is_err());
+
+        let mut faces = vec![solid(4, 0); 6];
+        faces[3] = solid(2, 0);
+        assert!(Skybox::from_cubemap(faces).is_err());
+    }
+}
@@ -1,9 +1,48 @@
 #![allow(dead_code)]
 
+use super::sampler::Sampler;
+use gfx::color::{linear_to_srgb, srgb_to_linear};
+use std::fmt;
+
+/// How the texels of a texture are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Colors encoded with the sRGB transfer function, decoded to linear when sampled.
+    Srgb,
+    /// Data like normals, roughness or occlusion, which is sampled as stored.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Decode a color component to a linear value in [0, 1].
+    pub fn decode(self, c: u8) -> f32 {
+        let c = f32::from(c) / 255.0;
+
+        match self {
+            ColorSpace::Srgb => srgb_to_linear(c),
+            ColorSpace::Linear => c,
+        }
+    }
+
+    /// Encode a linear value in [0, 1] to a color component.
+    pub fn encode(self, c: f32) -> u8 {
+        let c = match self {
+            ColorSpace::Srgb => linear_to_srgb(c),
+            ColorSpace::Linear => c,
+        };
+
+        (f32::min(f32::max(c, 0.0), 1.0) * 255.0).round() as u8
+    }
+}
+
 #[derive(Clone)]
 pub struct Texture {
     pub image_data: Vec<u8>,
     pub dimensions: (u32, u32),
+    /// How the texture should be sampled.
+    pub sampler: Sampler,
+    /// Color space of the texels, which decides the format the texture is uploaded with.
+    pub color_space: ColorSpace,
 }
 
 impl Texture {
@@ -11,6 +50,72 @@ impl Texture {
         Texture {
             image_data: image_data,
             dimensions: dimensions,
+            sampler: Sampler::default(),
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// Average color of all texels, with linear components in [0, 1].
+    ///
+    /// sRGB colors are decoded before averaging, since the encoded values are not proportional
+    /// to light.
+    pub fn average_color(&self) -> [f32; 4] {
+        let mut sum = [0f64; 4];
+
+        for texel in self.image_data.chunks(4) {
+            for (i, (s, c)) in sum.iter_mut().zip(texel).enumerate() {
+                // alpha is always stored linearly.
+                *s += if i < 3 {
+                    f64::from(self.color_space.decode(*c))
+                } else {
+                    f64::from(*c) / 255.0
+                };
+            }
+        }
+
+        let count = u64::max((self.image_data.len() / 4) as u64, 1) as f64;
+        [
+            (sum[0] / count) as f32,
+            (sum[1] / count) as f32,
+            (sum[2] / count) as f32,
+            (sum[3] / count) as f32,
+        ]
+    }
+}
+
+impl fmt::Debug for Texture {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Texture {{ dimensions: {:?}, sampler: {:?}, color_space: {:?} }}",
+            self.dimensions,
+            self.sampler,
+            self.color_space
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_color() {
+        let mut texture = Texture::from_raw(vec![0, 0, 255, 255, 255, 0, 255, 0], (2, 1));
+        texture.color_space = ColorSpace::Linear;
+        assert_eq!([0.5, 0.0, 1.0, 0.5], texture.average_color());
+
+        // a mid gray in sRGB carries a fifth of the light of white.
+        let gray = Texture::from_raw(vec![128, 128, 128, 255], (1, 1));
+        assert!((gray.average_color()[0] - 0.2159).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for c in 0..256 {
+            let c = c as u8;
+            assert_eq!(c, ColorSpace::Srgb.encode(ColorSpace::Srgb.decode(c)));
+            assert_eq!(c, ColorSpace::Linear.encode(ColorSpace::Linear.decode(c)));
         }
     }
 }
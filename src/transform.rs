@@ -0,0 +1,95 @@
+//! # Placement of entities in the world
+
+use cgmath::{Matrix3, Matrix4, Point3, Quaternion, Vector3};
+use cgmath::prelude::*;
+
+/// Transformation of an entity, which is scaled first, then rotated and then translated.
+pub fn trs(location: Point3<f32>, rotation: Quaternion<f32>, scale: Vector3<f32>) -> Matrix4<f32> {
+    Matrix4::from_translation(location.to_vec()) * Matrix4::from(rotation) *
+        Matrix4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+}
+
+/// Apply the movement of a tick, given as a turn and a translation relative to the facing of the
+/// entity.
+///
+/// The translation follows the direction the entity faced before turning.
+pub fn move_relative(
+    location: Point3<f32>,
+    rotation: Quaternion<f32>,
+    movement: &Matrix4<f32>,
+) -> (Point3<f32>, Quaternion<f32>) {
+    let translation = rotation.rotate_vector(movement.w.truncate());
+
+    let turn = Quaternion::from(Matrix3::from_cols(
+        movement.x.truncate(),
+        movement.y.truncate(),
+        movement.z.truncate(),
+    ));
+
+    (location + translation, (rotation * turn).normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::Deg;
+
+    fn assert_close(expected: Point3<f32>, actual: Point3<f32>) {
+        assert!(
+            (expected - actual).magnitude() < 1e-5,
+            "{:?} != {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_trs_order() {
+        let transform = trs(
+            Point3::new(10.0, 0.0, 0.0),
+            Quaternion::from_angle_y(Deg(90.0)),
+            Vector3::new(2.0, 1.0, 1.0),
+        );
+
+        // scaled to (2, 0, 0), rotated to (0, 0, -2) and then translated.
+        let p = transform.transform_point(Point3::new(1.0, 0.0, 0.0));
+        assert_close(Point3::new(10.0, 0.0, -2.0), p);
+    }
+
+    #[test]
+    fn test_trs_identity() {
+        let transform = trs(
+            Point3::new(1.0, 2.0, 3.0),
+            Quaternion::one(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+
+        let p = transform.transform_point(Point3::new(0.0, 0.0, 0.0));
+        assert_close(Point3::new(1.0, 2.0, 3.0), p);
+    }
+
+    #[test]
+    fn test_move_relative_follows_facing() {
+        let facing = Quaternion::from_angle_y(Deg(90.0));
+        let forward = Matrix4::from_translation(Vector3::new(1.0, 0.0, 0.0));
+
+        let (location, rotation) = move_relative(Point3::new(0.0, 0.0, 0.0), facing, &forward);
+
+        assert_close(Point3::new(0.0, 0.0, -1.0), location);
+        assert!((facing - rotation).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn test_move_relative_turns_after_moving() {
+        let movement = Matrix4::from_translation(Vector3::new(1.0, 0.0, 0.0)) *
+            Matrix4::from_angle_y(Deg(90.0));
+
+        let start = Point3::new(0.0, 0.0, 0.0);
+        let (location, rotation) = move_relative(start, Quaternion::one(), &movement);
+        assert_close(Point3::new(1.0, 0.0, 0.0), location);
+
+        // the next step moves along the turned forward axis.
+        let (location, _) = move_relative(location, rotation, &movement);
+        assert_close(Point3::new(1.0, 0.0, -1.0), location);
+    }
+}
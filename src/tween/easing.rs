@@ -0,0 +1,148 @@
+use std::f32::consts::PI;
+
+/// Curves shaping the progress of a tween.
+///
+/// All curves start at 0 and end at 1, but some overshoot in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Pulls back slightly before moving towards the target.
+    BackIn,
+    /// Overshoots the target slightly before settling.
+    BackOut,
+    /// Bounces against the target like a dropped ball.
+    BounceOut,
+}
+
+/// How far back the `Back` curves overshoot, gives roughly 10%.
+const BACK: f32 = 1.70158;
+
+impl Easing {
+    /// Map linear progress in [0, 1] onto the curve.
+    pub fn apply(&self, t: f32) -> f32 {
+        use self::Easing::*;
+
+        let t = f32::min(1.0, f32::max(0.0, t));
+
+        match *self {
+            Linear => t,
+            QuadIn => t * t,
+            QuadOut => t * (2.0 - t),
+            QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+            CubicIn => t * t * t,
+            CubicOut => {
+                let u = t - 1.0;
+                u * u * u + 1.0
+            }
+            CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let u = 2.0 * t - 2.0;
+                    0.5 * u * u * u + 1.0
+                }
+            }
+            SineIn => 1.0 - (t * PI / 2.0).cos(),
+            SineOut => (t * PI / 2.0).sin(),
+            SineInOut => 0.5 * (1.0 - (t * PI).cos()),
+            BackIn => t * t * ((BACK + 1.0) * t - BACK),
+            BackOut => {
+                let u = t - 1.0;
+                u * u * ((BACK + 1.0) * u + BACK) + 1.0
+            }
+            BounceOut => bounce_out(t),
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Easing {
+        Easing::Linear
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::Easing::*;
+
+    const ALL: &'static [Easing] = &[
+        Linear,
+        QuadIn,
+        QuadOut,
+        QuadInOut,
+        CubicIn,
+        CubicOut,
+        CubicInOut,
+        SineIn,
+        SineOut,
+        SineInOut,
+        BackIn,
+        BackOut,
+        BounceOut,
+    ];
+
+    #[test]
+    fn test_end_points() {
+        for easing in ALL {
+            assert!(easing.apply(0.0).abs() < 0.0001, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 0.0001, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn test_clamped_progress() {
+        for easing in ALL {
+            assert_eq!(easing.apply(0.0), easing.apply(-1.0));
+            assert_eq!(easing.apply(1.0), easing.apply(2.0));
+        }
+    }
+
+    #[test]
+    fn test_symmetric_in_out() {
+        assert!((QuadInOut.apply(0.5) - 0.5).abs() < 0.0001);
+        assert!((CubicInOut.apply(0.5) - 0.5).abs() < 0.0001);
+        assert!((SineInOut.apply(0.5) - 0.5).abs() < 0.0001);
+        assert!(QuadIn.apply(0.25) < 0.25);
+        assert!(QuadOut.apply(0.25) > 0.25);
+    }
+
+    #[test]
+    fn test_back_overshoots() {
+        assert!(BackIn.apply(0.2) < 0.0);
+        assert!(BackOut.apply(0.8) > 1.0);
+    }
+}
@@ -0,0 +1,46 @@
+//! # Tweening
+//!
+//! Animates properties of entities, cameras and colors over a number of ticks, driven by the
+//! [Scheduler](../scheduler/struct.Scheduler.html).
+//!
+//! A [Tween](steps/struct.Tween.html) moves a single [Property](property/struct.Property.html)
+//! towards a target value, shaped by an [Easing](easing/enum.Easing.html) curve.
+//! Tweens are combined using [Sequence](steps/struct.Sequence.html) and
+//! [Parallel](steps/struct.Parallel.html), and started with [start](fn.start.html) or
+//! [schedule](fn.schedule.html).
+
+pub mod easing;
+pub mod property;
+pub mod steps;
+
+pub use self::easing::Easing;
+pub use self::property::Property;
+pub use self::steps::{Call, Delay, Parallel, Sequence, Step, Tween};
+
+use scheduler::{CallbackFn, Scheduler, SelfScheduler};
+use std::cell::RefCell;
+
+/// Start running the given step on the next tick, from within a scheduled callback.
+pub fn start<S, T: 'static + Step>(scheduler: &mut SelfScheduler<S>, step: T) {
+    // offset 0 would never run, since the current tick is already being processed.
+    scheduler.run_at(1, callback(step));
+}
+
+/// Start running the given step on the next tick of the scheduler, typically while setting up a
+/// scene.
+pub fn schedule<S, T: 'static + Step>(scheduler: &mut Scheduler<S>, step: T) {
+    scheduler.run_at(0, callback(step));
+}
+
+/// Build a task which advances the step once per tick, until it is finished.
+fn callback<S, T: 'static + Step>(step: T) -> Box<CallbackFn<S>> {
+    let step = RefCell::new(step);
+
+    Box::new(move |scheduler, _| {
+        if !step.try_borrow_mut()?.tick()? {
+            scheduler.run_self_at(1);
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,118 @@
+use camera::Camera;
+use cgmath::{EuclideanSpace, Point3, Quaternion, Vector3};
+use errors::*;
+use static_entity::StaticEntity;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+
+/// A value which can be read and written, the target of a tween.
+pub struct Property<T> {
+    get: Box<Fn() -> Result<T>>,
+    set: Box<Fn(T) -> Result<()>>,
+}
+
+impl<T: 'static> Property<T> {
+    pub fn new<G, S>(get: G, set: S) -> Property<T>
+    where
+        G: 'static + Fn() -> Result<T>,
+        S: 'static + Fn(T) -> Result<()>,
+    {
+        Property {
+            get: Box::new(get),
+            set: Box::new(set),
+        }
+    }
+
+    pub fn get(&self) -> Result<T> {
+        (self.get)()
+    }
+
+    pub fn set(&self, value: T) -> Result<()> {
+        (self.set)(value)
+    }
+
+    /// A value shared through a cell, like a color read by game logic.
+    pub fn cell(cell: &Rc<Cell<T>>) -> Property<T>
+    where
+        T: Copy,
+    {
+        let get = cell.clone();
+        let set = cell.clone();
+
+        Property::new(move || Ok(get.get()), move |value| {
+            set.set(value);
+            Ok(())
+        })
+    }
+
+    /// A value shared through a lock, like a color read by the render thread.
+    pub fn shared(lock: &Arc<RwLock<T>>) -> Property<T>
+    where
+        T: Copy,
+    {
+        let get = lock.clone();
+        let set = lock.clone();
+
+        Property::new(
+            move || Ok(*get.read().map_err(|_| ErrorKind::PoisonError)?),
+            move |value| {
+                *set.write().map_err(|_| ErrorKind::PoisonError)? = value;
+                Ok(())
+            },
+        )
+    }
+}
+
+impl Property<Vector3<f32>> {
+    /// The position of an entity.
+    ///
+    /// Positions are tweened as vectors from the origin.
+    pub fn position(entity: &StaticEntity) -> Property<Vector3<f32>> {
+        let get = entity.clone();
+        let set = entity.clone();
+
+        Property::new(
+            move || Ok(get.position()?.to_vec()),
+            move |value| Ok(set.set_position(Point3::from_vec(value))?),
+        )
+    }
+
+    /// The scale of an entity along each axis.
+    pub fn scale(entity: &StaticEntity) -> Property<Vector3<f32>> {
+        let get = entity.clone();
+        let set = entity.clone();
+        Property::new(move || Ok(get.scale()?), move |value| Ok(set.set_scale(value)?))
+    }
+}
+
+impl Property<Quaternion<f32>> {
+    /// The orientation of an entity.
+    pub fn rotation(entity: &StaticEntity) -> Property<Quaternion<f32>> {
+        let get = entity.clone();
+        let set = entity.clone();
+
+        Property::new(
+            move || Ok(get.rotation()?),
+            move |value| Ok(set.set_rotation(value)?),
+        )
+    }
+}
+
+impl Property<f32> {
+    /// The zoom of a camera.
+    pub fn zoom(camera: &Arc<RwLock<Camera>>) -> Property<f32> {
+        let get = camera.clone();
+        let set = camera.clone();
+
+        Property::new(
+            move || Ok(get.read().map_err(|_| ErrorKind::PoisonError)?.zoom()),
+            move |value| {
+                set.write().map_err(|_| ErrorKind::PoisonError)?.set_zoom(
+                    value,
+                );
+                Ok(())
+            },
+        )
+    }
+}
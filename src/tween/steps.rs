@@ -0,0 +1,303 @@
+use super::easing::Easing;
+use super::property::Property;
+use animation::Interpolate;
+use errors::*;
+
+/// Something advanced once per tick until it is finished.
+///
+/// Every step takes at least one tick, so a step following another step in a
+/// [Sequence](struct.Sequence.html) starts on the tick after.
+pub trait Step {
+    /// Advance by a single tick, returns `true` when the step is finished.
+    fn tick(&mut self) -> Result<bool>;
+}
+
+/// Moves a property towards a target value over a number of ticks.
+pub struct Tween<T> {
+    property: Property<T>,
+    /// Where the tween starts, read from the property on the first tick if not set.
+    from: Option<T>,
+    to: T,
+    ticks: u32,
+    elapsed: u32,
+    easing: Easing,
+    on_complete: Option<Box<FnMut() -> Result<()>>>,
+}
+
+impl<T: 'static + Interpolate> Tween<T> {
+    /// Tween the property from its current value to the given value, over the given number of
+    /// ticks.
+    pub fn new(property: Property<T>, to: T, ticks: u32) -> Tween<T> {
+        Tween {
+            property: property,
+            from: None,
+            to: to,
+            ticks: ticks,
+            elapsed: 0,
+            easing: Easing::Linear,
+            on_complete: None,
+        }
+    }
+
+    /// Start from the given value, instead of the value of the property when the tween starts.
+    pub fn from(mut self, from: T) -> Tween<T> {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Tween<T> {
+        self.easing = easing;
+        self
+    }
+
+    /// Call the given function once the target value has been reached.
+    pub fn on_complete<F: 'static + FnMut() -> Result<()>>(mut self, callback: F) -> Tween<T> {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+}
+
+impl<T: 'static + Interpolate> Step for Tween<T> {
+    fn tick(&mut self) -> Result<bool> {
+        let from = match self.from {
+            Some(from) => from,
+            None => {
+                let from = self.property.get()?;
+                self.from = Some(from);
+                from
+            }
+        };
+
+        self.elapsed = u32::min(self.ticks, self.elapsed + 1);
+
+        let t = if self.ticks > 0 {
+            self.elapsed as f32 / self.ticks as f32
+        } else {
+            1.0
+        };
+
+        let value = if self.elapsed == self.ticks {
+            // land exactly on the target, regardless of rounding.
+            self.to
+        } else {
+            from.lerp(self.to, self.easing.apply(t)).normalize()
+        };
+
+        self.property.set(value)?;
+
+        if self.elapsed < self.ticks {
+            return Ok(false);
+        }
+
+        if let Some(mut callback) = self.on_complete.take() {
+            callback()?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Runs steps one after another.
+pub struct Sequence {
+    steps: Vec<Box<Step>>,
+    current: usize,
+}
+
+impl Sequence {
+    pub fn new() -> Sequence {
+        Sequence {
+            steps: Vec::new(),
+            current: 0,
+        }
+    }
+
+    /// Add a step to the end of the sequence.
+    pub fn then<T: 'static + Step>(mut self, step: T) -> Sequence {
+        self.steps.push(Box::new(step));
+        self
+    }
+}
+
+impl Step for Sequence {
+    fn tick(&mut self) -> Result<bool> {
+        if let Some(step) = self.steps.get_mut(self.current) {
+            if step.tick()? {
+                self.current += 1;
+            }
+        }
+
+        Ok(self.current >= self.steps.len())
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Sequence {
+        Sequence::new()
+    }
+}
+
+/// Runs steps at the same time, finishing when all of them have finished.
+pub struct Parallel {
+    steps: Vec<Option<Box<Step>>>,
+}
+
+impl Parallel {
+    pub fn new() -> Parallel {
+        Parallel { steps: Vec::new() }
+    }
+
+    pub fn with<T: 'static + Step>(mut self, step: T) -> Parallel {
+        self.steps.push(Some(Box::new(step)));
+        self
+    }
+}
+
+impl Step for Parallel {
+    fn tick(&mut self) -> Result<bool> {
+        let mut finished = true;
+
+        for slot in &mut self.steps {
+            let done = match *slot {
+                Some(ref mut step) => step.tick()?,
+                None => continue,
+            };
+
+            if done {
+                *slot = None;
+            } else {
+                finished = false;
+            }
+        }
+
+        Ok(finished)
+    }
+}
+
+impl Default for Parallel {
+    fn default() -> Parallel {
+        Parallel::new()
+    }
+}
+
+/// Waits for a number of ticks.
+#[derive(Debug, Clone, Copy)]
+pub struct Delay {
+    ticks: u32,
+}
+
+impl Delay {
+    pub fn new(ticks: u32) -> Delay {
+        Delay { ticks: ticks }
+    }
+}
+
+impl Step for Delay {
+    fn tick(&mut self) -> Result<bool> {
+        self.ticks = self.ticks.saturating_sub(1);
+        Ok(self.ticks == 0)
+    }
+}
+
+/// Calls a function once, typically at the end of a sequence.
+pub struct Call {
+    callback: Box<FnMut() -> Result<()>>,
+}
+
+impl Call {
+    pub fn new<F: 'static + FnMut() -> Result<()>>(callback: F) -> Call {
+        Call { callback: Box::new(callback) }
+    }
+}
+
+impl Step for Call {
+    fn tick(&mut self) -> Result<bool> {
+        (self.callback)()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn run<T: Step>(step: &mut T) -> u32 {
+        let mut ticks = 1;
+
+        while !step.tick().unwrap() {
+            ticks += 1;
+        }
+
+        ticks
+    }
+
+    #[test]
+    fn test_tween_from_current_value() {
+        let value = Rc::new(Cell::new(2.0f32));
+        let mut tween = Tween::new(Property::cell(&value), 6.0, 4);
+
+        assert!(!tween.tick().unwrap());
+        assert_eq!(3.0, value.get());
+        assert_eq!(4, 1 + run(&mut tween));
+        assert_eq!(6.0, value.get());
+    }
+
+    #[test]
+    fn test_tween_easing_and_from() {
+        let value = Rc::new(Cell::new(0.0f32));
+        let mut tween = Tween::new(Property::cell(&value), 1.0, 2)
+            .from(0.5)
+            .easing(Easing::QuadIn);
+
+        tween.tick().unwrap();
+        assert_eq!(0.5 + 0.5 * 0.25, value.get());
+    }
+
+    #[test]
+    fn test_zero_ticks() {
+        let value = Rc::new(Cell::new(0.0f32));
+        let mut tween = Tween::new(Property::cell(&value), 1.0, 0);
+
+        assert!(tween.tick().unwrap());
+        assert_eq!(1.0, value.get());
+    }
+
+    #[test]
+    fn test_sequence_and_callbacks() {
+        let value = Rc::new(Cell::new(0.0f32));
+        let calls = Rc::new(Cell::new(0));
+
+        let (a, b) = (calls.clone(), calls.clone());
+
+        let mut sequence = Sequence::new()
+            .then(Tween::new(Property::cell(&value), 1.0, 3).on_complete(move || {
+                a.set(a.get() + 1);
+                Ok(())
+            }))
+            .then(Delay::new(2))
+            .then(Tween::new(Property::cell(&value), 0.0, 3))
+            .then(Call::new(move || {
+                b.set(b.get() + 10);
+                Ok(())
+            }));
+
+        assert_eq!(9, run(&mut sequence));
+        assert_eq!(0.0, value.get());
+        assert_eq!(11, calls.get());
+    }
+
+    #[test]
+    fn test_parallel() {
+        let a = Rc::new(Cell::new(0.0f32));
+        let b = Rc::new(Cell::new(0.0f32));
+
+        let mut parallel = Parallel::new()
+            .with(Tween::new(Property::cell(&a), 1.0, 2))
+            .with(Tween::new(Property::cell(&b), 1.0, 5));
+
+        assert_eq!(5, run(&mut parallel));
+        assert_eq!(1.0, a.get());
+        assert_eq!(1.0, b.get());
+    }
+}
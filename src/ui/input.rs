@@ -0,0 +1,73 @@
+/// Special keys used by widgets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiKey {
+    Backspace,
+    Enter,
+    Escape,
+}
+
+/// Keyboard input for widgets with focus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiTyped {
+    /// A typed character, never a control character.
+    Char(char),
+    Key(UiKey),
+}
+
+/// Input collected between two ticks, used to drive widgets.
+#[derive(Debug, Clone)]
+pub struct UiInput {
+    /// Position of the mouse, in pixels from the top-left corner of the window.
+    pub mouse: [f32; 2],
+    /// If the primary mouse button is currently held down.
+    pub mouse_down: bool,
+    /// If the primary mouse button was pressed since the last tick.
+    pub mouse_pressed: bool,
+    /// If the primary mouse button was released since the last tick.
+    pub mouse_released: bool,
+    /// Characters typed and special keys pressed since the last tick, in the order they
+    /// arrived.
+    pub typed: Vec<UiTyped>,
+}
+
+impl UiInput {
+    pub fn new() -> UiInput {
+        UiInput {
+            mouse: [0.0, 0.0],
+            mouse_down: false,
+            mouse_pressed: false,
+            mouse_released: false,
+            typed: Vec::new(),
+        }
+    }
+
+    pub fn mouse_button(&mut self, down: bool) {
+        if down && !self.mouse_down {
+            self.mouse_pressed = true;
+        }
+
+        if !down && self.mouse_down {
+            self.mouse_released = true;
+        }
+
+        self.mouse_down = down;
+    }
+
+    /// Type a character, control characters are ignored.
+    pub fn type_char(&mut self, c: char) {
+        if !c.is_control() {
+            self.typed.push(UiTyped::Char(c));
+        }
+    }
+
+    pub fn press_key(&mut self, key: UiKey) {
+        self.typed.push(UiTyped::Key(key));
+    }
+
+    /// Clear everything which only applies to a single tick.
+    pub fn next_tick(&mut self) {
+        self.mouse_pressed = false;
+        self.mouse_released = false;
+        self.typed.clear();
+    }
+}
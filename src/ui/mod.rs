@@ -0,0 +1,12 @@
+//! # Immediate-mode user interface
+//!
+//! Widgets are declared every tick, and report interactions directly through their return values.
+//! Everything is drawn into the 2D overlay.
+
+pub mod input;
+pub mod style;
+pub mod ui;
+
+pub use self::input::{UiInput, UiKey, UiTyped};
+pub use self::style::Style;
+pub use self::ui::Ui;
@@ -0,0 +1,34 @@
+use gfx::color::Color;
+
+/// Colors and sizes used when drawing widgets.
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    pub panel: Color,
+    pub widget: Color,
+    /// Color of widgets under the mouse.
+    pub hot: Color,
+    /// Color of widgets being interacted with.
+    pub active: Color,
+    pub text: Color,
+    /// Height of a single widget row.
+    pub row_height: f32,
+    /// Space between the edge of a panel and its widgets.
+    pub padding: f32,
+    /// Space between two widget rows.
+    pub spacing: f32,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            panel: Color::from_rgba(0.1, 0.1, 0.1, 0.8),
+            widget: Color::from_rgba(0.3, 0.3, 0.3, 1.0),
+            hot: Color::from_rgba(0.4, 0.4, 0.4, 1.0),
+            active: Color::from_rgba(0.2, 0.4, 0.6, 1.0),
+            text: Color::from_rgb(1.0, 1.0, 1.0),
+            row_height: 24.0,
+            padding: 8.0,
+            spacing: 4.0,
+        }
+    }
+}
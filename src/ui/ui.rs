@@ -0,0 +1,484 @@
+use super::input::{UiInput, UiKey, UiTyped};
+use super::style::Style;
+use gfx::color::Color;
+use gfx::overlay::{Overlay, Rect};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Arc;
+use text::{Align, Font, TextOptions};
+
+/// Identifies a widget across ticks.
+pub type WidgetId = u64;
+
+/// Width of widgets placed outside of any panel.
+const DEFAULT_WIDTH: f32 = 200.0;
+
+/// Vertical layout of widgets inside of a panel.
+struct Layout {
+    id: WidgetId,
+    x: f32,
+    y: f32,
+    width: f32,
+}
+
+pub struct Ui {
+    font: Option<Arc<Font>>,
+    pub style: Style,
+    input: UiInput,
+    /// Widget under the mouse.
+    hot: Option<WidgetId>,
+    /// Widget being interacted with, typically where the mouse button was pressed.
+    active: Option<WidgetId>,
+    /// Widget receiving keyboard input.
+    focused: Option<WidgetId>,
+    /// If a widget claimed the mouse press of the current tick.
+    pressed_claimed: bool,
+    layouts: Vec<Layout>,
+    overlay: Overlay,
+}
+
+impl Ui {
+    pub fn new() -> Ui {
+        Ui {
+            font: None,
+            style: Style::default(),
+            input: UiInput::new(),
+            hot: None,
+            active: None,
+            focused: None,
+            pressed_claimed: false,
+            layouts: Vec::new(),
+            overlay: Overlay::new(),
+        }
+    }
+
+    /// Set the font used for all text.
+    pub fn set_font(&mut self, font: Arc<Font>) {
+        self.font = Some(font);
+    }
+
+    /// If a widget is currently receiving keyboard input.
+    ///
+    /// Game controls should ignore the keyboard while this is the case.
+    pub fn wants_keyboard(&self) -> bool {
+        self.focused.is_some()
+    }
+
+    /// If the mouse is over, or interacting with, a widget.
+    pub fn wants_mouse(&self) -> bool {
+        self.hot.is_some() || self.active.is_some()
+    }
+
+    /// Start declaring widgets for a new tick.
+    pub fn begin(&mut self, input: UiInput) {
+        self.input = input;
+        self.hot = None;
+        self.pressed_claimed = false;
+        self.layouts.clear();
+
+        let padding = self.style.padding;
+
+        self.layouts.push(Layout {
+            id: 0,
+            x: padding,
+            y: padding,
+            width: DEFAULT_WIDTH,
+        });
+    }
+
+    /// Finish the current tick, appending everything drawn to the given overlay.
+    pub fn end(&mut self, overlay: &mut Overlay) {
+        if !self.input.mouse_down {
+            self.active = None;
+        }
+
+        // clicking outside of any widget removes keyboard focus.
+        if self.input.mouse_pressed && !self.pressed_claimed {
+            self.focused = None;
+        }
+
+        let drawn = mem::replace(&mut self.overlay, Overlay::new());
+        overlay.quads.extend(drawn.quads);
+    }
+
+    /// Start a panel, which lays out all widgets declared until `end_panel` vertically.
+    pub fn begin_panel(&mut self, label: &str, rect: Rect) {
+        let id = self.id(label);
+        let panel = self.style.panel;
+        let padding = self.style.padding;
+
+        self.overlay.rect(rect, panel);
+        self.overlay.push_clip(rect);
+
+        self.layouts.push(Layout {
+            id: id,
+            x: rect.x + padding,
+            y: rect.y + padding,
+            width: rect.width - padding * 2.0,
+        });
+    }
+
+    pub fn end_panel(&mut self) {
+        // the root layout is never removed.
+        if self.layouts.len() > 1 {
+            self.layouts.pop();
+            self.overlay.pop_clip();
+        }
+    }
+
+    /// A line of text.
+    pub fn label(&mut self, text: &str) {
+        let rect = self.next_rect();
+        self.text(rect, text, Align::Left);
+    }
+
+    /// A button, returns `true` if it was clicked.
+    pub fn button(&mut self, label: &str) -> bool {
+        let id = self.id(label);
+        let rect = self.next_rect();
+        let clicked = self.interact(id, rect);
+
+        let color = self.widget_color(id);
+        self.overlay.rect(rect, color);
+        self.text(rect, label, Align::Center);
+
+        clicked
+    }
+
+    /// A checkbox, returns `true` if it was toggled.
+    pub fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let id = self.id(label);
+        let rect = self.next_rect();
+        let toggled = self.interact(id, rect);
+
+        if toggled {
+            *value = !*value;
+        }
+
+        let size = rect.height;
+        let check = Rect::new(rect.x, rect.y, size, size);
+        let color = self.widget_color(id);
+        self.overlay.rect(check, color);
+
+        if *value {
+            let inner = size / 4.0;
+            let active = self.style.active;
+
+            self.overlay.rect(
+                Rect::new(
+                    check.x + inner,
+                    check.y + inner,
+                    size - inner * 2.0,
+                    size - inner * 2.0,
+                ),
+                active,
+            );
+        }
+
+        let spacing = self.style.spacing;
+
+        self.text(
+            Rect::new(
+                rect.x + size + spacing,
+                rect.y,
+                rect.width - size - spacing,
+                rect.height,
+            ),
+            label,
+            Align::Left,
+        );
+
+        toggled
+    }
+
+    /// A horizontal slider between `min` and `max`, returns `true` if the value changed.
+    pub fn slider(&mut self, label: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let id = self.id(label);
+        let rect = self.next_rect();
+        self.interact(id, rect);
+
+        let mut changed = false;
+
+        if self.active == Some(id) && self.input.mouse_down && rect.width > 0.0 {
+            let fraction = (self.input.mouse[0] - rect.x) / rect.width;
+            let new_value = min + f32::min(1.0, f32::max(0.0, fraction)) * (max - min);
+
+            if new_value != *value {
+                *value = new_value;
+                changed = true;
+            }
+        }
+
+        let color = self.widget_color(id);
+        self.overlay.rect(rect, color);
+
+        let fraction = if max > min {
+            (*value - min) / (max - min)
+        } else {
+            0.0
+        };
+
+        let handle_width = rect.height / 2.0;
+        let active = self.style.active;
+
+        self.overlay.rect(
+            Rect::new(
+                rect.x + (rect.width - handle_width) * fraction,
+                rect.y,
+                handle_width,
+                rect.height,
+            ),
+            active,
+        );
+
+        let text = format!("{}: {:.2}", label, value);
+        self.text(rect, &text, Align::Center);
+        changed
+    }
+
+    /// A single line text input, returns `true` if the text changed.
+    ///
+    /// Clicking the input gives it keyboard focus, which is removed by pressing enter or escape,
+    /// or by clicking somewhere else.
+    pub fn text_input(&mut self, label: &str, value: &mut String) -> bool {
+        let id = self.id(label);
+        let rect = self.next_rect();
+
+        if self.interact(id, rect) {
+            self.focused = Some(id);
+        }
+
+        let mut changed = false;
+
+        if self.focused == Some(id) {
+            // applied in the order they were typed, so that backspace removes the right one.
+            for typed in &self.input.typed {
+                match *typed {
+                    UiTyped::Char(c) => {
+                        value.push(c);
+                        changed = true;
+                    }
+                    UiTyped::Key(UiKey::Backspace) => {
+                        changed = value.pop().is_some() || changed;
+                    }
+                    UiTyped::Key(UiKey::Enter) |
+                    UiTyped::Key(UiKey::Escape) => {
+                        self.focused = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let color = if self.focused == Some(id) {
+            self.style.active
+        } else {
+            self.widget_color(id)
+        };
+
+        self.overlay.rect(rect, color);
+
+        let text = if self.focused == Some(id) {
+            format!("{}_", value)
+        } else {
+            value.clone()
+        };
+
+        let padding = self.style.spacing;
+
+        self.text(
+            Rect::new(
+                rect.x + padding,
+                rect.y,
+                rect.width - padding * 2.0,
+                rect.height,
+            ),
+            &text,
+            Align::Left,
+        );
+
+        changed
+    }
+
+    /// Build an id for a widget, unique within the current panel.
+    fn id(&self, label: &str) -> WidgetId {
+        let mut hasher = DefaultHasher::new();
+        self.layouts.last().map(|l| l.id).unwrap_or(0).hash(&mut hasher);
+        label.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Allocate the rectangle for the next widget in the current layout.
+    fn next_rect(&mut self) -> Rect {
+        let row_height = self.style.row_height;
+        let spacing = self.style.spacing;
+
+        let layout = self.layouts.last_mut().expect("root layout");
+        let rect = Rect::new(layout.x, layout.y, layout.width, row_height);
+        layout.y += row_height + spacing;
+        rect
+    }
+
+    /// Handle mouse interaction with a widget, returns `true` if it was clicked.
+    fn interact(&mut self, id: WidgetId, rect: Rect) -> bool {
+        let hovered = rect.contains(self.input.mouse);
+
+        if hovered {
+            self.hot = Some(id);
+
+            if self.input.mouse_pressed {
+                self.active = Some(id);
+                self.pressed_claimed = true;
+            }
+        }
+
+        self.active == Some(id) && self.input.mouse_released && hovered
+    }
+
+    fn widget_color(&self, id: WidgetId) -> Color {
+        if self.active == Some(id) {
+            self.style.active
+        } else if self.hot == Some(id) {
+            self.style.hot
+        } else {
+            self.style.widget
+        }
+    }
+
+    /// Draw text vertically centered in the given rectangle.
+    fn text(&mut self, rect: Rect, text: &str, align: Align) {
+        let font = match self.font {
+            Some(ref font) => font.clone(),
+            None => return,
+        };
+
+        let options = TextOptions {
+            color: self.style.text,
+            max_width: Some(rect.width),
+            align: align,
+        };
+
+        let y = rect.y + (rect.height - font.line_height) / 2.0;
+
+        if let Err(e) = font.draw(&mut self.overlay, rect.x, y, text, &options) {
+            warn!("failed to draw text: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(mouse: [f32; 2], down: bool, previous: &UiInput) -> UiInput {
+        let mut input = previous.clone();
+        input.next_tick();
+        input.mouse = mouse;
+        input.mouse_button(down);
+        input
+    }
+
+    /// Run a single tick with a panel containing a button, returns if it was clicked.
+    fn button_tick(ui: &mut Ui, input: UiInput) -> bool {
+        ui.begin(input);
+        ui.begin_panel("panel", Rect::new(0.0, 0.0, 100.0, 100.0));
+        let clicked = ui.button("button");
+        ui.end_panel();
+        ui.end(&mut Overlay::new());
+        clicked
+    }
+
+    #[test]
+    fn test_button_click() {
+        let mut ui = Ui::new();
+
+        let hover = input([20.0, 20.0], false, &UiInput::new());
+        assert!(!button_tick(&mut ui, hover.clone()));
+
+        let press = input([20.0, 20.0], true, &hover);
+        assert!(!button_tick(&mut ui, press.clone()));
+
+        let release = input([20.0, 20.0], false, &press);
+        assert!(button_tick(&mut ui, release));
+    }
+
+    #[test]
+    fn test_button_release_outside() {
+        let mut ui = Ui::new();
+
+        let press = input([20.0, 20.0], true, &UiInput::new());
+        assert!(!button_tick(&mut ui, press.clone()));
+
+        let release = input([200.0, 200.0], false, &press);
+        assert!(!button_tick(&mut ui, release));
+    }
+
+    #[test]
+    fn test_slider_drag() {
+        let mut ui = Ui::new();
+        let mut value = 0.0f32;
+
+        let press = input([8.0, 10.0], true, &UiInput::new());
+        let drag = input([58.0, 10.0], true, &press);
+
+        for input in vec![press, drag] {
+            ui.begin(input);
+            ui.slider("slider", &mut value, 0.0, 200.0);
+            ui.end(&mut Overlay::new());
+        }
+
+        assert_eq!(50.0, value);
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut ui = Ui::new();
+        let mut value = String::from("ab");
+
+        let press = input([10.0, 10.0], true, &UiInput::new());
+        let mut typing = input([10.0, 10.0], false, &press);
+        typing.type_char('c');
+        typing.type_char('d');
+        typing.press_key(UiKey::Backspace);
+
+        for input in vec![press, typing] {
+            ui.begin(input);
+            ui.text_input("input", &mut value);
+            ui.end(&mut Overlay::new());
+        }
+
+        assert_eq!("abc", value);
+        assert!(ui.wants_keyboard());
+
+        let elsewhere = input([500.0, 500.0], true, &UiInput::new());
+        ui.begin(elsewhere);
+        ui.text_input("input", &mut value);
+        ui.end(&mut Overlay::new());
+
+        assert!(!ui.wants_keyboard());
+    }
+
+    #[test]
+    fn test_text_input_order() {
+        let mut ui = Ui::new();
+        let mut value = String::from("ab");
+
+        let press = input([10.0, 10.0], true, &UiInput::new());
+        let mut typing = input([10.0, 10.0], false, &press);
+        typing.press_key(UiKey::Backspace);
+        typing.type_char('c');
+        typing.press_key(UiKey::Enter);
+        typing.type_char('d');
+
+        for input in vec![press, typing] {
+            ui.begin(input);
+            ui.text_input("input", &mut value);
+            ui.end(&mut Overlay::new());
+        }
+
+        // characters typed after leaving the input are not added.
+        assert_eq!("ac", value);
+        assert!(!ui.wants_keyboard());
+    }
+}